@@ -44,15 +44,21 @@ mod binary_matrix {
     #[bench]
     fn matrix_mul_slice(b: &mut Bencher) {
         let v = BinVector::random(500);
-        let v_stor = v
-            .get_storage()
-            .iter()
-            .map(|v| *v as u64)
-            .collect::<Vec<_>>();
-
         let m = BinMatrix::random(34, 128);
 
-        b.iter(|| m.mul_slice(&v_stor))
+        b.iter(|| m.mul_slice(v.as_words()))
+    }
+
+    #[bench]
+    fn row_ref_to_vector(b: &mut Bencher) {
+        let m = BinMatrix::random(1000, 1000);
+        b.iter(|| (0..1000).map(|i| m.row_ref(i).to_vector()).count())
+    }
+
+    #[bench]
+    fn from_rows_iter(b: &mut Bencher) {
+        let m = BinMatrix::random(1000, 1000);
+        b.iter(|| BinMatrix::from_rows_iter((0..1000).rev().map(|i| m.row_ref(i)), 1000))
     }
 
     macro_rules! multiply {
@@ -0,0 +1,423 @@
+//! Command-line tool for matrices over GF(2)
+//!
+//! Reads matrices as text (any style `BinMatrix` parses, including SageMath,
+//! Magma and MATLAB syntax), PBM images, Matrix Market files or the binary
+//! format of `BinMatrix::save`, detected from the contents. The output format
+//! is chosen with `--format`, or from the extension of the `--output` file.
+extern crate clap;
+extern crate m4ri_rust;
+
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::process;
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+use m4ri_rust::friendly::{BinMatrix, DisplayStyle, Entries, Gf2Matrix, PbmFormat};
+
+/// The formats in which matrices can be written
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Text(DisplayStyle),
+    Pbm(PbmFormat),
+    Binary,
+    MatrixMarket,
+}
+
+/// Names of the formats for `--format`
+const FORMATS: [&str; 10] = [
+    "text",
+    "dots",
+    "bits",
+    "sage",
+    "magma",
+    "matlab",
+    "pbm",
+    "plain-pbm",
+    "mtx",
+    "binary",
+];
+
+impl Format {
+    fn from_name(name: &str) -> Option<Format> {
+        Some(match name {
+            "text" => Format::Text(DisplayStyle::Brackets),
+            "dots" => Format::Text(DisplayStyle::Dots),
+            "bits" => Format::Text(DisplayStyle::Bits),
+            "sage" => Format::Text(DisplayStyle::Sage),
+            "magma" => Format::Text(DisplayStyle::Magma),
+            "matlab" => Format::Text(DisplayStyle::Matlab),
+            "pbm" => Format::Pbm(PbmFormat::Raw),
+            "plain-pbm" => Format::Pbm(PbmFormat::Plain),
+            "mtx" => Format::MatrixMarket,
+            "binary" => Format::Binary,
+            _ => return None,
+        })
+    }
+
+    /// The format that belongs to the extension of `path`, text by default
+    fn from_path(path: &str) -> Format {
+        match path.rsplit('.').next() {
+            Some("pbm") => Format::Pbm(PbmFormat::Raw),
+            Some("mtx") => Format::MatrixMarket,
+            Some("m4ri") => Format::Binary,
+            _ => Format::Text(DisplayStyle::Brackets),
+        }
+    }
+}
+
+/// Parse a matrix in any of the supported input formats
+fn parse_matrix(data: &[u8]) -> Result<BinMatrix, String> {
+    let result = if data.starts_with(b"M4RIMAT\0") {
+        BinMatrix::read_binary(data)
+    } else if data.starts_with(b"P1") || data.starts_with(b"P4") {
+        BinMatrix::read_pbm(data)
+    } else if data.starts_with(b"%%MatrixMarket") {
+        BinMatrix::read_matrix_market(data)
+    } else {
+        let text = std::str::from_utf8(data).map_err(|_| "unknown matrix format".to_string())?;
+        return text.parse().map_err(|e| format!("{}", e));
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Read a matrix from a file, or from standard input if `path` is `-`
+fn read_matrix(path: &str) -> Result<BinMatrix, String> {
+    let mut data = Vec::new();
+    let result = if path == "-" {
+        io::stdin().read_to_end(&mut data)
+    } else {
+        File::open(path).and_then(|mut file| file.read_to_end(&mut data))
+    };
+    result.map_err(|e| format!("{}: {}", path, e))?;
+    parse_matrix(&data).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn write_matrix_to<W: Write>(matrix: &BinMatrix, mut writer: W, format: Format) -> io::Result<()> {
+    match format {
+        Format::Text(style) => {
+            writeln!(writer, "{}", matrix.display(style))?;
+            writer.flush()
+        }
+        Format::Pbm(format) => matrix.write_pbm(writer, format),
+        Format::MatrixMarket => matrix.write_matrix_market(writer, Entries::NonZero),
+        Format::Binary => matrix.write_binary(writer),
+    }
+}
+
+/// Write the result to `--output` in `--format`
+fn write_matrix(matrix: &BinMatrix, args: &ArgMatches) -> Result<(), String> {
+    let output = args.get_one::<String>("output").map(String::as_str);
+    let format = match (args.get_one::<String>("format"), output) {
+        (Some(name), _) => Format::from_name(name).expect("clap checks the format names"),
+        (None, Some(path)) => Format::from_path(path),
+        (None, None) => Format::Text(DisplayStyle::Brackets),
+    };
+    // Rows of bits can't show the dimensions of a matrix without rows or columns
+    let format = match format {
+        Format::Text(DisplayStyle::Brackets | DisplayStyle::Dots | DisplayStyle::Bits)
+            if matrix.nrows() == 0 || matrix.ncols() == 0 =>
+        {
+            if let Some(name) = args.get_one::<String>("format") {
+                return Err(format!(
+                    "a {}x{} matrix can't be written as {}, use sage, magma, matlab or a file format",
+                    matrix.nrows(),
+                    matrix.ncols(),
+                    name
+                ));
+            }
+            Format::Text(DisplayStyle::Magma)
+        }
+        format => format,
+    };
+    let result = match output {
+        None | Some("-") => write_matrix_to(matrix, io::stdout().lock(), format),
+        Some(path) => File::create(path).and_then(|file| write_matrix_to(matrix, file, format)),
+    };
+    result.map_err(|e| format!("{}: {}", output.unwrap_or("-"), e))
+}
+
+fn input(args: &ArgMatches, name: &str) -> Result<BinMatrix, String> {
+    read_matrix(args.get_one::<String>(name).expect("required argument"))
+}
+
+fn run(args: &ArgMatches) -> Result<(), String> {
+    let (command, args) = args.subcommand().expect("clap requires a subcommand");
+    let result = match command {
+        "rank" => {
+            println!("{}", input(args, "matrix")?.rank());
+            return Ok(());
+        }
+        "echelon" => {
+            let mut matrix = input(args, "matrix")?;
+            matrix.echelonize();
+            matrix
+        }
+        "inverse" => input(args, "matrix")?
+            .try_inverted()
+            .map_err(|e| e.to_string())?,
+        "solve" => {
+            let a = input(args, "a")?;
+            let b = input(args, "b")?;
+            if b.nrows() != 1 && b.ncols() != 1 {
+                return Err("the right-hand side needs to be a single row or column".to_string());
+            }
+            let b = b.as_vector();
+            if b.len() != a.nrows() {
+                return Err(format!(
+                    "the right-hand side has {} entries, but the matrix has {} rows",
+                    b.len(),
+                    a.nrows()
+                ));
+            }
+            Gf2Matrix::solve(&a, &b)
+                .ok_or_else(|| "the system has no solution".to_string())?
+                .as_column_matrix()
+        }
+        "kernel" => {
+            let matrix = input(args, "matrix")?;
+            let basis = Gf2Matrix::kernel(&matrix);
+            BinMatrix::from_slices(&basis, matrix.ncols())
+        }
+        "mul" => input(args, "a")?
+            .try_mul(&input(args, "b")?)
+            .map_err(|e| e.to_string())?,
+        "transpose" => input(args, "matrix")?.transposed(),
+        "random" => {
+            let nrows = *args.get_one::<usize>("rows").expect("required argument");
+            let ncols = *args.get_one::<usize>("cols").expect("required argument");
+            BinMatrix::try_random(nrows, ncols).map_err(|e| e.to_string())?
+        }
+        "convert" => input(args, "matrix")?,
+        _ => unreachable!("clap only accepts known subcommands"),
+    };
+    write_matrix(&result, args)
+}
+
+fn command() -> Command {
+    let matrix = || {
+        Arg::new("matrix")
+            .required(true)
+            .help("File with the matrix, or - for standard input")
+    };
+    let output_args = [
+        Arg::new("output")
+            .short('o')
+            .long("output")
+            .help("Write the result to this file instead of standard output"),
+        Arg::new("format")
+            .short('f')
+            .long("format")
+            .value_parser(FORMATS)
+            .help(
+                "Output format [default: from the extension of --output, or text, \
+                 which becomes magma for matrices without rows or columns]",
+            ),
+    ];
+    let with_output = |command: Command| command.args(output_args.clone());
+
+    Command::new("m4ri")
+        .about("Linear algebra over GF(2) on matrix files")
+        .long_about(
+            "Linear algebra over GF(2) on matrix files.\n\n\
+             Input matrices can be text (rows like `[1 0 1]` or `101`, or SageMath, Magma or \
+             MATLAB syntax), PBM images, Matrix Market files or the binary format of \
+             BinMatrix::save. The format is detected from the contents.",
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("rank")
+                .about("Print the rank of a matrix")
+                .arg(matrix()),
+        )
+        .subcommand(with_output(
+            Command::new("echelon")
+                .about("Bring a matrix in row echelon form")
+                .arg(matrix()),
+        ))
+        .subcommand(with_output(
+            Command::new("inverse")
+                .about("Invert a square matrix")
+                .arg(matrix()),
+        ))
+        .subcommand(with_output(
+            Command::new("solve")
+                .about("Solve A x = b, printing x as a column with free variables set to zero")
+                .arg(Arg::new("a").required(true).help("File with the matrix A"))
+                .arg(
+                    Arg::new("b")
+                        .required(true)
+                        .help("File with b, as a single row or column"),
+                ),
+        ))
+        .subcommand(with_output(
+            Command::new("kernel")
+                .about("Print a basis of the right kernel as the rows of a matrix")
+                .arg(matrix()),
+        ))
+        .subcommand(with_output(
+            Command::new("mul")
+                .about("Multiply two matrices")
+                .arg(
+                    Arg::new("a")
+                        .required(true)
+                        .help("File with the left matrix"),
+                )
+                .arg(
+                    Arg::new("b")
+                        .required(true)
+                        .help("File with the right matrix"),
+                ),
+        ))
+        .subcommand(with_output(
+            Command::new("transpose")
+                .about("Transpose a matrix")
+                .arg(matrix()),
+        ))
+        .subcommand(with_output(
+            Command::new("random")
+                .about("Generate a uniformly random matrix")
+                .arg(
+                    Arg::new("rows")
+                        .required(true)
+                        .value_parser(value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("cols")
+                        .required(true)
+                        .value_parser(value_parser!(usize)),
+                ),
+        ))
+        .subcommand(with_output(
+            Command::new("convert")
+                .about("Convert a matrix to another format")
+                .arg(matrix()),
+        ))
+}
+
+fn main() {
+    if let Err(message) = run(&command().get_matches()) {
+        eprintln!("m4ri: {}", message);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use m4ri_rust::friendly::BinVector;
+
+    fn run_args(args: &[&str]) -> Result<(), String> {
+        run(&command().try_get_matches_from(args).unwrap())
+    }
+
+    #[test]
+    fn command_is_valid() {
+        command().debug_assert();
+    }
+
+    #[test]
+    fn detect_formats() {
+        let m = BinMatrix::random(10, 70);
+        for &name in &FORMATS {
+            let format = Format::from_name(name).unwrap();
+            let mut data = Vec::new();
+            write_matrix_to(&m, &mut data, format).unwrap();
+            assert_eq!(parse_matrix(&data), Ok(m.clone()), "{}", name);
+        }
+        assert!(parse_matrix(b"10x").is_err());
+        assert!(parse_matrix(b"\xff\xfe").is_err());
+    }
+
+    #[test]
+    fn output_format_from_path() {
+        assert_eq!(Format::from_path("a.pbm"), Format::Pbm(PbmFormat::Raw));
+        assert_eq!(Format::from_path("dir.d/a.mtx"), Format::MatrixMarket);
+        assert_eq!(Format::from_path("a.m4ri"), Format::Binary);
+        assert_eq!(
+            Format::from_path("a.txt"),
+            Format::Text(DisplayStyle::Brackets)
+        );
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn subcommands() {
+        let dir = std::env::temp_dir().join(format!("m4ri-cli-test-{}", process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+        std::fs::write(path("a.txt"), "110; 011").unwrap();
+        std::fs::write(path("b.txt"), "1; 1").unwrap();
+        run_args(&["m4ri", "kernel", &path("a.txt"), "-o", &path("kernel.m4ri")]).unwrap();
+        let kernel = BinMatrix::load(path("kernel.m4ri")).unwrap();
+        assert_eq!(kernel, "111".parse().unwrap());
+
+        run_args(&[
+            "m4ri",
+            "solve",
+            &path("a.txt"),
+            &path("b.txt"),
+            "-o",
+            &path("x.pbm"),
+        ])
+        .unwrap();
+        let x = read_matrix(&path("x.pbm")).unwrap();
+        let a = read_matrix(&path("a.txt")).unwrap();
+        assert_eq!(&a * &x.as_vector(), BinVector::from_bools(&[true, true]));
+
+        run_args(&["m4ri", "random", "5", "70", "-o", &path("r.mtx")]).unwrap();
+        run_args(&[
+            "m4ri",
+            "convert",
+            &path("r.mtx"),
+            "-f",
+            "sage",
+            "-o",
+            &path("r.sage"),
+        ])
+        .unwrap();
+        run_args(&["m4ri", "transpose", &path("r.sage"), "-o", &path("t.txt")]).unwrap();
+        let r = read_matrix(&path("r.mtx")).unwrap();
+        assert_eq!(read_matrix(&path("r.sage")).unwrap(), r);
+        assert_eq!(read_matrix(&path("t.txt")).unwrap(), r.transposed());
+
+        // The kernel of a full-rank matrix has no rows, but keeps its columns
+        std::fs::write(path("full.txt"), "10; 01").unwrap();
+        run_args(&["m4ri", "kernel", &path("full.txt"), "-o", &path("k.txt")]).unwrap();
+        assert_eq!(read_matrix(&path("k.txt")).unwrap(), BinMatrix::zero(0, 2));
+        run_args(&["m4ri", "convert", &path("k.txt"), "-o", &path("k.pbm")]).unwrap();
+        run_args(&["m4ri", "transpose", &path("k.pbm"), "-o", &path("t.pbm")]).unwrap();
+        run_args(&["m4ri", "echelon", &path("t.pbm"), "-o", &path("e.mtx")]).unwrap();
+        assert_eq!(read_matrix(&path("e.mtx")).unwrap(), BinMatrix::zero(2, 0));
+        run_args(&["m4ri", "rank", &path("t.pbm")]).unwrap();
+        let explicit = run_args(&["m4ri", "convert", &path("k.txt"), "-f", "bits"]);
+        assert_eq!(
+            explicit,
+            Err(
+                "a 0x2 matrix can't be written as bits, use sage, magma, matlab or a file format"
+                    .to_string()
+            )
+        );
+        run_args(&[
+            "m4ri",
+            "convert",
+            &path("k.txt"),
+            "-f",
+            "matlab",
+            "-o",
+            &path("k.m"),
+        ])
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(path("k.m")).unwrap(),
+            "zeros(0, 2)\n"
+        );
+
+        assert!(run_args(&["m4ri", "inverse", &path("a.txt")]).is_err());
+        assert!(run_args(&["m4ri", "mul", &path("a.txt"), &path("a.txt")]).is_err());
+        assert!(run_args(&["m4ri", "rank", &path("missing.txt")]).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,469 @@
+//! Covering codes built from smaller codes
+use std::fmt;
+
+use super::{subvector, CoveringCode, LinearCode};
+use friendly::{BinMatrix, BinVector};
+
+/// Place the rows of every block next to each other, with zeros elsewhere
+fn block_diagonal(blocks: &[BinMatrix]) -> BinMatrix {
+    let length: usize = blocks.iter().map(BinMatrix::ncols).sum();
+    let mut rows = Vec::new();
+    let mut offset = 0;
+    for block in blocks {
+        for row in block.rows() {
+            let mut padded = BinVector::from_elem(offset, false);
+            padded.extend_from_binvec(&row.to_vector());
+            padded.extend_from_binvec(&BinVector::from_elem(length - padded.len(), false));
+            rows.push(padded);
+        }
+        offset += block.ncols();
+    }
+    BinMatrix::from_slices(&rows, length)
+}
+
+/// Split `vector` into blocks of the given lengths, apply `f` to each and join the results
+fn map_blocks<I, F>(vector: &BinVector, lengths: I, mut f: F) -> BinVector
+where
+    I: IntoIterator<Item = usize>,
+    F: FnMut(usize, &BinVector) -> BinVector,
+{
+    let mut result = BinVector::new();
+    let mut start = 0;
+    for (index, len) in lengths.into_iter().enumerate() {
+        result.extend_from_binvec(&f(index, &subvector(vector, start, len)));
+        start += len;
+    }
+    result
+}
+
+/// The direct sum of two codes: the codewords `(a | b)` for codewords `a` and `b`
+///
+/// Decoding decodes both halves separately, and the covering radius is the
+/// sum of the covering radii.
+#[derive(Clone, Debug)]
+pub struct DirectSum<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: CoveringCode, B: CoveringCode> DirectSum<A, B> {
+    /// Create the direct sum of `first` and `second`
+    pub fn new(first: A, second: B) -> DirectSum<A, B> {
+        DirectSum { first, second }
+    }
+
+    /// The code on the first coordinates
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// The code on the last coordinates
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+}
+
+impl<A: CoveringCode, B: CoveringCode> CoveringCode for DirectSum<A, B> {
+    fn length(&self) -> usize {
+        self.first.length() + self.second.length()
+    }
+
+    fn dimension(&self) -> usize {
+        self.first.dimension() + self.second.dimension()
+    }
+
+    fn generator(&self) -> BinMatrix {
+        block_diagonal(&[self.first.generator(), self.second.generator()])
+    }
+
+    fn encode(&self, message: &BinVector) -> BinVector {
+        assert_eq!(message.len(), self.dimension(), "Mismatched sizes");
+        let dimensions = [self.first.dimension(), self.second.dimension()];
+        map_blocks(message, dimensions.iter().cloned(), |index, part| {
+            if index == 0 {
+                self.first.encode(part)
+            } else {
+                self.second.encode(part)
+            }
+        })
+    }
+
+    fn decode(&self, word: &BinVector) -> BinVector {
+        assert_eq!(word.len(), self.length(), "Mismatched sizes");
+        let lengths = [self.first.length(), self.second.length()];
+        map_blocks(word, lengths.iter().cloned(), |index, part| {
+            if index == 0 {
+                self.first.decode(part)
+            } else {
+                self.second.decode(part)
+            }
+        })
+    }
+
+    fn covering_radius(&self) -> usize {
+        self.first.covering_radius() + self.second.covering_radius()
+    }
+}
+
+/// Codes on consecutive blocks of coordinates
+///
+/// This is the direct sum of any number of codes, which is how the large
+/// covering codes of LPN attacks are usually built from small perfect codes.
+pub struct Concatenated {
+    codes: Vec<Box<dyn CoveringCode>>,
+}
+
+impl Concatenated {
+    /// Put the codes next to each other, in this order
+    pub fn new(codes: Vec<Box<dyn CoveringCode>>) -> Concatenated {
+        Concatenated { codes }
+    }
+
+    /// Concatenate `copies` copies of `code`
+    pub fn repeated<C: CoveringCode + Clone + 'static>(code: &C, copies: usize) -> Concatenated {
+        Concatenated::new(
+            (0..copies)
+                .map(|_| Box::new(code.clone()) as Box<dyn CoveringCode>)
+                .collect(),
+        )
+    }
+
+    /// The codes on the blocks
+    pub fn codes(&self) -> &[Box<dyn CoveringCode>] {
+        &self.codes
+    }
+}
+
+impl fmt::Debug for Concatenated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parameters: Vec<(usize, usize)> = self
+            .codes
+            .iter()
+            .map(|code| (code.length(), code.dimension()))
+            .collect();
+        f.debug_struct("Concatenated")
+            .field("codes", &parameters)
+            .finish()
+    }
+}
+
+impl CoveringCode for Concatenated {
+    fn length(&self) -> usize {
+        self.codes.iter().map(|code| code.length()).sum()
+    }
+
+    fn dimension(&self) -> usize {
+        self.codes.iter().map(|code| code.dimension()).sum()
+    }
+
+    fn generator(&self) -> BinMatrix {
+        let blocks: Vec<BinMatrix> = self.codes.iter().map(|code| code.generator()).collect();
+        block_diagonal(&blocks)
+    }
+
+    fn encode(&self, message: &BinVector) -> BinVector {
+        assert_eq!(message.len(), self.dimension(), "Mismatched sizes");
+        let dimensions = self.codes.iter().map(|code| code.dimension());
+        map_blocks(message, dimensions, |index, part| {
+            self.codes[index].encode(part)
+        })
+    }
+
+    fn decode(&self, word: &BinVector) -> BinVector {
+        assert_eq!(word.len(), self.length(), "Mismatched sizes");
+        let lengths = self.codes.iter().map(|code| code.length());
+        map_blocks(word, lengths, |index, part| self.codes[index].decode(part))
+    }
+
+    fn covering_radius(&self) -> usize {
+        self.codes.iter().map(|code| code.covering_radius()).sum()
+    }
+}
+
+/// Split a code in the subcode with a zero at `col` and a codeword with a one there
+///
+/// **Panics** if all codewords are zero at `col`
+fn split_at(code: &LinearCode, col: usize) -> (LinearCode, u64) {
+    let rows = code.generator_words();
+    let offset = *rows
+        .iter()
+        .find(|&&row| row >> col & 1 == 1)
+        .expect("The shared coordinate needs to be one in some codeword");
+    let subcode_rows = rows
+        .iter()
+        .filter(|&&row| row != offset)
+        .map(|&row| {
+            if row >> col & 1 == 1 {
+                row ^ offset
+            } else {
+                row
+            }
+        })
+        .collect();
+    (LinearCode::from_words(subcode_rows, code.length()), offset)
+}
+
+/// The amalgamated direct sum of Graham and Sloane
+///
+/// The last coordinate of the first code is shared with the first coordinate
+/// of the second code: the codewords are `(a, x, b)` with `(a, x)` in the
+/// first code and `(x, b)` in the second. For codes of length `n1` and `n2`
+/// and dimension `k1` and `k2` this gives a `[n1 + n2 - 1, k1 + k2 - 1]`
+/// code. If both codes are normal, its covering radius is at most the sum
+/// of their covering radii.
+///
+/// Messages are the message of `(a, x)` in the subcode of the first code
+/// with `x = 0`, then `x`, then the message of `(x, b)` in the subcode of
+/// the second code.
+#[derive(Clone, Debug)]
+pub struct Amalgamated {
+    first: LinearCode,
+    second: LinearCode,
+    /// The subcode of the first code that is zero at the shared coordinate
+    first_zero: LinearCode,
+    /// A codeword of the first code that is one at the shared coordinate
+    first_offset: u64,
+    /// The subcode of the second code that is zero at the shared coordinate
+    second_zero: LinearCode,
+    /// A codeword of the second code that is one at the shared coordinate
+    second_offset: u64,
+}
+
+impl Amalgamated {
+    /// Amalgamate the last coordinate of `first` with the first coordinate of `second`
+    ///
+    /// **Panics** if those coordinates are zero in all codewords, or if the
+    /// redundancy of a code is `MAX_REDUNDANCY`
+    pub fn new(first: &LinearCode, second: &LinearCode) -> Amalgamated {
+        assert!(
+            first.length() > 0 && second.length() > 0,
+            "Can't amalgamate empty codes"
+        );
+        let (first_zero, first_offset) = split_at(first, first.length() - 1);
+        let (second_zero, second_offset) = split_at(second, 0);
+        Amalgamated {
+            first: first.clone(),
+            second: second.clone(),
+            first_zero,
+            first_offset,
+            second_zero,
+            second_offset,
+        }
+    }
+
+    /// The code on the first coordinates, including the shared one
+    pub fn first(&self) -> &LinearCode {
+        &self.first
+    }
+
+    /// The code on the last coordinates, including the shared one
+    pub fn second(&self) -> &LinearCode {
+        &self.second
+    }
+
+    /// The sum of the covering radii of the two codes
+    ///
+    /// This bounds the covering radius if both codes are normal.
+    pub fn covering_radius_bound(&self) -> usize {
+        self.first.covering_radius() + self.second.covering_radius()
+    }
+
+    /// The position of the shared coordinate
+    fn shared(&self) -> usize {
+        self.first.length() - 1
+    }
+
+    /// Join codewords of both codes that agree on the shared coordinate
+    fn join(&self, first: u64, second: u64) -> BinVector {
+        let mut word = BinVector::from_words(&[first], self.first.length());
+        word.extend_from_binvec(&BinVector::from_words(
+            &[second >> 1],
+            self.second.length() - 1,
+        ));
+        word
+    }
+}
+
+impl CoveringCode for Amalgamated {
+    fn length(&self) -> usize {
+        self.first.length() + self.second.length() - 1
+    }
+
+    fn dimension(&self) -> usize {
+        self.first.dimension() + self.second.dimension() - 1
+    }
+
+    fn generator(&self) -> BinMatrix {
+        let mut rows: Vec<BinVector> = self
+            .first_zero
+            .generator_words()
+            .iter()
+            .map(|&row| self.join(row, 0))
+            .collect();
+        rows.push(self.join(self.first_offset, self.second_offset));
+        rows.extend(
+            self.second_zero
+                .generator_words()
+                .iter()
+                .map(|&row| self.join(0, row)),
+        );
+        BinMatrix::from_slices(&rows, self.length())
+    }
+
+    fn encode(&self, message: &BinVector) -> BinVector {
+        assert_eq!(message.len(), self.dimension(), "Mismatched sizes");
+        let first_dimension = self.first_zero.dimension();
+        let shared = message[first_dimension];
+        let mut first = self
+            .first_zero
+            .encode_word(subvector(message, 0, first_dimension).as_u64());
+        let mut second = self.second_zero.encode_word(
+            subvector(message, first_dimension + 1, self.second_zero.dimension()).as_u64(),
+        );
+        if shared {
+            first ^= self.first_offset;
+            second ^= self.second_offset;
+        }
+        self.join(first, second)
+    }
+
+    /// Try both values of the shared coordinate and decode the two parts in the matching coset
+    fn decode(&self, word: &BinVector) -> BinVector {
+        assert_eq!(word.len(), self.length(), "Mismatched sizes");
+        let shared = self.shared();
+        let first_word = subvector(word, 0, shared).as_u64();
+        let second_word = subvector(word, shared + 1, self.second.length() - 1).as_u64() << 1;
+        let nearest = |zero: &LinearCode, offset: u64, word: u64| {
+            let nearest = zero.nearest_word(word ^ offset);
+            (nearest, (nearest ^ word ^ offset).count_ones())
+        };
+        let (bit, first, second) = [false, true]
+            .iter()
+            .map(|&bit| {
+                let (first_offset, second_offset) = if bit {
+                    (self.first_offset, self.second_offset)
+                } else {
+                    (0, 0)
+                };
+                let first_word = first_word | u64::from(bit) << shared;
+                let second_word = second_word | u64::from(bit);
+                let (first, first_distance) = nearest(&self.first_zero, first_offset, first_word);
+                let (second, second_distance) =
+                    nearest(&self.second_zero, second_offset, second_word);
+                let distance = first_distance + second_distance + u32::from(bit != word[shared]);
+                (distance, bit, first, second)
+            })
+            .min_by_key(|&(distance, ..)| distance)
+            .map(|(_, bit, first, second)| (bit, first, second))
+            .unwrap();
+
+        let mut message = BinVector::from_words(
+            &[self.first_zero.message_word(first)],
+            self.first_zero.dimension(),
+        );
+        message.push(bit);
+        message.extend_from_binvec(&BinVector::from_words(
+            &[self.second_zero.message_word(second)],
+            self.second_zero.dimension(),
+        ));
+        message
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codes::covering_radius;
+
+    /// All codewords of a small code
+    fn codewords<C: CoveringCode>(code: &C) -> Vec<BinVector> {
+        let k = code.dimension();
+        (0..1u64 << k)
+            .map(|message| code.encode(&BinVector::from_words(&[message], k)))
+            .collect()
+    }
+
+    /// Check that every word decodes to a nearest codeword, and return the covering radius
+    fn check_exhaustively<C: CoveringCode>(code: &C) -> usize {
+        let n = code.length();
+        let codewords = codewords(code);
+        let mut radius = 0;
+        for word in 0..1u64 << n {
+            let word = BinVector::from_words(&[word], n);
+            let best = codewords
+                .iter()
+                .map(|c| (c + &word).count_ones())
+                .min()
+                .unwrap();
+            let nearest = code.nearest_codeword(&word);
+            assert_eq!((&nearest + &word).count_ones(), best, "{:?}", word);
+            assert_eq!(code.encode(&code.decode(&nearest)), nearest);
+            radius = radius.max(best as usize);
+        }
+        radius
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn direct_sum() {
+        let code = DirectSum::new(LinearCode::hamming(3), LinearCode::repetition(3));
+        assert_eq!((code.length(), code.dimension()), (10, 5));
+        assert_eq!(code.covering_radius(), 2);
+        assert_eq!(check_exhaustively(&code), 2);
+        assert_eq!(covering_radius(&code.generator()), 2);
+        assert_eq!(code.generator().rank(), 5);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn concatenated() {
+        let code = Concatenated::repeated(&LinearCode::repetition(3), 3);
+        assert_eq!((code.length(), code.dimension()), (9, 3));
+        assert_eq!(check_exhaustively(&code), 3);
+        assert_eq!(code.covering_radius(), 3);
+
+        let large = Concatenated::new(vec![
+            Box::new(LinearCode::golay()),
+            Box::new(LinearCode::hamming(6)),
+            Box::new(DirectSum::new(
+                LinearCode::repetition(5),
+                LinearCode::extended_golay(),
+            )),
+        ]);
+        assert_eq!((large.length(), large.dimension()), (115, 82));
+        assert_eq!(large.covering_radius(), 3 + 1 + 2 + 4);
+        for _ in 0..100 {
+            let word = BinVector::random(115);
+            let message = large.decode(&word);
+            assert_eq!(message.len(), 82);
+            assert!((&large.encode(&message) + &word).count_ones() <= 10);
+        }
+        assert_eq!(large.generator().rank(), 82);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn amalgamated() {
+        let hamming = LinearCode::hamming(3);
+        let code = Amalgamated::new(&hamming, &hamming);
+        assert_eq!((code.length(), code.dimension()), (13, 7));
+        let radius = check_exhaustively(&code);
+        assert_eq!(code.covering_radius(), radius);
+        assert!(radius <= code.covering_radius_bound());
+        assert_eq!(code.generator().rank(), 7);
+
+        let mixed = Amalgamated::new(&LinearCode::repetition(3), &LinearCode::hamming(3));
+        assert_eq!((mixed.length(), mixed.dimension()), (9, 4));
+        assert_eq!(mixed.covering_radius(), check_exhaustively(&mixed));
+        let message = BinVector::from_bools(&[true, false, true, true]);
+        assert_eq!(mixed.decode(&mixed.encode(&message)), message);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    #[should_panic(expected = "shared coordinate")]
+    fn amalgamated_needs_shared_coordinate() {
+        let code = LinearCode::new(&"110".parse().unwrap());
+        Amalgamated::new(&code, &LinearCode::repetition(3));
+    }
+}
@@ -0,0 +1,327 @@
+//! Small linear codes decoded with a table of coset leaders
+use super::{check_redundancy, coset_leaders, CoveringCode};
+use friendly::{BinMatrix, BinVector};
+
+/// Generator polynomial of the binary Golay code, `x^11 + x^10 + x^6 + x^5 + x^4 + x^2 + 1`
+const GOLAY_POLYNOMIAL: u64 = 0b1100_0111_0101;
+
+/// A linear code of length at most 64 with syndrome decoding
+///
+/// Decoding looks up a coset leader of minimal weight for the syndrome of
+/// the word, so it always finds a nearest codeword. The table has
+/// `2^(n - k)` entries, which limits the redundancy to `MAX_REDUNDANCY`.
+///
+/// The generator is kept in reduced row echelon form, so the message of a
+/// codeword consists of its bits at the pivot columns.
+#[derive(Clone, Debug)]
+pub struct LinearCode {
+    length: usize,
+    /// Rows of the generator in reduced row echelon form
+    rows: Vec<u64>,
+    /// Pivot column of every row
+    pivots: Vec<usize>,
+    /// Columns of the parity-check matrix
+    columns: Vec<u32>,
+    /// For every syndrome the last column of its coset leader, plus one
+    last_columns: Vec<u16>,
+    covering_radius: usize,
+}
+
+impl LinearCode {
+    /// Create the code spanned by the rows of `generator`
+    ///
+    /// The rows don't need to be independent.
+    ///
+    /// **Panics** if the code is longer than 64 bits or if its redundancy is
+    /// larger than `MAX_REDUNDANCY`
+    pub fn new(generator: &BinMatrix) -> LinearCode {
+        let length = generator.ncols();
+        assert!(
+            length <= 64,
+            "Codes can be at most 64 bits long, got {}",
+            length
+        );
+        let rows = generator
+            .rows()
+            .map(|row| row.masked_words().next().unwrap_or(0))
+            .collect();
+        LinearCode::from_words(rows, length)
+    }
+
+    /// Create the code spanned by `rows`, with bit `i` of a word as coordinate `i`
+    pub(super) fn from_words(mut rows: Vec<u64>, length: usize) -> LinearCode {
+        let mut pivots = Vec::new();
+        for col in 0..length {
+            let rank = pivots.len();
+            if let Some(pivot) = (rank..rows.len()).find(|&row| rows[row] >> col & 1 == 1) {
+                rows.swap(rank, pivot);
+                let pivot_row = rows[rank];
+                for (index, row) in rows.iter_mut().enumerate() {
+                    if index != rank && *row >> col & 1 == 1 {
+                        *row ^= pivot_row;
+                    }
+                }
+                pivots.push(col);
+            }
+        }
+        rows.truncate(pivots.len());
+
+        // Every free column gives a parity check: itself plus its entries in the pivot columns
+        let mut is_pivot = vec![false; length];
+        for &col in &pivots {
+            is_pivot[col] = true;
+        }
+        let free: Vec<usize> = (0..length).filter(|&col| !is_pivot[col]).collect();
+        check_redundancy(free.len());
+        let mut columns = vec![0u32; length];
+        for (check, &free_col) in free.iter().enumerate() {
+            columns[free_col] |= 1 << check;
+            for (row, &pivot) in rows.iter().zip(&pivots) {
+                columns[pivot] |= ((row >> free_col & 1) as u32) << check;
+            }
+        }
+        let (last_columns, weights) = coset_leaders(&columns, free.len());
+        let covering_radius = weights.into_iter().max().map_or(0, usize::from);
+        LinearCode {
+            length,
+            rows,
+            pivots,
+            columns,
+            last_columns,
+            covering_radius,
+        }
+    }
+
+    /// The `[2^r - 1, 2^r - 1 - r]` Hamming code, a perfect code with covering radius 1
+    ///
+    /// **Panics** if `r` is not between 2 and 6
+    pub fn hamming(r: usize) -> LinearCode {
+        assert!(
+            (2..=6).contains(&r),
+            "Hamming codes need 2 <= r <= 6, got {}",
+            r
+        );
+        let length = (1 << r) - 1;
+        // The parity-check columns are the numbers 1..2^r, the generator is its kernel
+        let rows = (1..=length)
+            .filter(|&col: &usize| !col.is_power_of_two())
+            .map(|col| {
+                let checks = (0..r)
+                    .filter(|&bit| col >> bit & 1 == 1)
+                    .fold(0u64, |word, bit| word | 1 << ((1 << bit) - 1));
+                checks | 1 << (col - 1)
+            })
+            .collect();
+        LinearCode::from_words(rows, length)
+    }
+
+    /// The `[23, 12]` binary Golay code, a perfect code with covering radius 3
+    pub fn golay() -> LinearCode {
+        let rows = (0..12).map(|shift| GOLAY_POLYNOMIAL << shift).collect();
+        LinearCode::from_words(rows, 23)
+    }
+
+    /// The `[24, 12]` extended Golay code, with covering radius 4
+    pub fn extended_golay() -> LinearCode {
+        let rows = (0..12)
+            .map(|shift| {
+                let word = GOLAY_POLYNOMIAL << shift;
+                word | u64::from(word.count_ones() % 2) << 23
+            })
+            .collect();
+        LinearCode::from_words(rows, 24)
+    }
+
+    /// The `[n, 1]` repetition code, with covering radius `n / 2`
+    ///
+    /// **Panics** if `n` is zero or larger than `MAX_REDUNDANCY + 1`
+    pub fn repetition(n: usize) -> LinearCode {
+        assert!(n > 0, "Repetition codes need at least one bit");
+        // Before the shift, which needs n <= 64
+        check_redundancy(n - 1);
+        LinearCode::from_words(vec![u64::MAX >> (64 - n)], n)
+    }
+
+    /// The parity-check matrix, with a row for every non-pivot column of the generator
+    pub fn parity_check(&self) -> BinMatrix {
+        let rows: Vec<Vec<u64>> = (0..self.length - self.rows.len())
+            .map(|check| {
+                let word = self
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .fold(0u64, |word, (col, &column)| {
+                        word | u64::from(column >> check & 1) << col
+                    });
+                vec![word]
+            })
+            .collect();
+        BinMatrix::from_slices(&rows, self.length)
+    }
+
+    /// The syndrome of a word
+    fn syndrome(&self, word: u64) -> u32 {
+        self.columns
+            .iter()
+            .enumerate()
+            .filter(|&(col, _)| word >> col & 1 == 1)
+            .fold(0, |syndrome, (_, &column)| syndrome ^ column)
+    }
+
+    /// A codeword closest to `word`
+    pub(super) fn nearest_word(&self, word: u64) -> u64 {
+        let mut syndrome = self.syndrome(word);
+        let mut codeword = word;
+        while syndrome != 0 {
+            let col = usize::from(self.last_columns[syndrome as usize] - 1);
+            codeword ^= 1 << col;
+            syndrome ^= self.columns[col];
+        }
+        codeword
+    }
+
+    /// The message of a codeword
+    pub(super) fn message_word(&self, codeword: u64) -> u64 {
+        self.pivots
+            .iter()
+            .enumerate()
+            .fold(0, |message, (i, &col)| message | (codeword >> col & 1) << i)
+    }
+
+    /// The codeword of a message
+    pub(super) fn encode_word(&self, message: u64) -> u64 {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| message >> i & 1 == 1)
+            .fold(0, |codeword, (_, &row)| codeword ^ row)
+    }
+
+    /// The generator rows in reduced row echelon form
+    pub(super) fn generator_words(&self) -> &[u64] {
+        &self.rows
+    }
+}
+
+impl CoveringCode for LinearCode {
+    fn length(&self) -> usize {
+        self.length
+    }
+
+    fn dimension(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn generator(&self) -> BinMatrix {
+        let rows: Vec<[u64; 1]> = self.rows.iter().map(|&row| [row]).collect();
+        BinMatrix::from_slices(&rows, self.length)
+    }
+
+    fn encode(&self, message: &BinVector) -> BinVector {
+        assert_eq!(message.len(), self.dimension(), "Mismatched sizes");
+        BinVector::from_words(&[self.encode_word(message.as_u64())], self.length)
+    }
+
+    fn decode(&self, word: &BinVector) -> BinVector {
+        assert_eq!(word.len(), self.length, "Mismatched sizes");
+        let message = self.message_word(self.nearest_word(word.as_u64()));
+        BinVector::from_words(&[message], self.dimension())
+    }
+
+    fn nearest_codeword(&self, word: &BinVector) -> BinVector {
+        assert_eq!(word.len(), self.length, "Mismatched sizes");
+        BinVector::from_words(&[self.nearest_word(word.as_u64())], self.length)
+    }
+
+    /// The largest weight of a coset leader, which was found when building the table
+    fn covering_radius(&self) -> usize {
+        self.covering_radius
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use codes::covering_radius;
+
+    /// Check decoding against all codewords for every word of a short code
+    fn check_exhaustively(code: &LinearCode) {
+        let n = code.length();
+        let codewords: Vec<u64> = (0..1u64 << code.dimension())
+            .map(|message| code.encode_word(message))
+            .collect();
+        for word in 0..1u64 << n {
+            let nearest = code.nearest_word(word);
+            let best = codewords
+                .iter()
+                .map(|c| (c ^ word).count_ones())
+                .min()
+                .unwrap();
+            assert_eq!((nearest ^ word).count_ones(), best);
+            assert_eq!(code.encode_word(code.message_word(nearest)), nearest);
+        }
+    }
+
+    #[test]
+    fn parameters() {
+        for &(ref code, n, k, radius) in &[
+            (LinearCode::hamming(3), 7, 4, 1),
+            (LinearCode::hamming(6), 63, 57, 1),
+            (LinearCode::golay(), 23, 12, 3),
+            (LinearCode::extended_golay(), 24, 12, 4),
+            (LinearCode::repetition(1), 1, 1, 0),
+            (LinearCode::repetition(7), 7, 1, 3),
+            (LinearCode::repetition(8), 8, 1, 4),
+        ] {
+            assert_eq!((code.length(), code.dimension()), (n, k));
+            assert_eq!(code.covering_radius(), radius);
+            let generator = code.generator();
+            assert_eq!(generator.rank(), k);
+            if n > k {
+                let product = &code.parity_check() * &generator.transposed();
+                assert!(product.rows().all(|row| row.count_ones() == 0));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "A redundancy of 39 needs too large a table")]
+    fn redundancy_limit() {
+        LinearCode::new(&BinMatrix::from_slices(&[[1]], 40));
+    }
+
+    #[test]
+    #[should_panic(expected = "A redundancy of 99 needs too large a table")]
+    fn repetition_limit() {
+        LinearCode::repetition(100);
+    }
+
+    #[test]
+    fn exhaustive_decoding() {
+        check_exhaustively(&LinearCode::hamming(3));
+        check_exhaustively(&LinearCode::repetition(9));
+        check_exhaustively(&LinearCode::new(&"110100; 011010; 101001".parse().unwrap()));
+    }
+
+    #[test]
+    fn golay_corrects_three_errors() {
+        let code = LinearCode::golay();
+        let message = BinVector::random(12);
+        let codeword = code.encode(&message);
+        let mut word = codeword.clone();
+        for &i in &[0, 11, 22] {
+            let flipped = !word[i];
+            word.set(i, flipped);
+        }
+        assert_eq!(code.nearest_codeword(&word), codeword);
+        assert_eq!(code.decode(&word), message);
+    }
+
+    #[test]
+    fn dependent_rows() {
+        let code = LinearCode::new(&"1100; 0110; 1010".parse().unwrap());
+        assert_eq!(code.dimension(), 2);
+        assert_eq!(code.covering_radius(), covering_radius(&code.generator()));
+        assert_eq!(code.generator_words().len(), 2);
+    }
+}
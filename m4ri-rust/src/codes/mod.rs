@@ -0,0 +1,220 @@
+//! Covering codes
+//!
+//! A covering code with covering radius `R` has a codeword within distance
+//! `R` of every word. LPN attacks use them to map samples onto a smaller
+//! dimension: a sample vector is replaced by the message of its nearest
+//! codeword, which adds noise depending on the distance.
+//!
+//! `LinearCode` decodes small codes, like the Hamming, Golay and repetition
+//! codes, with a table of coset leaders. Larger codes are built from small
+//! ones with `DirectSum`, `Concatenated` and `Amalgamated`.
+//!
+//! ```
+//! # extern crate m4ri_rust;
+//! # fn main() {
+//! use m4ri_rust::codes::{Concatenated, CoveringCode, LinearCode};
+//! use m4ri_rust::friendly::BinVector;
+//!
+//! // A [46, 24] code from two Golay codes
+//! let code = Concatenated::repeated(&LinearCode::golay(), 2);
+//! assert_eq!((code.length(), code.dimension()), (46, 24));
+//! assert_eq!(code.covering_radius(), 6);
+//!
+//! let word = BinVector::random(46);
+//! let message = code.decode(&word);
+//! let distance = (&code.encode(&message) + &word).count_ones();
+//! assert!(distance <= 6);
+//! # }
+//! ```
+use friendly::{BinMatrix, BinVector, Gf2Matrix};
+
+mod combined;
+mod linear;
+pub use self::combined::*;
+pub use self::linear::*;
+
+/// Largest redundancy `n - k` of codes for which tables of coset leaders are built
+pub const MAX_REDUNDANCY: usize = 20;
+
+/// A binary linear code with a nearest-codeword decoder
+///
+/// Messages are `BinVector`s of length `dimension()` and codewords are
+/// `message * generator()`.
+pub trait CoveringCode {
+    /// The length `n` of the codewords
+    fn length(&self) -> usize;
+
+    /// The dimension `k` of the code, the length of the messages
+    fn dimension(&self) -> usize;
+
+    /// The `k x n` generator matrix
+    fn generator(&self) -> BinMatrix;
+
+    /// Encode a message into a codeword
+    ///
+    /// **Panics** if the length of `message` is not the dimension
+    fn encode(&self, message: &BinVector) -> BinVector {
+        message * &self.generator()
+    }
+
+    /// The message of a codeword closest to `word`
+    ///
+    /// **Panics** if the length of `word` is not the length of the code
+    fn decode(&self, word: &BinVector) -> BinVector;
+
+    /// A codeword closest to `word`
+    ///
+    /// **Panics** if the length of `word` is not the length of the code
+    fn nearest_codeword(&self, word: &BinVector) -> BinVector {
+        self.encode(&self.decode(word))
+    }
+
+    /// The largest distance from any word to the code
+    ///
+    /// By default this searches all syndromes.
+    ///
+    /// **Panics** if the redundancy `n - k` is larger than `MAX_REDUNDANCY`
+    fn covering_radius(&self) -> usize {
+        covering_radius(&self.generator())
+    }
+}
+
+impl<C: CoveringCode + ?Sized> CoveringCode for Box<C> {
+    fn length(&self) -> usize {
+        (**self).length()
+    }
+
+    fn dimension(&self) -> usize {
+        (**self).dimension()
+    }
+
+    fn generator(&self) -> BinMatrix {
+        (**self).generator()
+    }
+
+    fn encode(&self, message: &BinVector) -> BinVector {
+        (**self).encode(message)
+    }
+
+    fn decode(&self, word: &BinVector) -> BinVector {
+        (**self).decode(word)
+    }
+
+    fn nearest_codeword(&self, word: &BinVector) -> BinVector {
+        (**self).nearest_codeword(word)
+    }
+
+    fn covering_radius(&self) -> usize {
+        (**self).covering_radius()
+    }
+}
+
+/// The covering radius of the code spanned by the rows of `generator`
+///
+/// This is the largest weight of a coset leader, found by a breadth-first
+/// search over all `2^(n - k)` syndromes.
+///
+/// **Panics** if the redundancy `n - k` is larger than `MAX_REDUNDANCY`
+pub fn covering_radius(generator: &BinMatrix) -> usize {
+    let parity_check = Gf2Matrix::kernel(generator);
+    check_redundancy(parity_check.len());
+    let columns: Vec<u32> = (0..generator.ncols())
+        .map(|col| {
+            parity_check
+                .iter()
+                .enumerate()
+                .fold(0, |column, (row, check)| {
+                    column | (u32::from(check[col]) << row)
+                })
+        })
+        .collect();
+    let (_, weights) = coset_leaders(&columns, parity_check.len());
+    weights.into_iter().max().map_or(0, usize::from)
+}
+
+/// Check the redundancy before syndromes of that many bits are built
+fn check_redundancy(redundancy: usize) {
+    assert!(
+        redundancy <= MAX_REDUNDANCY,
+        "A redundancy of {} needs too large a table of coset leaders",
+        redundancy
+    );
+}
+
+/// Find a coset leader for every syndrome with a breadth-first search
+///
+/// `columns` are the columns of a full-rank parity-check matrix with
+/// `redundancy` rows. Returns for every syndrome the last column added to its
+/// leader, plus one (zero for the zero syndrome), and the weight of the leader.
+fn coset_leaders(columns: &[u32], redundancy: usize) -> (Vec<u16>, Vec<u8>) {
+    check_redundancy(redundancy);
+    let mut last_columns = vec![0u16; 1 << redundancy];
+    let mut weights = vec![0u8; 1 << redundancy];
+    let mut seen = vec![false; 1 << redundancy];
+    seen[0] = true;
+    let mut queue = vec![0u32];
+    let mut next = 0;
+    while next < queue.len() {
+        let syndrome = queue[next];
+        next += 1;
+        for (col, &column) in columns.iter().enumerate() {
+            let reached = (syndrome ^ column) as usize;
+            if !seen[reached] {
+                seen[reached] = true;
+                last_columns[reached] = col as u16 + 1;
+                weights[reached] = weights[syndrome as usize] + 1;
+                queue.push(reached as u32);
+            }
+        }
+    }
+    assert!(
+        queue.len() == seen.len(),
+        "The parity-check matrix doesn't have full rank"
+    );
+    (last_columns, weights)
+}
+
+/// The bits `start..start + len` of `vector`
+fn subvector(vector: &BinVector, start: usize, len: usize) -> BinVector {
+    let bits: Vec<bool> = vector.iter().skip(start).take(len).collect();
+    BinVector::from_bools(&bits)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn radius_from_generator() {
+        // The [3, 1] repetition code and the [4, 3] parity code
+        assert_eq!(covering_radius(&"111".parse().unwrap()), 1);
+        assert_eq!(covering_radius(&"1001; 0101; 0011".parse().unwrap()), 1);
+        // The [5, 2] code with codewords 00000, 11100, 00111, 11011
+        assert_eq!(covering_radius(&"11100; 00111".parse().unwrap()), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "A redundancy of 39 needs too large a table")]
+    fn radius_redundancy_limit() {
+        covering_radius(&BinMatrix::from_slices(&[[1]], 40));
+    }
+
+    #[test]
+    fn coset_leader_weights() {
+        // Columns of the [7, 4] Hamming code: every syndrome is a single column
+        let columns: Vec<u32> = (1..8).collect();
+        let (last_columns, weights) = coset_leaders(&columns, 3);
+        assert_eq!(weights, vec![0, 1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(last_columns, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn subvectors() {
+        let v = BinVector::from_bools(&[true, false, true, true, false]);
+        assert_eq!(
+            subvector(&v, 1, 3),
+            BinVector::from_bools(&[false, true, true])
+        );
+        assert_eq!(subvector(&v, 5, 0).len(), 0);
+    }
+}
@@ -0,0 +1,498 @@
+//! Information-set decoding
+//!
+//! Every iteration permutes the columns of the parity-check matrix at random
+//! and (partially) echelonizes it. The columns that were not used as pivots
+//! form the information set. An iteration succeeds if the error has the
+//! weight distribution over the information set that the algorithm looks for:
+//!
+//! * `Prange`: no errors in the information set,
+//! * `LeeBrickell { p }`: at most `p` errors in the information set,
+//! * `Stern { p, l }`: at most `p` errors in each half of the information set and
+//!   none in the pivot positions of `l` chosen rows, which are used to match
+//!   the two halves,
+//! * `Dumer { p, l }`: only `r - l` pivots, and at most `p` errors in each half of the
+//!   resulting `k + l` columns, matched on the remaining `l` rows.
+//!
+//! ```
+//! # extern crate m4ri_rust;
+//! # fn main() {
+//! use m4ri_rust::decoding::isd::{Algorithm, Isd};
+//! use m4ri_rust::friendly::{BinMatrix, BinVector};
+//!
+//! let h = BinMatrix::random(20, 40);
+//! let mut e = BinVector::from_elem(40, false);
+//! e.set(3, true);
+//! e.set(17, true);
+//! let s = &h * &e;
+//!
+//! let decoded = Isd::new(Algorithm::Stern { p: 1, l: 4 })
+//!     .seed(1)
+//!     .max_iterations(10_000)
+//!     .decode(&h, &s, 2)
+//!     .unwrap();
+//! assert!(decoded.error.count_ones() <= 2);
+//! assert_eq!(&h * &decoded.error, s);
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::ops::Range;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use friendly::{BinMatrix, BinVector};
+
+/// The information-set decoding algorithms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Prange's algorithm: all errors are outside the information set
+    Prange,
+    /// Lee–Brickell: at most `p` errors in the information set
+    LeeBrickell {
+        /// Maximal number of errors in the information set
+        p: usize,
+    },
+    /// Stern: at most `p` errors in each half of the information set, matched on `l` rows
+    Stern {
+        /// Maximal number of errors in each half of the information set
+        p: usize,
+        /// Number of rows on which the halves are matched
+        l: usize,
+    },
+    /// Dumer: like Stern, but with `l` fewer pivots instead of `l` error-free positions
+    Dumer {
+        /// Maximal number of errors in each half of the information set
+        p: usize,
+        /// Number of rows that are not echelonized, on which the halves are matched
+        l: usize,
+    },
+}
+
+/// Errors of the information-set decoders
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IsdError {
+    /// The syndrome is not in the column space of the parity-check matrix
+    NoSolution,
+    /// No error of the requested weight was found within the iteration limit
+    IterationLimit {
+        /// Number of iterations that were done
+        iterations: u64,
+    },
+}
+
+impl fmt::Display for IsdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IsdError::NoSolution => write!(f, "the syndrome is not in the column space"),
+            IsdError::IterationLimit { iterations } => {
+                write!(f, "no solution found in {} iterations", iterations)
+            }
+        }
+    }
+}
+
+impl error::Error for IsdError {}
+
+/// The result of a successful decoding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decoded {
+    /// The error vector, with `H * error = s`
+    pub error: BinVector,
+    /// Number of iterations it took, including the successful one
+    pub iterations: u64,
+}
+
+/// Information-set decoder with its parameters
+///
+/// ISD runs until it finds a solution, so without an iteration limit it does
+/// not terminate if there is no solution of the requested weight.
+#[derive(Clone, Debug)]
+pub struct Isd {
+    algorithm: Algorithm,
+    max_iterations: Option<u64>,
+    seed: Option<u64>,
+}
+
+impl Isd {
+    /// Create a decoder without iteration limit that is seeded from the system
+    pub fn new(algorithm: Algorithm) -> Isd {
+        Isd {
+            algorithm,
+            max_iterations: None,
+            seed: None,
+        }
+    }
+
+    /// Give up after `max_iterations` iterations
+    pub fn max_iterations(mut self, max_iterations: u64) -> Isd {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    /// Use a `StdRng` with this seed, which makes `decode` deterministic
+    pub fn seed(mut self, seed: u64) -> Isd {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The algorithm used by this decoder
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Find an `e` of weight at most `weight` with `h * e = s`
+    ///
+    /// **Panics** if the length of `s` is not the number of rows of `h`
+    pub fn decode(&self, h: &BinMatrix, s: &BinVector, weight: usize) -> Result<Decoded, IsdError> {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        self.decode_with_rng(h, s, weight, &mut rng)
+    }
+
+    /// Like `decode`, but draws the permutations from `rng` and ignores the seed
+    ///
+    /// **Panics** if the length of `s` is not the number of rows of `h`
+    pub fn decode_with_rng<R: Rng + ?Sized>(
+        &self,
+        h: &BinMatrix,
+        s: &BinVector,
+        weight: usize,
+        rng: &mut R,
+    ) -> Result<Decoded, IsdError> {
+        assert_eq!(h.nrows(), s.len(), "Mismatched sizes");
+        let ncols = h.ncols();
+        if s.count_ones() == 0 {
+            return Ok(Decoded {
+                error: BinVector::from_elem(ncols, false),
+                iterations: 0,
+            });
+        }
+        // `[H | s]`, with the columns of `H` in the order of `permutation`
+        // and reduced in place, as row operations keep the solutions
+        let mut permuted = h.augmented(&s.as_column_matrix());
+        // `s` is in the column space of `H` if it doesn't add to the rank
+        if permuted.rank() > h.rank() {
+            return Err(IsdError::NoSolution);
+        }
+        let mut permutation: Vec<usize> = (0..ncols).collect();
+        let mut iterations = 0;
+        loop {
+            if self.max_iterations.is_some_and(|max| iterations >= max) {
+                return Err(IsdError::IterationLimit { iterations });
+            }
+            iterations += 1;
+            // Fisher–Yates shuffle of the columns of `H`
+            for col in (1..ncols).rev() {
+                let other = rng.gen_range(0..=col);
+                permutation.swap(col, other);
+                permuted.swap_columns(col, other);
+            }
+            let system = ReducedSystem::new(&mut permuted, &permutation, self.algorithm);
+            let found = match self.algorithm {
+                Algorithm::Prange => system.lee_brickell(0, weight),
+                Algorithm::LeeBrickell { p } => system.lee_brickell(p, weight),
+                Algorithm::Stern { p, .. } | Algorithm::Dumer { p, .. } => {
+                    system.collisions(p, weight)
+                }
+            };
+            if let Some(selected) = found {
+                let error = system.error(&selected, ncols);
+                debug_assert_eq!(h * &error, *s);
+                return Ok(Decoded { error, iterations });
+            }
+        }
+    }
+}
+
+/// The system after permuting the columns and reducing the rows
+///
+/// The rows are split in check rows, which must be satisfied by the selected
+/// information-set columns alone, and the other rows, which have a pivot that
+/// absorbs the remaining errors.
+struct ReducedSystem {
+    /// Original column of the pivot of every non-check row
+    pivots: Vec<usize>,
+    /// Original columns of the information set
+    free: Vec<usize>,
+    /// Bits of the information-set columns in the check rows
+    checks: Vec<BinVector>,
+    /// Bits of the information-set columns in the other rows
+    others: Vec<BinVector>,
+    /// The reduced syndrome in the check rows
+    syndrome_checks: BinVector,
+    /// The reduced syndrome in the other rows
+    syndrome_others: BinVector,
+}
+
+impl ReducedSystem {
+    /// Reduce `[H | s]`, whose columns of `H` were permuted by `permutation`
+    ///
+    /// The pivots are those of the reduced row echelon form, of which Dumer
+    /// needs only the first `r - l`, so the reduction stops there. The rows
+    /// of the last pivots of Stern then become check rows, and their pivot
+    /// columns part of the information set.
+    fn new(reduced: &mut BinMatrix, permutation: &[usize], algorithm: Algorithm) -> ReducedSystem {
+        let (nrows, ncols) = (reduced.nrows(), permutation.len());
+        let max_pivots = match algorithm {
+            Algorithm::Dumer { l, .. } => nrows.saturating_sub(l),
+            _ => nrows,
+        };
+        // `s` is in the column space of `H`, so its column needs no pivot
+        let pivots = reduced.echelonize_reduced_partial(max_pivots, ncols);
+        // Stern requires no errors in the pivots of its check rows
+        let error_free = match algorithm {
+            Algorithm::Stern { l, .. } => l.min(pivots.len()),
+            _ => 0,
+        };
+        // Rows without pivot are check rows as well
+        let split = pivots.len() - error_free;
+
+        let mut is_pivot = vec![false; ncols];
+        for &col in &pivots {
+            is_pivot[col] = true;
+        }
+        let free: Vec<usize> = (0..ncols).filter(|&col| !is_pivot[col]).collect();
+        let reduced = &*reduced;
+        let column = |rows: Range<usize>, col: usize| -> BinVector {
+            let bits: Vec<bool> = rows.map(|row| reduced.bit(row, col)).collect();
+            BinVector::from_bools(&bits)
+        };
+        ReducedSystem {
+            pivots: pivots[..split]
+                .iter()
+                .map(|&col| permutation[col])
+                .collect(),
+            checks: free.iter().map(|&col| column(split..nrows, col)).collect(),
+            others: free.iter().map(|&col| column(0..split, col)).collect(),
+            syndrome_checks: column(split..nrows, ncols),
+            syndrome_others: column(0..split, ncols),
+            free: free.iter().map(|&col| permutation[col]).collect(),
+        }
+    }
+
+    /// Sum the given information-set columns onto `start`
+    fn sum(start: &BinVector, parts: &[BinVector], selected: &[usize]) -> BinVector {
+        let mut sum = start.clone();
+        for &index in selected {
+            sum += &parts[index];
+        }
+        sum
+    }
+
+    /// Check if the selected columns lead to an error of at most `weight`
+    fn fits(&self, selected: &[usize], weight: usize) -> bool {
+        let others = ReducedSystem::sum(&self.syndrome_others, &self.others, selected);
+        others.count_ones() as usize + selected.len() <= weight
+    }
+
+    /// Try all subsets of at most `p` columns of the information set
+    fn lee_brickell(&self, p: usize, weight: usize) -> Option<Vec<usize>> {
+        subsets_up_to(self.free.len(), p).find(|selected| {
+            ReducedSystem::sum(&self.syndrome_checks, &self.checks, selected).count_ones() == 0
+                && self.fits(selected, weight)
+        })
+    }
+
+    /// Match subsets of at most `p` columns of both halves of the information set on the check rows
+    fn collisions(&self, p: usize, weight: usize) -> Option<Vec<usize>> {
+        let half = self.free.len() / 2;
+        let zero = BinVector::from_elem(self.syndrome_checks.len(), false);
+        let mut left: HashMap<BinVector, Vec<Vec<usize>>> = HashMap::new();
+        for selected in subsets_up_to(half, p) {
+            let key = ReducedSystem::sum(&zero, &self.checks, &selected);
+            left.entry(key).or_default().push(selected);
+        }
+        for right in subsets_up_to(self.free.len() - half, p) {
+            let right: Vec<usize> = right.iter().map(|&index| index + half).collect();
+            let key = ReducedSystem::sum(&self.syndrome_checks, &self.checks, &right);
+            for left in left.get(&key).into_iter().flatten() {
+                let selected: Vec<usize> = left.iter().chain(&right).cloned().collect();
+                if self.fits(&selected, weight) {
+                    return Some(selected);
+                }
+            }
+        }
+        None
+    }
+
+    /// The error vector for the selected information-set columns
+    fn error(&self, selected: &[usize], ncols: usize) -> BinVector {
+        let mut error = BinVector::from_elem(ncols, false);
+        for &index in selected {
+            error.set(self.free[index], true);
+        }
+        let others = ReducedSystem::sum(&self.syndrome_others, &self.others, selected);
+        for row in others.iter_set_bits(..) {
+            error.set(self.pivots[row], true);
+        }
+        error
+    }
+}
+
+/// All subsets of `size` elements of `0..len`, in lexicographic order
+struct Subsets {
+    indices: Vec<usize>,
+    len: usize,
+    done: bool,
+}
+
+fn subsets(len: usize, size: usize) -> Subsets {
+    Subsets {
+        indices: (0..size).collect(),
+        len,
+        done: size > len,
+    }
+}
+
+/// All subsets of at most `size` elements of `0..len`, smallest first
+fn subsets_up_to(len: usize, size: usize) -> impl Iterator<Item = Vec<usize>> {
+    (0..=size).flat_map(move |size| subsets(len, size))
+}
+
+impl Iterator for Subsets {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        let current = self.indices.clone();
+        let size = self.indices.len();
+        match (0..size)
+            .rev()
+            .find(|&i| self.indices[i] < self.len - size + i)
+        {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..size {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ALGORITHMS: [Algorithm; 6] = [
+        Algorithm::Prange,
+        Algorithm::LeeBrickell { p: 1 },
+        Algorithm::LeeBrickell { p: 2 },
+        Algorithm::Stern { p: 1, l: 3 },
+        Algorithm::Dumer { p: 1, l: 3 },
+        Algorithm::Dumer { p: 2, l: 6 },
+    ];
+
+    /// A random instance with an error of weight `weight`
+    fn instance(
+        rng: &mut StdRng,
+        nrows: usize,
+        ncols: usize,
+        weight: usize,
+    ) -> (BinMatrix, BinVector) {
+        let rows: Vec<BinVector> = (0..nrows)
+            .map(|_| BinVector::from_bools(&(0..ncols).map(|_| rng.gen()).collect::<Vec<_>>()))
+            .collect();
+        let h = BinMatrix::new(rows);
+        let mut e = BinVector::from_elem(ncols, false);
+        for col in rand::seq::index::sample(rng, ncols, weight) {
+            e.set(col, true);
+        }
+        let s = &h * &e;
+        (h, s)
+    }
+
+    #[test]
+    fn subsets_are_complete() {
+        assert_eq!(subsets(4, 2).count(), 6);
+        assert_eq!(subsets(3, 0).collect::<Vec<_>>(), vec![Vec::<usize>::new()]);
+        assert_eq!(subsets(2, 3).count(), 0);
+        assert_eq!(subsets_up_to(4, 2).count(), 1 + 4 + 6);
+        assert_eq!(
+            subsets(3, 2).collect::<Vec<_>>(),
+            vec![vec![0, 1], vec![0, 2], vec![1, 2]]
+        );
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn decodes() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &algorithm in &ALGORITHMS {
+            for &(nrows, ncols, weight) in &[(20, 40, 4), (35, 70, 5), (64, 130, 4)] {
+                let (h, s) = instance(&mut rng, nrows, ncols, weight);
+                let decoded = Isd::new(algorithm)
+                    .max_iterations(100_000)
+                    .decode_with_rng(&h, &s, weight, &mut rng)
+                    .unwrap_or_else(|e| panic!("{:?}: {}", algorithm, e));
+                assert!(
+                    decoded.error.count_ones() as usize <= weight,
+                    "{:?}",
+                    algorithm
+                );
+                assert_eq!(&h * &decoded.error, s, "{:?}", algorithm);
+                assert!(decoded.iterations >= 1);
+            }
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn rank_deficient() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let (h, _) = instance(&mut rng, 15, 40, 0);
+        let h = h.stacked(&h);
+        let mut e = BinVector::from_elem(40, false);
+        e.set(5, true);
+        e.set(30, true);
+        let s = &h * &e;
+        for &algorithm in &ALGORITHMS {
+            let decoded = Isd::new(algorithm).seed(3).decode(&h, &s, 2).unwrap();
+            assert_eq!(&h * &decoded.error, s, "{:?}", algorithm);
+        }
+        let mut inconsistent = s.clone();
+        let flipped = !inconsistent[0];
+        inconsistent.set(0, flipped);
+        assert_eq!(
+            Isd::new(Algorithm::Prange).decode(&h, &inconsistent, 40),
+            Err(IsdError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn seeded_runs_repeat() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let (h, s) = instance(&mut rng, 30, 60, 4);
+        let decoder = Isd::new(Algorithm::LeeBrickell { p: 1 }).seed(99);
+        assert_eq!(decoder.decode(&h, &s, 4), decoder.decode(&h, &s, 4));
+    }
+
+    #[test]
+    fn limits() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let (h, s) = instance(&mut rng, 30, 60, 6);
+        assert_eq!(
+            Isd::new(Algorithm::Prange)
+                .max_iterations(10)
+                .decode(&h, &s, 0),
+            Err(IsdError::IterationLimit { iterations: 10 })
+        );
+        let zero = BinVector::from_elem(30, false);
+        let decoded = Isd::new(Algorithm::Prange).decode(&h, &zero, 0).unwrap();
+        assert_eq!(decoded.error.count_ones(), 0);
+        assert_eq!(decoded.iterations, 0);
+        let no_columns = BinMatrix::zero(30, 0);
+        assert_eq!(
+            Isd::new(Algorithm::Prange).decode(&no_columns, &s, 6),
+            Err(IsdError::NoSolution)
+        );
+    }
+}
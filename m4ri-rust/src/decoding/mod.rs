@@ -0,0 +1,6 @@
+//! Decoding algorithms for binary linear codes
+//!
+//! The decoders work on the syndrome formulation: given a parity-check
+//! matrix `H` and a syndrome `s`, find a low-weight `e` with `H e = s`.
+
+pub mod isd;
@@ -0,0 +1,282 @@
+//! Generators of random matrices and vectors for property tests
+//!
+//! With the `proptest` feature `BinMatrix` and `BinVector` implement
+//! `proptest::arbitrary::Arbitrary`, and the `strategy` module has strategies
+//! for other shapes. With the `quickcheck` feature they implement
+//! `quickcheck::Arbitrary`.
+//!
+//! Both favour the shapes where bugs tend to live: single rows and columns,
+//! and lengths around the 64-bit word boundary.
+
+/// Lengths around the word boundaries, which are picked more often
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+const EDGE_LENGTHS: [usize; 7] = [1, 2, 63, 64, 65, 127, 128];
+
+/// Largest number of rows or columns that is generated
+#[cfg(any(feature = "proptest", feature = "quickcheck"))]
+const MAX_LENGTH: usize = 200;
+
+#[cfg(feature = "proptest")]
+pub mod strategy {
+    //! Proptest strategies for matrices and vectors
+    //!
+    //! ```
+    //! # extern crate m4ri_rust;
+    //! # #[macro_use] extern crate proptest;
+    //! use m4ri_rust::friendly::strategy::{bin_matrix, shapes};
+    //! use m4ri_rust::friendly::BinMatrix;
+    //!
+    //! proptest! {
+    //!     fn transpose_twice(m in bin_matrix(shapes())) {
+    //!         prop_assert_eq!(m.transposed().transposed(), m);
+    //!     }
+    //! }
+    //! # fn main() { transpose_twice(); }
+    //! ```
+    use proptest::arbitrary::{any, Arbitrary};
+    use proptest::collection;
+    use proptest::strategy::{BoxedStrategy, Just, Strategy};
+
+    use super::{EDGE_LENGTHS, MAX_LENGTH};
+    use friendly::binary_matrix::BinMatrix;
+    use friendly::binary_vector::BinVector;
+
+    /// Lengths from 1 to 200, with the word boundaries picked more often
+    ///
+    /// Shrinks towards shorter lengths.
+    pub fn lengths() -> impl Strategy<Value = usize> + Clone {
+        proptest::prop_oneof![
+            1 => 1..=MAX_LENGTH,
+            1 => proptest::sample::select(&EDGE_LENGTHS[..]),
+        ]
+    }
+
+    /// Matrix shapes `(nrows, ncols)`, including single rows and single columns
+    pub fn shapes() -> impl Strategy<Value = (usize, usize)> + Clone {
+        proptest::prop_oneof![
+            (Just(1), lengths()),
+            (lengths(), Just(1)),
+            (lengths(), lengths()),
+        ]
+    }
+
+    /// Vectors with a length drawn from `len`
+    ///
+    /// Shrinks towards shorter vectors with fewer set bits.
+    pub fn bin_vector<S>(len: S) -> impl Strategy<Value = BinVector>
+    where
+        S: Strategy<Value = usize>,
+    {
+        len.prop_flat_map(|len| collection::vec(any::<bool>(), len))
+            .prop_map(|bits| BinVector::from_bools(&bits))
+    }
+
+    /// Matrices with a shape drawn from `shape`
+    ///
+    /// Shrinks towards smaller matrices with fewer set bits.
+    pub fn bin_matrix<S>(shape: S) -> impl Strategy<Value = BinMatrix>
+    where
+        S: Strategy<Value = (usize, usize)>,
+    {
+        shape
+            .prop_flat_map(|(nrows, ncols)| {
+                let rows = collection::vec(collection::vec(any::<bool>(), ncols), nrows);
+                (Just(ncols), rows)
+            })
+            .prop_map(|(ncols, rows)| {
+                let rows: Vec<BinVector> =
+                    rows.iter().map(|row| BinVector::from_bools(row)).collect();
+                BinMatrix::from_slices(&rows, ncols)
+            })
+    }
+
+    impl Arbitrary for BinVector {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<BinVector>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            bin_vector(lengths()).boxed()
+        }
+    }
+
+    impl Arbitrary for BinMatrix {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<BinMatrix>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            bin_matrix(shapes()).boxed()
+        }
+    }
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impls {
+    use quickcheck::{Arbitrary, Gen};
+
+    use super::{EDGE_LENGTHS, MAX_LENGTH};
+    use friendly::binary_matrix::BinMatrix;
+    use friendly::binary_vector::BinVector;
+
+    /// A length up to the size of `g`, or one of the word boundaries
+    fn length(g: &mut Gen) -> usize {
+        if bool::arbitrary(g) {
+            *g.choose(&EDGE_LENGTHS).unwrap()
+        } else {
+            usize::arbitrary(g) % g.size().clamp(1, MAX_LENGTH) + 1
+        }
+    }
+
+    fn random_words(g: &mut Gen, len: usize) -> Vec<u64> {
+        (0..len.div_ceil(64)).map(|_| u64::arbitrary(g)).collect()
+    }
+
+    /// The vector without the bit at every set position, at most 64 of them
+    fn clear_set_bits(vector: &BinVector) -> impl Iterator<Item = BinVector> + '_ {
+        vector.iter_set_bits(..).take(64).map(move |i| {
+            let mut sparser = vector.clone();
+            sparser.set(i, false);
+            sparser
+        })
+    }
+
+    impl Arbitrary for BinVector {
+        fn arbitrary(g: &mut Gen) -> BinVector {
+            let len = length(g);
+            BinVector::from_words(&random_words(g, len), len)
+        }
+
+        /// Shrinks to shorter vectors, then to vectors with fewer set bits
+        fn shrink(&self) -> Box<dyn Iterator<Item = BinVector>> {
+            let len = self.len();
+            let mut candidates = Vec::new();
+            for shorter in [len / 2, len.saturating_sub(1)] {
+                if shorter > 0 && shorter < len {
+                    let mut vector = self.clone();
+                    vector.truncate(shorter);
+                    candidates.push(vector);
+                }
+            }
+            candidates.extend(clear_set_bits(self));
+            Box::new(candidates.into_iter())
+        }
+    }
+
+    impl Arbitrary for BinMatrix {
+        fn arbitrary(g: &mut Gen) -> BinMatrix {
+            let (nrows, ncols) = match u8::arbitrary(g) % 3 {
+                0 => (1, length(g)),
+                1 => (length(g), 1),
+                _ => (length(g), length(g)),
+            };
+            let rows: Vec<Vec<u64>> = (0..nrows).map(|_| random_words(g, ncols)).collect();
+            BinMatrix::from_slices(&rows, ncols)
+        }
+
+        /// Shrinks to fewer rows or columns, then to matrices with fewer set bits
+        fn shrink(&self) -> Box<dyn Iterator<Item = BinMatrix>> {
+            let (nrows, ncols) = (self.nrows(), self.ncols());
+            let mut candidates = Vec::new();
+            for fewer in [nrows / 2, nrows.saturating_sub(1)] {
+                if fewer > 0 && fewer < nrows {
+                    candidates.push(BinMatrix::from_rows_iter(self.rows().take(fewer), ncols));
+                }
+            }
+            for fewer in [ncols / 2, ncols.saturating_sub(1)] {
+                if fewer > 0 && fewer < ncols {
+                    candidates.push(BinMatrix::from_rows_iter(self.rows(), fewer));
+                }
+            }
+            for (i, row) in self.rows().enumerate() {
+                if row.count_ones() > 0 {
+                    let mut sparser = self.clone();
+                    sparser.write_row_words(i, &vec![0; row.as_words().len()]);
+                    candidates.push(sparser);
+                }
+            }
+            if nrows == 1 {
+                candidates.extend(clear_set_bits(&self.as_vector()).map(|row| row.as_matrix()));
+            }
+            Box::new(candidates.into_iter())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "proptest")]
+    mod proptest_properties {
+        use friendly::binary_matrix::BinMatrix;
+        use friendly::binary_vector::BinVector;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn count_ones_of_vectors(v: BinVector) {
+                prop_assert_eq!(v.count_ones() as usize, v.iter().filter(|&bit| bit).count());
+                prop_assert_eq!(v.as_matrix().count_ones(), v.count_ones());
+                prop_assert_eq!(v.as_column_matrix().count_ones(), v.count_ones());
+            }
+
+            #[test]
+            fn as_vector(m: BinMatrix) {
+                prop_assume!(m.nrows() == 1 || m.ncols() == 1);
+                let v = m.as_vector();
+                prop_assert_eq!(v.len(), m.nrows() * m.ncols());
+                prop_assert_eq!(v.count_ones(), m.count_ones());
+            }
+
+            #[test]
+            fn transpose(m: BinMatrix) {
+                let t = m.transposed();
+                prop_assert_eq!((t.nrows(), t.ncols()), (m.ncols(), m.nrows()));
+                prop_assert_eq!(t.transposed(), m);
+            }
+
+            #[test]
+            fn multiply_by_vector(m: BinMatrix) {
+                let v = BinVector::from_elem(m.ncols(), true);
+                let product = &m * &v;
+                for (i, row) in m.rows().enumerate() {
+                    prop_assert_eq!(product[i], row.count_ones() % 2 == 1);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "quickcheck")]
+    mod quickcheck_properties {
+        use friendly::binary_matrix::BinMatrix;
+        use friendly::binary_vector::BinVector;
+        use quickcheck::{quickcheck, Arbitrary};
+
+        quickcheck! {
+            fn count_ones_of_vectors(v: BinVector) -> bool {
+                v.as_matrix().count_ones() == v.count_ones()
+                    && v.as_column_matrix().count_ones() == v.count_ones()
+            }
+
+            fn transpose(m: BinMatrix) -> bool {
+                m.transposed().transposed() == m
+            }
+        }
+
+        #[test]
+        fn shrinking() {
+            let m = BinMatrix::from_words(3, 70, &[!0; 6], ::friendly::Layout::RowMajor);
+            for smaller in m.shrink() {
+                assert!(smaller.nrows() <= 3 && smaller.ncols() <= 70);
+                assert!(smaller != m);
+            }
+            let v = BinVector::from_elem(3, true);
+            let shrunk: Vec<BinVector> = v.shrink().collect();
+            assert!(shrunk.iter().all(|s| s.count_ones() < 3));
+            assert_eq!(shrunk.len(), 2 + 3);
+            for &(nrows, ncols) in &[(0, 5), (5, 0), (0, 0)] {
+                for smaller in BinMatrix::zero(nrows, ncols).shrink() {
+                    assert!(smaller.nrows() * smaller.ncols() == 0);
+                    assert!(smaller.nrows() + smaller.ncols() < nrows + ncols);
+                }
+            }
+        }
+    }
+}
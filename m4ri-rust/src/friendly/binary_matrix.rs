@@ -1,9 +1,18 @@
 use ffi::*;
 use friendly::binary_vector::BinVector;
-use libc::c_int;
+use friendly::binary_vector::BinVectorRef;
+use friendly::binary_vector::BitOrder;
+use friendly::format::{DisplayStyle, Displayed};
 use std::cmp;
+use std::error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter;
 use std::ops;
+use std::os::raw::c_int;
+use std::panic;
 use std::ptr;
+use std::slice;
 #[cfg(feature = "serde")]
 use vob::Vob;
 
@@ -12,15 +21,14 @@ use vob::Vob;
 #[serde(remote = "ptr::NonNull<Mzd>")]
 struct MzdSerializer {
     #[serde(getter = "mzd_to_vecs")]
-    rows: Vec<Vob>,
+    rows: Vec<Vob<u64>>,
 }
 
 #[cfg(feature = "serde")]
-fn mzd_to_vecs(mzd: &ptr::NonNull<Mzd>) -> Vec<Vob> {
+fn mzd_to_vecs(mzd: &ptr::NonNull<Mzd>) -> Vec<Vob<u64>> {
     let m = BinMatrix { mzd: *mzd };
     let result = (0..m.nrows())
-        .into_iter()
-        .map(|r| m.get_window(r, 0, r + 1, m.ncols()).as_vector().into_vob())
+        .map(|r| m.row_ref(r).to_vector().into_vob())
         .collect();
     // We shouldn't free m as we stole mzd.
     std::mem::forget(m);
@@ -28,25 +36,139 @@ fn mzd_to_vecs(mzd: &ptr::NonNull<Mzd>) -> Vec<Vob> {
 }
 
 /// Structure to represent matrices
-#[derive(Debug)]
+///
+/// Allocations that fail make the `try_` constructors return
+/// `MatrixError::AllocationFailed`. With the C backend, M4RI aborts the process
+/// when it runs out of memory instead, unless the application opted in to
+/// panicking with `m4ri_sys::m4ri_install_error_hook`, which needs the vendored M4RI.
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct BinMatrix {
     #[cfg_attr(feature = "serde", serde(with = "MzdSerializer", rename = "matrix"))]
     mzd: ptr::NonNull<Mzd>,
 }
 
+/// Order of the rows or columns in a flat buffer of packed bits
+///
+/// Used by `BinMatrix::from_words` and `to_words`, and their byte variants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Layout {
+    /// One row after the other, each padded to a whole number of words or bytes
+    RowMajor,
+    /// One column after the other, each padded to a whole number of words or bytes.
+    /// This is the row-major layout of the transpose.
+    ColumnMajor,
+}
+
 unsafe impl Sync for BinMatrix {}
 unsafe impl Send for BinMatrix {}
 
 impl ops::Drop for BinMatrix {
     fn drop(&mut self) {
-        unsafe { ptr::drop_in_place(self.mzd.as_ptr()) }
+        unsafe { mzd_free(self.mzd.as_ptr()) }
+    }
+}
+
+/// Errors that can occur in operations on a `BinMatrix`
+///
+/// The panicking operations report the same errors as their `try_` variants, through a panic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The dimensions of the operands don't match
+    DimensionMismatch {
+        /// The operation that was attempted
+        operation: &'static str,
+        /// Number of rows and columns of the left operand
+        left: (usize, usize),
+        /// Number of rows and columns of the right operand
+        right: (usize, usize),
+    },
+    /// The matrix is too large for M4RI, which uses `int` indices
+    TooLarge {
+        /// Number of rows
+        nrows: usize,
+        /// Number of columns
+        ncols: usize,
+    },
+    /// Allocating the memory for the matrix failed
+    AllocationFailed {
+        /// Number of rows
+        nrows: usize,
+        /// Number of columns
+        ncols: usize,
+    },
+    /// The operation needs a square matrix
+    NotSquare {
+        /// Number of rows
+        nrows: usize,
+        /// Number of columns
+        ncols: usize,
+    },
+    /// The matrix is not invertible
+    Singular,
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MatrixError::DimensionMismatch {
+                operation,
+                left,
+                right,
+            } => write!(
+                f,
+                "can't {} a {}x{} matrix and a {}x{} matrix",
+                operation, left.0, left.1, right.0, right.1
+            ),
+            MatrixError::TooLarge { nrows, ncols } => {
+                write!(f, "a {}x{} matrix is too large", nrows, ncols)
+            }
+            MatrixError::AllocationFailed { nrows, ncols } => {
+                write!(f, "failed to allocate a {}x{} matrix", nrows, ncols)
+            }
+            MatrixError::NotSquare { nrows, ncols } => {
+                write!(f, "a {}x{} matrix is not square", nrows, ncols)
+            }
+            MatrixError::Singular => write!(f, "the matrix is not invertible"),
+        }
+    }
+}
+
+impl error::Error for MatrixError {}
+
+/// Panic with the error, used by the operations that have a `try_` variant
+fn unwrap<T>(result: Result<T, MatrixError>) -> T {
+    result.unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Allocate a zero matrix after checking that M4RI can represent it
+///
+/// Also makes M4RI panic instead of aborting the process on errors it catches itself,
+/// if the library allows that, and turns the panic of a failed allocation into an error.
+fn try_init(nrows: usize, ncols: usize) -> Result<ptr::NonNull<Mzd>, MatrixError> {
+    let max = Rci::MAX as usize;
+    let bytes = nrows
+        .checked_mul(ncols.div_ceil(64))
+        .and_then(|words| words.checked_mul(8));
+    if nrows > max || ncols > max || bytes.is_none_or(|bytes| bytes > isize::MAX as usize) {
+        return Err(MatrixError::TooLarge { nrows, ncols });
     }
+    let mzd = if m4ri_error_hook_installed() {
+        // With the hook, M4RI reports running out of memory by panicking
+        match panic::catch_unwind(|| unsafe { mzd_init(nrows as Rci, ncols as Rci) }) {
+            Ok(mzd) => mzd,
+            Err(payload) if m4ri_is_allocation_failure(&*payload) => ptr::null_mut(),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    } else {
+        unsafe { mzd_init(nrows as Rci, ncols as Rci) }
+    };
+    ptr::NonNull::new(mzd).ok_or(MatrixError::AllocationFailed { nrows, ncols })
 }
 
+/// Check a matrix returned by M4RI
 macro_rules! nonnull {
     ($exp:expr) => {
-        ptr::NonNull::new_unchecked($exp)
+        ptr::NonNull::new($exp).expect("M4RI failed to allocate a matrix")
     };
 }
 
@@ -96,73 +218,176 @@ macro_rules! mul_impl {
 
 impl BinMatrix {
     /// Create a zero matrix
+    ///
+    /// Either dimension may be zero.
     pub fn zero(rows: usize, cols: usize) -> BinMatrix {
-        if rows == 0 || cols == 0 {
-            panic!("Can't create a 0 matrix");
-        }
-        let mzd = unsafe { nonnull!(mzd_init(rows as c_int, cols as c_int)) };
-        BinMatrix { mzd }
+        unwrap(BinMatrix::try_zero(rows, cols))
+    }
+
+    /// Create a zero matrix, or return an error if it can't be allocated
+    pub fn try_zero(rows: usize, cols: usize) -> Result<BinMatrix, MatrixError> {
+        try_init(rows, cols).map(|mzd| BinMatrix { mzd })
     }
 
     /// Create a new matrix
+    ///
+    /// Without rows this is a 0x0 matrix.
+    ///
+    /// **Panics** if the rows don't all have the same length
     pub fn new(rows: Vec<BinVector>) -> BinMatrix {
-        let rowlen = rows[0].len();
-        let storage: Vec<Vec<u64>> = rows
-            .iter()
-            .map(|vec| {
-                vec.get_storage()
-                    .into_iter()
-                    .copied()
-                    .map(|b| b as u64)
-                    .collect()
-            })
-            .collect();
-        BinMatrix::from_slices(&storage, rowlen)
+        let rowlen = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == rowlen),
+            "All rows need to have the same length"
+        );
+        BinMatrix::from_slices(&rows, rowlen)
     }
 
     /// Create a new matrix from slices
+    ///
+    /// Each row needs at least `ceil(rowlen / 64)` words, stored least significant bit first.
+    /// Bits past `rowlen` are ignored.
     pub fn from_slices<T: AsRef<[u64]>>(rows: &[T], rowlen: usize) -> BinMatrix {
-        if rows.is_empty() || rowlen == 0 {
-            panic!("Can't create a 0 matrix");
+        let mut result = BinMatrix::zero(rows.len(), rowlen);
+        for (row_index, row) in rows.iter().enumerate() {
+            result.copy_words_into_row(row_index, row.as_ref());
         }
+        result
+    }
 
-        for row in rows {
-            debug_assert!(row.as_ref().len() * 64 >= rowlen, "expected len {} bits but got only {} blocks", rowlen, row.as_ref().len());
+    /// Create a new matrix from an iterator over rows
+    ///
+    /// Rows are given as words like in `from_slices`, so this accepts
+    /// `BinVector`s, `BinVectorRef`s (e.g. rows of another matrix) or word slices.
+    /// Every row is copied word by word.
+    ///
+    /// # Example
+    /// ```
+    /// # use m4ri_rust::friendly::BinMatrix;
+    /// let m = BinMatrix::random(10, 70);
+    /// let even_rows = BinMatrix::from_rows_iter((0..10).step_by(2).map(|i| m.row_ref(i)), 70);
+    /// assert_eq!(even_rows.nrows(), 5);
+    /// assert_eq!(even_rows.row_ref(1), m.row_ref(2));
+    /// ```
+    pub fn from_rows_iter<I>(rows: I, rowlen: usize) -> BinMatrix
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u64]>,
+    {
+        let rows: Vec<I::Item> = rows.into_iter().collect();
+        BinMatrix::from_slices(&rows, rowlen)
+    }
+
+    /// Create a matrix from a single buffer of packed words
+    ///
+    /// In `Layout::RowMajor` every row takes `ceil(ncols / 64)` words,
+    /// like `row_words`. In `Layout::ColumnMajor` every column takes
+    /// `ceil(nrows / 64)` words. Padding bits are ignored.
+    ///
+    /// **Panics** if `words` doesn't have exactly the right length
+    ///
+    /// # Example
+    /// ```
+    /// # use m4ri_rust::friendly::{BinMatrix, Layout};
+    /// let m = BinMatrix::from_words(2, 3, &[0b101, 0b110], Layout::RowMajor);
+    /// assert_eq!(m, "101; 011".parse().unwrap());
+    /// let columns = BinMatrix::from_words(2, 3, &[0b01, 0b10, 0b11], Layout::ColumnMajor);
+    /// assert_eq!(columns, m);
+    /// assert_eq!(m.to_words(Layout::ColumnMajor), vec![0b01, 0b10, 0b11]);
+    /// ```
+    pub fn from_words(nrows: usize, ncols: usize, words: &[u64], layout: Layout) -> BinMatrix {
+        match layout {
+            Layout::RowMajor => {
+                let width = ncols.div_ceil(64);
+                assert_eq!(
+                    words.len(),
+                    nrows * width,
+                    "A {}x{} matrix needs {} words",
+                    nrows,
+                    ncols,
+                    nrows * width
+                );
+                let mut result = BinMatrix::zero(nrows, ncols);
+                if width > 0 {
+                    for (row, words) in words.chunks(width).enumerate() {
+                        result.copy_words_into_row(row, words);
+                    }
+                }
+                result
+            }
+            Layout::ColumnMajor => {
+                BinMatrix::from_words(ncols, nrows, words, Layout::RowMajor).transposed()
+            }
         }
+    }
 
-        let mzd_ptr = unsafe { mzd_init(rows.len() as c_int, rowlen as c_int) };
+    /// Copy the matrix into a single buffer of packed words
+    ///
+    /// This is the inverse of `from_words`, the padding bits are zero.
+    pub fn to_words(&self, layout: Layout) -> Vec<u64> {
+        match layout {
+            Layout::RowMajor => self.rows().flat_map(|row| row.masked_words()).collect(),
+            Layout::ColumnMajor => self.transposed().to_words(Layout::RowMajor),
+        }
+    }
 
-        let blocks_per_row = rowlen / 64 + if rowlen % 64 == 0 { 0 } else { 1 };
-        // Directly write to the underlying Mzd storage
-        for (row_index, row) in rows.into_iter().enumerate() {
-            let row_ptr: *const *mut Word = unsafe { (*mzd_ptr).rows.add(row_index) };
-            for (block_index, row_block) in row
-                .as_ref()
-                .iter()
-                .take(blocks_per_row)
-                .copied()
-                .enumerate()
-            {
+    /// Create a matrix from a single buffer of packed bytes
+    ///
+    /// Like `from_words`, but every row (or column) takes `ceil(ncols / 8)`
+    /// (or `ceil(nrows / 8)`) bytes, with the bits in each byte in the given order.
+    ///
+    /// **Panics** if `bytes` doesn't have exactly the right length
+    ///
+    /// # Example
+    /// ```
+    /// # use m4ri_rust::friendly::{BinMatrix, BitOrder, Layout};
+    /// let m = BinMatrix::from_bytes(2, 3, &[0b101_00000, 0b011_00000], Layout::RowMajor, BitOrder::MsbFirst);
+    /// assert_eq!(m, "101; 011".parse().unwrap());
+    /// assert_eq!(m.to_bytes(Layout::RowMajor, BitOrder::LsbFirst), vec![0b101, 0b110]);
+    /// ```
+    pub fn from_bytes(
+        nrows: usize,
+        ncols: usize,
+        bytes: &[u8],
+        layout: Layout,
+        order: BitOrder,
+    ) -> BinMatrix {
+        match layout {
+            Layout::RowMajor => {
+                let width = ncols.div_ceil(8);
                 assert_eq!(
-                    ::std::mem::size_of::<usize>(),
-                    ::std::mem::size_of::<u64>(),
-                    "only works on 64 bit"
+                    bytes.len(),
+                    nrows * width,
+                    "A {}x{} matrix needs {} bytes",
+                    nrows,
+                    ncols,
+                    nrows * width
                 );
-                let row_block = if block_index == rowlen / 64 {
-                    row_block & ((1 << (rowlen % 64)) - 1)
-                } else {
-                    row_block
-                };
-                unsafe {
-                    *((*row_ptr).add(block_index)) = row_block as u64;
+                let mut result = BinMatrix::zero(nrows, ncols);
+                if width > 0 {
+                    for (row, bytes) in bytes.chunks(width).enumerate() {
+                        let vector = BinVector::from_le_bytes(bytes, order);
+                        result.copy_words_into_row(row, vector.as_words());
+                    }
                 }
+                result
+            }
+            Layout::ColumnMajor => {
+                BinMatrix::from_bytes(ncols, nrows, bytes, Layout::RowMajor, order).transposed()
             }
         }
+    }
 
-        unsafe {
-            BinMatrix {
-                mzd: nonnull!(mzd_ptr),
-            }
+    /// Copy the matrix into a single buffer of packed bytes
+    ///
+    /// This is the inverse of `from_bytes`, the padding bits are zero.
+    pub fn to_bytes(&self, layout: Layout, order: BitOrder) -> Vec<u8> {
+        match layout {
+            Layout::RowMajor => self
+                .rows()
+                .flat_map(|row| row.to_vector().to_le_bytes(order))
+                .collect(),
+            Layout::ColumnMajor => self.transposed().to_bytes(Layout::RowMajor, order),
         }
     }
 
@@ -171,35 +396,35 @@ impl BinMatrix {
     /// **Panics** if ``nrows > 1 && ncols > 1``
     pub fn count_ones(&self) -> u32 {
         assert!(self.nrows() == 1 || self.ncols() == 1, "only works on single row or single column matrices");
-        let mut accumulator = 0;
-        for row in 0..self.nrows() {
-            let row_ptr: *const *mut Word = unsafe { (*self.mzd.as_ptr()).rows.add(row) };
-            for i in 0..(self.ncols() / 64) {
-                let word_ptr: *const Word = unsafe { (*row_ptr).add(i) };
-                accumulator += unsafe { (*word_ptr).count_ones() };
-            }
-            // process last block
-            if self.ncols() % 64 != 0 {
-                let word_ptr: *const Word = unsafe { (*row_ptr).add((self.ncols() - 1) / 64) };
-                let word = unsafe { *word_ptr } & ((1 << self.ncols() % 64) - 1);
-                accumulator += word.count_ones();
-            }
-        }
-        accumulator
+        (0..self.nrows())
+            .map(|row| self.row_ref(row).count_ones())
+            .sum()
     }
 
     /// Construct a randomized matrix
     pub fn random(rows: usize, columns: usize) -> BinMatrix {
-        let mzd = unsafe { mzd_init(rows as Rci, columns as Rci) };
-        // Randomize
-        unsafe {
-            mzd_randomize(mzd);
+        unwrap(BinMatrix::try_random(rows, columns))
+    }
+
+    /// Construct a randomized matrix, or return an error if it can't be allocated
+    pub fn try_random(rows: usize, columns: usize) -> Result<BinMatrix, MatrixError> {
+        let matrix = BinMatrix::try_zero(rows, columns)?;
+        if !matrix.is_empty() {
+            unsafe {
+                mzd_randomize(matrix.mzd.as_ptr());
+            }
         }
-        unsafe { BinMatrix { mzd: nonnull!(mzd) } }
+        Ok(matrix)
     }
 
     /// Construct a BinMatrix from the raw mzd pointer
-    pub fn from_mzd(mzd: *mut Mzd) -> BinMatrix {
+    ///
+    /// # Safety
+    /// `mzd` needs to be a matrix allocated with `mzd_init` that is not a window
+    /// and is not used or freed elsewhere, as the BinMatrix takes ownership of it.
+    ///
+    /// **Panics** if `mzd` is NULL
+    pub unsafe fn from_mzd(mzd: *mut Mzd) -> BinMatrix {
         let mzd = ptr::NonNull::new(mzd).expect("Can't be NULL");
         BinMatrix { mzd }
     }
@@ -207,40 +432,147 @@ impl BinMatrix {
     /// Get an identity matrix
     #[inline]
     pub fn identity(rows: usize) -> BinMatrix {
-        unsafe {
-            let mzd_ptr = mzd_init(rows as c_int, rows as c_int);
-            mzd_set_ui(mzd_ptr, 1);
-            let mzd = nonnull!(mzd_ptr);
-            BinMatrix { mzd }
+        unwrap(BinMatrix::try_identity(rows))
+    }
+
+    /// Get an identity matrix, or return an error if it can't be allocated
+    pub fn try_identity(rows: usize) -> Result<BinMatrix, MatrixError> {
+        let mzd = try_init(rows, rows)?;
+        unsafe { mzd_set_ui(mzd.as_ptr(), 1) };
+        Ok(BinMatrix { mzd })
+    }
+
+    /// The number of rows and columns, to report mismatches
+    fn dimensions(&self) -> (usize, usize) {
+        (self.nrows(), self.ncols())
+    }
+
+    /// Check if the matrix has no rows or no columns
+    ///
+    /// M4RI accesses the last word of every row in many functions, which such matrices don't have.
+    fn is_empty(&self) -> bool {
+        self.nrows() == 0 || self.ncols() == 0
+    }
+
+    /// Return a mismatch error for `operation` if `matches` is false
+    fn check_dimensions(
+        &self,
+        other: &BinMatrix,
+        operation: &'static str,
+        matches: bool,
+    ) -> Result<(), MatrixError> {
+        if matches {
+            Ok(())
+        } else {
+            Err(MatrixError::DimensionMismatch {
+                operation,
+                left: self.dimensions(),
+                right: other.dimensions(),
+            })
         }
     }
 
     /// Augment the matrix:
     ///  ``[A] [B] => [A B]``
+    ///
+    /// **Panics** if the number of rows differs
     #[inline]
     pub fn augmented(&self, other: &BinMatrix) -> BinMatrix {
-        debug_assert_eq!(self.nrows(), other.nrows(), "The rows need to be equal");
+        unwrap(self.try_augmented(other))
+    }
+
+    /// Augment the matrix, or return an error if the number of rows differs
+    pub fn try_augmented(&self, other: &BinMatrix) -> Result<BinMatrix, MatrixError> {
+        self.check_dimensions(other, "augment", self.nrows() == other.nrows())?;
+        let target = try_init(self.nrows(), self.ncols() + other.ncols())?;
         let mzd = unsafe {
             nonnull!(mzd_concat(
-                ptr::null_mut(),
+                target.as_ptr(),
                 self.mzd.as_ptr(),
                 other.mzd.as_ptr()
             ))
         };
-        BinMatrix { mzd }
+        Ok(BinMatrix { mzd })
     }
 
     /// Stack the matrix with another and return the result
+    ///
+    /// **Panics** if the number of columns differs
     #[inline]
     pub fn stacked(&self, other: &BinMatrix) -> BinMatrix {
+        unwrap(self.try_stacked(other))
+    }
+
+    /// Stack the matrix with another, or return an error if the number of columns differs
+    pub fn try_stacked(&self, other: &BinMatrix) -> Result<BinMatrix, MatrixError> {
+        self.check_dimensions(other, "stack", self.ncols() == other.ncols())?;
+        let target = try_init(self.nrows() + other.nrows(), self.ncols())?;
         let mzd = unsafe {
             nonnull!(mzd_stack(
-                ptr::null_mut(),
+                target.as_ptr(),
                 self.mzd.as_ptr(),
                 other.mzd.as_ptr()
             ))
         };
-        BinMatrix { mzd }
+        Ok(BinMatrix { mzd })
+    }
+
+    /// Compute the product of two matrices
+    ///
+    /// Returns an error if the number of columns of `self` is not the number of rows of `other`.
+    pub fn try_mul(&self, other: &BinMatrix) -> Result<BinMatrix, MatrixError> {
+        self.check_dimensions(other, "multiply", self.ncols() == other.nrows())?;
+        let target = try_init(self.nrows(), other.ncols())?;
+        if self.is_empty() || other.is_empty() {
+            return Ok(BinMatrix { mzd: target });
+        }
+        unsafe {
+            #[cfg(feature = "parallel")]
+            let mzd_ptr =
+                if ::parallel::use_parallel(&[self.nrows(), self.ncols(), other.ncols()]) {
+                    mzd_mul_mp(target.as_ptr(), self.mzd.as_ptr(), other.mzd.as_ptr(), 0)
+                } else {
+                    mul_impl!(target.as_ptr(), self.mzd.as_ptr(), other.mzd.as_ptr())
+                };
+            #[cfg(not(feature = "parallel"))]
+            let mzd_ptr = mul_impl!(target.as_ptr(), self.mzd.as_ptr(), other.mzd.as_ptr());
+
+            Ok(BinMatrix {
+                mzd: nonnull!(mzd_ptr),
+            })
+        }
+    }
+
+    /// Add up two matrices
+    ///
+    /// Returns an error if the dimensions differ.
+    pub fn try_add(&self, other: &BinMatrix) -> Result<BinMatrix, MatrixError> {
+        self.check_dimensions(other, "add", self.dimensions() == other.dimensions())?;
+        let target = try_init(self.nrows(), self.ncols())?;
+        if self.is_empty() {
+            return Ok(BinMatrix { mzd: target });
+        }
+        let mzd = unsafe {
+            nonnull!(mzd_add(
+                target.as_ptr(),
+                self.mzd.as_ptr(),
+                other.mzd.as_ptr()
+            ))
+        };
+        Ok(BinMatrix { mzd })
+    }
+
+    /// Add another matrix to this one in-place
+    ///
+    /// Returns an error if the dimensions differ.
+    pub fn try_add_assign(&mut self, other: &BinMatrix) -> Result<(), MatrixError> {
+        self.check_dimensions(other, "add", self.dimensions() == other.dimensions())?;
+        if !self.is_empty() {
+            unsafe {
+                mzd_add(self.mzd.as_ptr(), self.mzd.as_ptr(), other.mzd.as_ptr());
+            }
+        }
+        Ok(())
     }
 
     /// Get the rank of the matrix
@@ -256,20 +588,155 @@ impl BinMatrix {
     /// Return: the rank of the matrix
     #[inline]
     pub fn echelonize(&mut self) -> usize {
-        let rank = unsafe { mzd_echelonize(self.mzd.as_ptr(), false as c_int) };
+        self.echelonize_with(false)
+    }
+
+    /// Bring this matrix in reduced row echelon form in-place
+    ///
+    /// Return: the rank of the matrix
+    pub(crate) fn echelonize_reduced(&mut self) -> usize {
+        self.echelonize_with(true)
+    }
+
+    /// Bring this matrix in reduced row echelon form until `max_pivots` pivots are found
+    ///
+    /// Only the first `pivot_cols` columns are searched for pivots, the others
+    /// are just reduced along. Unlike M4RI this stops early, which information-set
+    /// decoding needs. The first rows of the result have a pivot each.
+    ///
+    /// Return: the column of the pivot of each of these rows
+    pub(crate) fn echelonize_reduced_partial(
+        &mut self,
+        max_pivots: usize,
+        pivot_cols: usize,
+    ) -> Vec<usize> {
+        let max_pivots = max_pivots.min(self.nrows());
+        let mut pivots = Vec::with_capacity(max_pivots);
+        for col in 0..pivot_cols.min(self.ncols()) {
+            if pivots.len() == max_pivots {
+                break;
+            }
+            let (word, bit) = (col / 64, col % 64);
+            // Safety: `row` is always below `nrows()`, and `col` below `ncols()`
+            let has_bit =
+                |m: &BinMatrix, row| unsafe { m.get_word_unchecked(row, word) } >> bit & 1 == 1;
+            let row = pivots.len();
+            let found = match (row..self.nrows()).find(|&other| has_bit(self, other)) {
+                Some(found) => found,
+                None => continue,
+            };
+            self.swap_rows(row, found);
+            for other in 0..self.nrows() {
+                if other != row && has_bit(self, other) {
+                    // The pivot row is zero left of `col`
+                    self.add_row_from(row, other, word);
+                }
+            }
+            pivots.push(col);
+        }
+        pivots
+    }
+
+    /// Swap the contents of two rows
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        let width = self.width();
+        unsafe {
+            let rows = (*self.mzd.as_ptr()).rows;
+            let a: *mut Word = *rows.add(a);
+            let b: *mut Word = *rows.add(b);
+            ptr::swap_nonoverlapping(a, b, width);
+        }
+    }
+
+    /// Add row `source` to row `target`, from word `start` on
+    fn add_row_from(&mut self, source: usize, target: usize, start: usize) {
+        debug_assert!(source != target && source < self.nrows() && target < self.nrows());
+        let width = self.width();
+        unsafe {
+            let rows = (*self.mzd.as_ptr()).rows;
+            let source: *const Word = *rows.add(source);
+            let target: *mut Word = *rows.add(target);
+            for word in start..width {
+                *target.add(word) ^= *source.add(word);
+            }
+        }
+    }
+
+    /// A basis of the right kernel, all `x` with `self * x = 0`, as the rows of a matrix
+    pub(crate) fn kernel_rows(&self) -> BinMatrix {
+        let ncols = self.ncols();
+        if self.is_empty() {
+            return BinMatrix::identity(ncols);
+        }
+        let copy = self.clone();
+        // Overwrites the copy; NULL means that the kernel is trivial
+        let kernel = unsafe { mzd_kernel_left_pluq(copy.mzd.as_ptr(), 0) };
+        if kernel.is_null() {
+            return BinMatrix::zero(0, ncols);
+        }
+        unsafe { BinMatrix::from_mzd(kernel) }.transposed()
+    }
+
+    fn echelonize_with(&mut self, full: bool) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        #[cfg(feature = "parallel")]
+        {
+            if ::parallel::use_parallel(&[self.nrows(), self.ncols()]) {
+                let rank = unsafe { mzd_echelonize_pluq(self.mzd.as_ptr(), full as c_int) };
+                return rank as usize;
+            }
+        }
+        let rank = unsafe { mzd_echelonize(self.mzd.as_ptr(), full as c_int) };
         rank as usize
     }
 
     /// Compute the inverse of this matrix, returns a new matrix
+    ///
+    /// The result is meaningless if the matrix is not invertible,
+    /// use `try_inverted` to check that.
+    ///
+    /// **Panics** if the matrix is not square
     #[inline]
     pub fn inverted(&self) -> BinMatrix {
-        let mzd = unsafe { nonnull!(mzd_inv_m4ri(ptr::null_mut(), self.mzd.as_ptr(), 0 as c_int)) };
+        let (nrows, ncols) = self.dimensions();
+        if nrows != ncols {
+            panic!("{}", MatrixError::NotSquare { nrows, ncols });
+        }
+        let target = unwrap(try_init(self.nrows(), self.ncols()));
+        if self.is_empty() {
+            return BinMatrix { mzd: target };
+        }
+        let mzd =
+            unsafe { nonnull!(mzd_inv_m4ri(target.as_ptr(), self.mzd.as_ptr(), 0 as c_int)) };
         BinMatrix { mzd }
     }
 
+    /// Compute the inverse of this matrix
+    ///
+    /// Returns an error if the matrix is not square or not invertible.
+    /// Checking the latter costs an extra echelonization.
+    pub fn try_inverted(&self) -> Result<BinMatrix, MatrixError> {
+        let (nrows, ncols) = self.dimensions();
+        if nrows != ncols {
+            return Err(MatrixError::NotSquare { nrows, ncols });
+        }
+        if self.rank() < nrows {
+            return Err(MatrixError::Singular);
+        }
+        Ok(self.inverted())
+    }
+
     /// Compute the transpose of the matrix
     #[inline]
     pub fn transposed(&self) -> BinMatrix {
+        if self.is_empty() {
+            return BinMatrix::zero(self.ncols(), self.nrows());
+        }
         let mzd;
         unsafe {
             let mzd_ptr = mzd_transpose(ptr::null_mut(), self.mzd.as_ptr());
@@ -294,36 +761,208 @@ impl BinMatrix {
         unsafe { self.mzd.as_ref().ncols as usize }
     }
 
-    /// Get a single word from the matrix at a certain offset
-    pub fn get_word(&self, row: usize, column: usize) -> Word {
-        assert!(row < self.nrows());
-        assert!(column < self.ncols());
+    /// Number of words per row
+    #[inline]
+    fn width(&self) -> usize {
+        unsafe { self.mzd.as_ref().width as usize }
+    }
+
+    /// Show the matrix in another style than `Display`, one row per line
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::{BinMatrix, DisplayStyle};
+    /// let m = BinMatrix::identity(2);
+    /// assert_eq!(m.display(DisplayStyle::Dots).to_string(), "[1 .]\n[. 1]");
+    /// ```
+    pub fn display(&self, style: DisplayStyle) -> Displayed<'_> {
+        Displayed::matrix(self, style)
+    }
+
+    /// Get the words that make up a row
+    ///
+    /// The bits are stored least significant bit first.
+    /// Bits past `ncols()` in the last word should be zero, but this is not guaranteed.
+    ///
+    /// O(1)
+    #[inline]
+    pub fn row_words(&self, row: usize) -> &[Word] {
+        assert!(row < self.nrows(), "row {} out of bounds", row);
+        let width = self.width();
+        if width == 0 {
+            return &[];
+        }
+        unsafe {
+            let row_ptr: *const Word = *(*self.mzd.as_ptr()).rows.add(row);
+            slice::from_raw_parts(row_ptr, width)
+        }
+    }
+
+    /// Iterate over the rows without copying
+    #[inline]
+    pub fn rows(&self) -> Rows<'_> {
+        Rows {
+            matrix: self,
+            range: 0..self.nrows(),
+        }
+    }
+
+    /// Borrow a row as a vector without copying
+    ///
+    /// O(1)
+    #[inline]
+    pub fn row_ref(&self, row: usize) -> BinVectorRef<'_> {
+        BinVectorRef::new(self.row_words(row), self.ncols())
+    }
+
+    /// Overwrite a row with a vector
+    ///
+    /// O(words)
+    ///
+    /// **Panics** if the length of the vector is not `ncols()`.
+    pub fn set_row<'a, V: Into<BinVectorRef<'a>>>(&mut self, row: usize, vector: V) {
+        let vector = vector.into();
+        assert_eq!(
+            vector.len(),
+            self.ncols(),
+            "The vector needs to have as many bits as there are columns"
+        );
+        self.copy_words_into_row(row, vector.as_words());
+    }
+
+    /// Overwrite a row with packed words, like `set_row` without a `BinVector`
+    ///
+    /// Bits past `ncols()` in the last word are ignored.
+    ///
+    /// **Panics** if `words` doesn't have exactly `ceil(ncols / 64)` words
+    pub fn write_row_words(&mut self, row: usize, words: &[u64]) {
+        assert_eq!(
+            words.len(),
+            self.width(),
+            "A row of {} bits needs {} words",
+            self.ncols(),
+            self.width()
+        );
+        self.copy_words_into_row(row, words);
+    }
+
+    /// Copy the first words of `words` into `row`, ignoring bits past `ncols()`
+    pub(crate) fn copy_words_into_row(&mut self, row: usize, words: &[u64]) {
+        assert!(row < self.nrows(), "row {} out of bounds", row);
+        let width = self.width();
+        assert!(
+            words.len() >= width,
+            "expected len {} bits but got only {} blocks",
+            self.ncols(),
+            words.len()
+        );
+        if width == 0 {
+            return;
+        }
+        let target = unsafe {
+            let row_ptr: *mut Word = *(*self.mzd.as_ptr()).rows.add(row);
+            slice::from_raw_parts_mut(row_ptr, width)
+        };
+        target.copy_from_slice(&words[..width]);
+        if !self.ncols().is_multiple_of(64) {
+            target[width - 1] &= (1 << (self.ncols() % 64)) - 1;
+        }
+    }
+
+    /// Get the mutable words that make up a row
+    ///
+    /// The bits are stored least significant bit first.
+    /// M4RI relies on the bits past `ncols()` in the last word being zero,
+    /// so the returned guard clears them again when it is dropped.
+    ///
+    /// O(1)
+    #[inline]
+    pub fn row_words_mut(&mut self, row: usize) -> RowWordsMut<'_> {
+        assert!(row < self.nrows(), "row {} out of bounds", row);
+        let width = self.width();
+        let last_word_mask = self.last_word_mask();
+        let words = if width == 0 {
+            &mut []
+        } else {
+            unsafe {
+                let row_ptr: *mut Word = *(*self.mzd.as_ptr()).rows.add(row);
+                slice::from_raw_parts_mut(row_ptr, width)
+            }
+        };
+        RowWordsMut {
+            words,
+            last_word_mask,
+        }
+    }
+
+    /// The bits of the last word of a row that are within `ncols()`
+    fn last_word_mask(&self) -> Word {
+        match self.ncols() % 64 {
+            0 => !0,
+            bits => (1 << bits) - 1,
+        }
+    }
 
-        unsafe { self.get_word_unchecked(row, column) }
+    /// Get a single word from the matrix
+    ///
+    /// `word` indexes the words of the row, so column `c` is in word `c / 64`.
+    ///
+    /// **Panics** if `row >= nrows()` or `word` is not below the number of words per row.
+    pub fn get_word(&self, row: usize, word: usize) -> Word {
+        self.check_word_index(row, word);
+        unsafe { self.get_word_unchecked(row, word) }
     }
 
-    /// Get a particular word from the matrix
-    /// Does not do any bounds checking!
+    /// Get a single word from the matrix without bounds checking
+    ///
+    /// # Safety
+    /// `row` needs to be below `nrows()` and `word` below `ncols().div_ceil(64)`.
     #[inline]
-    pub unsafe fn get_word_unchecked(&self, row: usize, column: usize) -> Word {
-        let row_ptr: *const *mut Word = (*self.mzd.as_ptr()).rows.add(row);
-        let word_ptr: *const Word = ((*row_ptr) as *const Word).add(column);
-        *word_ptr
+    pub unsafe fn get_word_unchecked(&self, row: usize, word: usize) -> Word {
+        let row_ptr: *const Word = *(*self.mzd.as_ptr()).rows.add(row);
+        *row_ptr.add(word)
     }
 
-    /// Get a mutable reference to a particular word in the matrix
-    pub fn get_word_mut(&self, row: usize, column: usize) -> &mut Word {
-        assert!(row < self.nrows());
-        assert!(column < self.ncols());
-        unsafe { self.get_word_mut_unchecked(row, column) }
+    /// Get a mutable reference to a single word in the matrix
+    ///
+    /// `word` indexes the words of the row, so column `c` is in word `c / 64`.
+    /// Bits past `ncols()` in the last word are cleared when the guard is dropped.
+    ///
+    /// **Panics** if `row >= nrows()` or `word` is not below the number of words per row.
+    pub fn get_word_mut(&mut self, row: usize, word: usize) -> WordMut<'_> {
+        self.check_word_index(row, word);
+        let mask = if word + 1 == self.width() {
+            self.last_word_mask()
+        } else {
+            !0
+        };
+        WordMut {
+            word: unsafe { self.get_word_mut_unchecked(row, word) },
+            mask,
+        }
     }
 
-    /// Get a mutable reference to a particular word in the matrix without bounds checking.
+    /// Get a mutable reference to a single word in the matrix without bounds checking
+    ///
+    /// # Safety
+    /// `row` needs to be below `nrows()` and `word` below `ncols().div_ceil(64)`.
+    /// Bits past `ncols()` in the last word need to stay zero,
+    /// M4RI relies on this for comparisons and some of its algorithms.
     #[inline]
-    pub unsafe fn get_word_mut_unchecked(&self, row: usize, column: usize) -> &mut Word {
-        let row_ptr: *const *mut Word = (*self.mzd.as_ptr()).rows.add(row);
-        let word_ptr: *mut Word = ((*row_ptr) as *mut Word).add(column / 64);
-        word_ptr.as_mut().unwrap()
+    pub unsafe fn get_word_mut_unchecked(&mut self, row: usize, word: usize) -> &mut Word {
+        let row_ptr: *mut Word = *(*self.mzd.as_ptr()).rows.add(row);
+        &mut *row_ptr.add(word)
+    }
+
+    fn check_word_index(&self, row: usize, word: usize) {
+        assert!(
+            row < self.nrows() && word < self.width(),
+            "Word ({}, {}) out of bounds for a {}x{} matrix with {} words per row",
+            row,
+            word,
+            self.nrows(),
+            self.ncols(),
+            self.width()
+        );
     }
 
     /// Get as a vector
@@ -332,112 +971,373 @@ impl BinMatrix {
     pub fn as_vector(&self) -> BinVector {
         if self.nrows() != 1 {
             assert_eq!(self.ncols(), 1, "needs to have only one column or row");
-            self.transposed().as_vector()
+            let mut words = vec![0 as Word; self.nrows().div_ceil(64)];
+            for row in 0..self.nrows() {
+                words[row / 64] |= (self.row_words(row)[0] & 1) << (row % 64);
+            }
+            BinVector::from_words(&words, self.nrows())
         } else {
-            assert_eq!(self.nrows(), 1, "needs to have only one column or row");
-            let mut bits = BinVector::with_capacity(self.ncols());
-            {
-                let collector = unsafe { bits.get_storage_mut() };
-                for i in 0..(self.ncols() / 64) {
-                    let row_ptr: *const *mut Word = unsafe { (*self.mzd.as_ptr()).rows };
-                    let word_ptr: *const Word = unsafe { ((*row_ptr) as *const Word).add(i) };
-                    collector.push(unsafe { *word_ptr as usize });
+            self.row_ref(0).to_vector()
+        }
+    }
+
+    /// Get a certain bit
+    ///
+    /// **Panics** if the position is out of bounds
+    pub fn bit(&self, row: usize, col: usize) -> bool {
+        self.check_bit_index(row, col);
+        let bit = unsafe { mzd_read_bit(self.mzd.as_ptr(), row as Rci, col as Rci) };
+        debug_assert!(bit == 0 || bit == 1, "Invalid bool for bit??");
+        bit == 1
+    }
+
+    /// Set a certain bit
+    ///
+    /// **Panics** if the position is out of bounds
+    pub fn set_bit(&mut self, row: usize, col: usize, bit: bool) {
+        self.check_bit_index(row, col);
+        unsafe { mzd_write_bit(self.mzd.as_ptr(), row as Rci, col as Rci, bit as BIT) }
+    }
+
+    /// Swap two columns
+    ///
+    /// **Panics** if a column is out of bounds
+    pub(crate) fn swap_columns(&mut self, a: usize, b: usize) {
+        assert!(
+            a < self.ncols() && b < self.ncols(),
+            "Columns {} and {} out of bounds for a matrix with {} columns",
+            a,
+            b,
+            self.ncols()
+        );
+        if a != b && self.nrows() > 0 {
+            unsafe { mzd_col_swap(self.mzd.as_ptr(), a as Rci, b as Rci) }
+        }
+    }
+
+    fn check_bit_index(&self, row: usize, col: usize) {
+        assert!(
+            row < self.nrows() && col < self.ncols(),
+            "Position ({}, {}) out of bounds for a {}x{} matrix",
+            row,
+            col,
+            self.nrows(),
+            self.ncols()
+        );
+    }
+
+    /// Get a window from the matrix. Makes a copy.
+    pub fn get_window(
+        &self,
+        start_row: usize,
+        start_col: usize,
+        high_row: usize,
+        high_col: usize,
+    ) -> BinMatrix {
+        let (rows, cols) = (high_row - start_row, high_col - start_col);
+        debug_assert!(rows > 0 && rows <= self.nrows());
+        debug_assert!(cols > 0 && cols <= self.ncols());
+        let mzd_ptr = unwrap(try_init(rows, cols)).as_ptr();
+        for (r, i) in (start_row..high_row).enumerate() {
+            // FIXME speed
+            for (c, j) in (start_col..high_col).enumerate() {
+                let bit = self.bit(i, j);
+                unsafe {
+                    mzd_write_bit(mzd_ptr, r as Rci, c as Rci, bit as BIT);
                 }
-                // process last block
-                if self.ncols() % 64 != 0 {
-                    let row_ptr: *const *mut Word = unsafe { (*self.mzd.as_ptr()).rows };
-                    let word_ptr: *const Word = unsafe { (*row_ptr).add((self.ncols() - 1) / 64) };
-                    let word = unsafe { *word_ptr };
-                    collector.push(word as usize);
+            }
+        }
+        unsafe { BinMatrix::from_mzd(mzd_ptr) }
+    }
+
+    /// Set a window in the matrix to another matrix
+    ///
+    /// Currently does bit-by-bit, should use more optimal means
+    /// if alignment allows it
+    pub fn set_window(&mut self, start_row: usize, start_col: usize, other: &BinMatrix) {
+        let highr = start_row + other.nrows();
+        let highc = start_col + other.ncols();
+        assert!(self.ncols() >= highc, "This matrix is too small!");
+        assert!(self.nrows() >= highr, "This matrix has too few rows !");
+        let mzd_ptr = self.mzd.as_ptr();
+
+        for r in start_row..highr {
+            for c in start_col..highc {
+                let bit = other.bit(r - start_row, c - start_col);
+                unsafe {
+                    mzd_write_bit(mzd_ptr, r as Rci, c as Rci, bit as BIT);
                 }
             }
-            unsafe {
-                bits.set_len(self.ncols());
-                bits.mask_last_block();
+        }
+    }
+
+    /// Multiply a matrix by a vector represented as a [u64]
+    pub fn mul_slice(&self, other: &[u64]) -> BinMatrix {
+        // I've tried to use thread-local storage for the temporary here, but it wasn't faster.
+        debug_assert!(
+            self.ncols() <= other.len() * 64,
+            "Mismatched sizes: ({}x{}) * ({}x1) (too big)",
+            self.nrows(),
+            self.ncols(),
+            other.len() * 64
+        );
+        let result = {
+            let other = BinMatrix::from_slices(&[other], self.ncols()).transposed();
+            unsafe { mzd_mul_naive(ptr::null_mut(), self.mzd.as_ptr(), other.mzd.as_ptr()) }
+        };
+        unsafe { BinMatrix::from_mzd(result) }
+    }
+}
+
+impl cmp::PartialEq for BinMatrix {
+    fn eq(&self, other: &BinMatrix) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return self.dimensions() == other.dimensions();
+        }
+        unsafe { mzd_equal(self.mzd.as_ptr(), other.mzd.as_ptr()) == 1 }
+    }
+}
+
+impl cmp::Eq for BinMatrix {}
+
+impl Hash for BinMatrix {
+    /// Hashes the dimensions and the words of the rows, without the padding bits
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.nrows().hash(state);
+        self.ncols().hash(state);
+        for row in self {
+            for word in row.masked_words() {
+                word.hash(state);
             }
+        }
+    }
+}
+
+impl cmp::PartialOrd for BinMatrix {
+    #[inline]
+    fn partial_cmp(&self, other: &BinMatrix) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl cmp::Ord for BinMatrix {
+    /// Orders by the number of rows, then columns, then the bits in row-major order
+    ///
+    /// The bits are compared lexicographically, with bit 0 of a row first and a one
+    /// ordered after a zero, which is also the order of the rows in `Display`.
+    fn cmp(&self, other: &BinMatrix) -> cmp::Ordering {
+        self.dimensions().cmp(&other.dimensions()).then_with(|| {
+            self.rows()
+                .zip(other.rows())
+                .map(|(a, b)| {
+                    a.masked_words()
+                        .map(u64::reverse_bits)
+                        .cmp(b.masked_words().map(u64::reverse_bits))
+                })
+                .find(|ordering| *ordering != cmp::Ordering::Equal)
+                .unwrap_or(cmp::Ordering::Equal)
+        })
+    }
+}
+
+impl Default for BinMatrix {
+    /// A 0x0 matrix
+    fn default() -> BinMatrix {
+        BinMatrix::zero(0, 0)
+    }
+}
+
+/// Iterator over the rows of a `BinMatrix`, see `BinMatrix::rows`
+#[derive(Clone, Debug)]
+pub struct Rows<'a> {
+    matrix: &'a BinMatrix,
+    range: ops::Range<usize>,
+}
+
+impl<'a> Iterator for Rows<'a> {
+    type Item = BinVectorRef<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<BinVectorRef<'a>> {
+        self.range.next().map(|row| self.matrix.row_ref(row))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Rows<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<BinVectorRef<'a>> {
+        self.range.next_back().map(|row| self.matrix.row_ref(row))
+    }
+}
+
+impl<'a> ExactSizeIterator for Rows<'a> {}
+
+/// The words of a row, borrowed mutably with `BinMatrix::row_words_mut`
+///
+/// Clears the bits past the last column when dropped.
+#[derive(Debug)]
+pub struct RowWordsMut<'a> {
+    words: &'a mut [Word],
+    last_word_mask: Word,
+}
+
+impl<'a> ops::Deref for RowWordsMut<'a> {
+    type Target = [Word];
+
+    #[inline]
+    fn deref(&self) -> &[Word] {
+        self.words
+    }
+}
+
+impl<'a> ops::DerefMut for RowWordsMut<'a> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [Word] {
+        self.words
+    }
+}
+
+impl<'a> Drop for RowWordsMut<'a> {
+    fn drop(&mut self) {
+        if let Some(last) = self.words.last_mut() {
+            *last &= self.last_word_mask;
+        }
+    }
+}
+
+/// A word of a matrix, borrowed mutably with `BinMatrix::get_word_mut`
+///
+/// Clears the bits past the last column when dropped.
+#[derive(Debug)]
+pub struct WordMut<'a> {
+    word: &'a mut Word,
+    mask: Word,
+}
+
+impl<'a> ops::Deref for WordMut<'a> {
+    type Target = Word;
+
+    #[inline]
+    fn deref(&self) -> &Word {
+        self.word
+    }
+}
+
+impl<'a> ops::DerefMut for WordMut<'a> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Word {
+        self.word
+    }
+}
 
-            bits
-        }
+impl<'a> Drop for WordMut<'a> {
+    fn drop(&mut self) {
+        *self.word &= self.mask;
     }
+}
 
-    /// Get a certain bit
-    pub fn bit(&self, row: usize, col: usize) -> bool {
-        let bit = unsafe { mzd_read_bit(self.mzd.as_ptr(), row as Rci, col as Rci) };
-        debug_assert!(bit == 0 || bit == 1, "Invalid bool for bit??");
-        bit == 1
+impl<'a> IntoIterator for &'a BinMatrix {
+    type Item = BinVectorRef<'a>;
+    type IntoIter = Rows<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Rows<'a> {
+        self.rows()
     }
+}
 
-    /// Get a window from the matrix. Makes a copy.
-    pub fn get_window(
-        &self,
-        start_row: usize,
-        start_col: usize,
-        high_row: usize,
-        high_col: usize,
-    ) -> BinMatrix {
-        let (rows, cols) = (high_row - start_row, high_col - start_col);
-        debug_assert!(rows > 0 && rows <= self.nrows());
-        debug_assert!(cols > 0 && cols <= self.ncols());
-        let mzd_ptr = unsafe { mzd_init(rows as Rci, cols as Rci) };
-        for (r, i) in (start_row..high_row).enumerate() {
-            // FIXME speed
-            for (c, j) in (start_col..high_col).enumerate() {
-                let bit = self.bit(i, j);
-                unsafe {
-                    mzd_write_bit(mzd_ptr, r as Rci, c as Rci, bit as BIT);
-                }
-            }
-        }
-        BinMatrix::from_mzd(mzd_ptr)
+impl IntoIterator for BinMatrix {
+    type Item = BinVector;
+    type IntoIter = std::vec::IntoIter<BinVector>;
+
+    /// Copies the rows into vectors
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows()
+            .map(|row| row.to_vector())
+            .collect::<Vec<_>>()
+            .into_iter()
     }
+}
 
-    /// Set a window in the matrix to another matrix
+impl iter::FromIterator<BinVector> for BinMatrix {
+    /// Use the vectors as rows
     ///
-    /// Currently does bit-by-bit, should use more optimal means
-    /// if alignment allows it
-    pub fn set_window(&mut self, start_row: usize, start_col: usize, other: &BinMatrix) {
-        let highr = start_row + other.nrows();
-        let highc = start_col + other.ncols();
-        debug_assert!(self.ncols() >= highc, "This matrix is too small!");
-        debug_assert!(self.nrows() >= highr, "This matrix has too few rows !");
-        let mzd_ptr = self.mzd.as_ptr();
+    /// **Panics** if the rows don't all have the same length
+    fn from_iter<I: IntoIterator<Item = BinVector>>(rows: I) -> BinMatrix {
+        BinMatrix::new(rows.into_iter().collect())
+    }
+}
 
-        for r in start_row..highr {
-            for c in start_col..highc {
-                let bit = other.bit(r - start_row, c - start_col);
-                unsafe {
-                    mzd_write_bit(mzd_ptr, r as Rci, c as Rci, bit as BIT);
-                }
-            }
-        }
+impl<'a> iter::FromIterator<BinVectorRef<'a>> for BinMatrix {
+    /// Use the vectors as rows
+    ///
+    /// **Panics** if the rows don't all have the same length
+    fn from_iter<I: IntoIterator<Item = BinVectorRef<'a>>>(rows: I) -> BinMatrix {
+        rows.into_iter().map(|row| row.to_vector()).collect()
     }
+}
 
-    /// Multiply a matrix by a vector represented as a [u64]
-    pub fn mul_slice(&self, other: &[u64]) -> BinMatrix {
-        // I've tried to use thread-local storage for the temporary here, but it wasn't faster.
-        debug_assert!(
-            self.ncols() <= other.len() * 64,
-            "Mismatched sizes: ({}x{}) * ({}x1) (too big)",
-            self.nrows(),
-            self.ncols(),
-            other.len() * 64
-        );
-        let result = {
-            let other = BinMatrix::from_slices(&[other], self.ncols()).transposed();
-            unsafe { mzd_mul_naive(ptr::null_mut(), self.mzd.as_ptr(), other.mzd.as_ptr()) }
+impl Extend<BinVector> for BinMatrix {
+    /// Append rows to the matrix
+    ///
+    /// This reallocates the matrix. A 0x0 matrix, like `BinMatrix::default()`,
+    /// takes the length of the first row.
+    ///
+    /// **Panics** if a row does not have `ncols()` bits
+    fn extend<I: IntoIterator<Item = BinVector>>(&mut self, rows: I) {
+        let rows: Vec<BinVector> = rows.into_iter().collect();
+        if rows.is_empty() {
+            return;
+        }
+        let ncols = if self.dimensions() == (0, 0) {
+            rows[0].len()
+        } else {
+            self.ncols()
         };
-        let matresult = BinMatrix::from_mzd(result);
-        matresult
+        assert!(
+            rows.iter().all(|row| row.len() == ncols),
+            "All rows need to have {} bits",
+            ncols
+        );
+        let mut result = BinMatrix::zero(self.nrows() + rows.len(), ncols);
+        for row in 0..self.nrows() {
+            result.copy_words_into_row(row, self.row_words(row));
+        }
+        for (i, row) in rows.iter().enumerate() {
+            result.copy_words_into_row(self.nrows() + i, row.as_words());
+        }
+        *self = result;
     }
 }
 
-impl cmp::PartialEq for BinMatrix {
-    fn eq(&self, other: &BinMatrix) -> bool {
-        unsafe { mzd_equal(self.mzd.as_ptr(), other.mzd.as_ptr()) == 1 }
+impl ops::Index<usize> for BinMatrix {
+    type Output = [Word];
+
+    /// The words of a row, like `row_words`
+    #[inline]
+    fn index(&self, row: usize) -> &[Word] {
+        self.row_words(row)
     }
 }
 
-impl cmp::Eq for BinMatrix {}
+impl ops::Index<(usize, usize)> for BinMatrix {
+    type Output = bool;
+
+    /// The bit at `(row, column)`
+    #[inline]
+    fn index(&self, (row, col): (usize, usize)) -> &bool {
+        if self.bit(row, col) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
 
 impl ops::Mul<BinMatrix> for BinMatrix {
     type Output = BinMatrix;
@@ -451,6 +1351,9 @@ impl ops::Mul<BinMatrix> for BinMatrix {
 
 impl std::clone::Clone for BinMatrix {
     fn clone(&self) -> Self {
+        if self.is_empty() {
+            return BinMatrix::zero(self.nrows(), self.ncols());
+        }
         let mzd = unsafe { nonnull!(mzd_copy(ptr::null_mut(), self.mzd.as_ptr())) };
         BinMatrix { mzd }
     }
@@ -461,13 +1364,7 @@ impl<'a> ops::Mul<&'a BinMatrix> for &'a BinMatrix {
     /// Computes the product of two matrices
     #[inline]
     fn mul(self, other: &BinMatrix) -> Self::Output {
-        unsafe {
-            let mzd_ptr = mul_impl!(ptr::null_mut(), self.mzd.as_ptr(), other.mzd.as_ptr());
-
-            BinMatrix {
-                mzd: ptr::NonNull::new(mzd_ptr).expect("Multiplication failed"),
-            }
-        }
+        unwrap(self.try_mul(other))
     }
 }
 
@@ -477,14 +1374,7 @@ impl<'a> ops::Add<&'a BinMatrix> for &'a BinMatrix {
     /// Add up two matrices
     #[inline]
     fn add(self, other: &BinMatrix) -> Self::Output {
-        let mzd = unsafe {
-            nonnull!(mzd_add(
-                ptr::null_mut(),
-                self.mzd.as_ptr(),
-                other.mzd.as_ptr()
-            ))
-        };
-        BinMatrix { mzd }
+        unwrap(self.try_add(other))
     }
 }
 
@@ -493,15 +1383,9 @@ impl ops::Add<BinMatrix> for BinMatrix {
 
     /// Add up two matrices, re-uses memory of A
     #[inline]
-    fn add(self, other: BinMatrix) -> Self::Output {
-        let mzd = unsafe {
-            nonnull!(mzd_add(
-                self.mzd.as_ptr(),
-                self.mzd.as_ptr(),
-                other.mzd.as_ptr()
-            ))
-        };
-        BinMatrix { mzd }
+    fn add(mut self, other: BinMatrix) -> Self::Output {
+        self += &other;
+        self
     }
 }
 
@@ -509,19 +1393,15 @@ impl ops::AddAssign<BinMatrix> for BinMatrix {
     /// Add up two matrices, re-uses memory of A
     #[inline]
     fn add_assign(&mut self, other: BinMatrix) {
-        unsafe {
-            mzd_add(self.mzd.as_ptr(), self.mzd.as_ptr(), other.mzd.as_ptr());
-        }
+        *self += &other;
     }
 }
 
-impl<'a> ops::AddAssign<&'a BinMatrix> for BinMatrix {
+impl ops::AddAssign<&BinMatrix> for BinMatrix {
     /// Add up two matrices, re-uses memory of A
     #[inline]
     fn add_assign(&mut self, other: &BinMatrix) {
-        unsafe {
-            mzd_add(self.mzd.as_ptr(), self.mzd.as_ptr(), other.mzd.as_ptr());
-        }
+        unwrap(self.try_add_assign(other))
     }
 }
 
@@ -530,14 +1410,21 @@ impl<'a> ops::Mul<&'a BinVector> for &'a BinMatrix {
     /// Computes (A * v^T)
     #[inline]
     fn mul(self, other: &BinVector) -> Self::Output {
-        self.mul_slice(
-            &other
-                .get_storage()
-                .iter()
-                .copied()
-                .map(|b| b as u64)
-                .collect::<Vec<u64>>(),
-        ).as_vector()
+        assert_eq!(
+            self.ncols(),
+            other.len(),
+            "Mismatched sizes: ({}x{}) * ({}x1)",
+            self.nrows(),
+            self.ncols(),
+            other.len()
+        );
+        let mut words = vec![0 as Word; self.nrows().div_ceil(64)];
+        for row in 0..self.nrows() {
+            if self.row_ref(row) * other {
+                words[row / 64] |= 1 << (row % 64);
+            }
+        }
+        BinVector::from_words(&words, self.nrows())
     }
 }
 
@@ -555,10 +1442,21 @@ impl<'a> ops::Mul<&'a BinMatrix> for &'a BinVector {
     #[inline]
     /// computes v^T * A
     fn mul(self, other: &BinMatrix) -> Self::Output {
-        let vec_mzd = self.as_matrix();
-        let tmp = &vec_mzd * other;
-
-        tmp.as_vector()
+        assert_eq!(
+            self.len(),
+            other.nrows(),
+            "Mismatched sizes: (1x{}) * ({}x{})",
+            self.len(),
+            other.nrows(),
+            other.ncols()
+        );
+        let mut words = vec![0 as Word; other.ncols().div_ceil(64)];
+        for row in self.iter_set_bits(..) {
+            for (word, row_word) in words.iter_mut().zip(other.row_words(row)) {
+                *word ^= row_word;
+            }
+        }
+        BinVector::from_words(&words, other.ncols())
     }
 }
 
@@ -579,15 +1477,34 @@ impl ops::Mul<BinMatrix> for BinVector {
 /// B will contain the solution afterwards
 ///
 /// Return True if it succeeded
+///
+/// **Panics** if A has more columns than rows, or if A and B have a different number of rows
 pub fn solve_left(a: BinMatrix, b: &mut BinMatrix) -> bool {
+    unwrap(try_solve_left(a, b))
+}
+
+/// Solve AX = B for X, like `solve_left`
+///
+/// Returns an error instead of panicking if the dimensions don't allow solving.
+pub fn try_solve_left(a: BinMatrix, b: &mut BinMatrix) -> Result<bool, MatrixError> {
+    a.check_dimensions(
+        b,
+        "solve with",
+        a.nrows() == b.nrows() && a.ncols() <= a.nrows(),
+    )?;
+    if a.is_empty() || b.is_empty() {
+        // Without columns in A, AX is zero, so there is a solution only if B is zero
+        return Ok(b.rows().all(|row| row.count_ones() == 0));
+    }
     let result = unsafe { mzd_solve_left(a.mzd.as_ptr(), b.mzd.as_ptr(), 0, 1) };
 
-    result == 0
+    Ok(result == 0)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use friendly::test_matrices;
     use rand::prelude::*;
     use vob::Vob;
 
@@ -685,11 +1602,414 @@ mod test {
         assert_eq!(result.len(), 3);
     }
 
+    #[test]
+    fn vecmul_random() {
+        for &(rows, cols) in &[(1, 1), (3, 64), (64, 3), (65, 130), (200, 100)] {
+            let m = BinMatrix::random(rows, cols);
+            let v = BinVector::random(cols);
+            assert_eq!(&m * &v, (&m * &v.as_column_matrix()).as_vector());
+            let v = BinVector::random(rows);
+            assert_eq!(&v * &m, (&v.as_matrix() * &m).as_vector());
+        }
+    }
+
+    #[test]
+    fn row_ref() {
+        let m = BinMatrix::random(10, 130);
+        for i in 0..10 {
+            let row = m.row_ref(i);
+            assert_eq!(row.len(), 130);
+            assert_eq!(row.as_words().len(), 3);
+            for j in 0..130 {
+                assert_eq!(row.get(j), Some(m.bit(i, j)));
+            }
+            assert_eq!(row.to_vector(), m.get_window(i, 0, i + 1, 130).as_vector());
+        }
+    }
+
+    #[test]
+    fn set_row() {
+        let mut m = BinMatrix::zero(5, 70);
+        let v = BinVector::random(70);
+        m.set_row(3, &v);
+        assert_eq!(m.row_ref(3), v);
+        assert_eq!(m.row_ref(2).count_ones(), 0);
+
+        let other = BinMatrix::random(2, 70);
+        m.set_row(0, other.row_ref(1));
+        assert_eq!(m.row_ref(0), other.row_ref(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "as many bits as there are columns")]
+    fn set_row_wrong_length() {
+        let mut m = BinMatrix::zero(5, 70);
+        m.set_row(0, &BinVector::random(69));
+    }
+
+    #[test]
+    fn word_boundaries() {
+        // M4RI words are 64 bits wide, also on 32-bit targets
+        let mut m = BinMatrix::zero(66, 66);
+        for &i in &[0, 31, 32, 63, 64, 65] {
+            m.set_bit(i, i, true);
+        }
+        assert_eq!(m.row_words(31), &[1 << 31, 0]);
+        assert_eq!(m.row_words(32), &[1 << 32, 0]);
+        assert_eq!(m.row_words(63), &[1 << 63, 0]);
+        assert_eq!(m.row_words(65), &[0, 0b10]);
+        assert_eq!(m.transposed(), m);
+        assert_eq!(&m * &m, m);
+        let v = m.row_ref(63).to_vector();
+        assert_eq!(&m * &v, v);
+    }
+
+    #[test]
+    fn dimension_errors() {
+        let a = BinMatrix::random(3, 4);
+        let b = BinMatrix::random(3, 5);
+        let mismatch = |operation| MatrixError::DimensionMismatch {
+            operation,
+            left: (3, 4),
+            right: (3, 5),
+        };
+        assert_eq!(a.try_mul(&b), Err(mismatch("multiply")));
+        assert_eq!(a.try_add(&b), Err(mismatch("add")));
+        assert_eq!(a.try_stacked(&b), Err(mismatch("stack")));
+        assert_eq!(a.clone().try_add_assign(&b), Err(mismatch("add")));
+        assert_eq!(a.try_augmented(&b).unwrap().ncols(), 9);
+        assert_eq!(a.transposed().try_mul(&b).unwrap(), &a.transposed() * &b);
+        assert_eq!(
+            mismatch("multiply").to_string(),
+            "can't multiply a 3x4 matrix and a 3x5 matrix"
+        );
+        let mut rhs = BinMatrix::random(4, 1);
+        assert!(try_solve_left(a, &mut rhs).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "can't multiply a 3x4 matrix and a 3x5 matrix")]
+    fn mul_mismatch_panics() {
+        let _ = &BinMatrix::random(3, 4) * &BinMatrix::random(3, 5);
+    }
+
+    #[test]
+    fn too_large() {
+        assert_eq!(
+            BinMatrix::try_zero(usize::MAX, 1).err(),
+            Some(MatrixError::TooLarge {
+                nrows: usize::MAX,
+                ncols: 1
+            })
+        );
+        assert!(BinMatrix::try_identity(c_int::MAX as usize + 1).is_err());
+    }
+
+    /// Only on the pure-Rust backend: M4RI may get the memory from an overcommitting allocator
+    #[cfg(feature = "pure-rust")]
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn allocation_failed() {
+        assert_eq!(
+            BinMatrix::try_zero(1 << 30, 1 << 30).err(),
+            Some(MatrixError::AllocationFailed {
+                nrows: 1 << 30,
+                ncols: 1 << 30
+            })
+        );
+    }
+
+    #[test]
+    fn try_inverted() {
+        assert_eq!(
+            BinMatrix::random(2, 3).try_inverted(),
+            Err(MatrixError::NotSquare { nrows: 2, ncols: 3 })
+        );
+        assert_eq!(
+            BinMatrix::zero(3, 3).try_inverted(),
+            Err(MatrixError::Singular)
+        );
+        let id = BinMatrix::identity(70);
+        assert_eq!(id.try_inverted(), Ok(id));
+    }
+
+    #[test]
+    fn hash_ignores_padding() {
+        use std::collections::HashSet;
+        let a = BinMatrix::random(4, 70);
+        // Extra bits past the last column of the input don't end up in the matrix
+        let rows: Vec<Vec<u64>> = a
+            .rows()
+            .map(|row| {
+                let mut words = row.as_words().to_vec();
+                words[1] |= 1 << 63;
+                words
+            })
+            .collect();
+        let b = BinMatrix::from_slices(&rows, 70);
+        assert_eq!(a, b);
+        let set: HashSet<BinMatrix> = vec![a.clone(), b, BinMatrix::zero(4, 70)]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&a));
+    }
+
+    #[test]
+    fn ordering() {
+        use friendly::format::DisplayStyle;
+        for _ in 0..20 {
+            let a = BinMatrix::random(3, 70);
+            let b = BinMatrix::random(3, 70);
+            let bits = |m: &BinMatrix| m.display(DisplayStyle::Bits).to_string();
+            assert_eq!(a.cmp(&b), bits(&a).cmp(&bits(&b)));
+            assert_eq!(a.cmp(&a.clone()), cmp::Ordering::Equal);
+        }
+        assert!(BinMatrix::zero(2, 5) < BinMatrix::zero(3, 1));
+        assert!(BinMatrix::zero(3, 1) < BinMatrix::identity(3));
+    }
+
+    #[test]
+    fn rows_iterators() {
+        let m = BinMatrix::random(5, 70);
+        let rows: Vec<BinVector> = m.clone().into_iter().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(m.rows().len(), 5);
+        for (i, row) in (&m).into_iter().enumerate() {
+            assert_eq!(row, rows[i]);
+            assert_eq!(&m[i], row.as_words());
+        }
+        assert_eq!(m.rows().next_back().unwrap(), rows[4]);
+        let collected: BinMatrix = rows.iter().cloned().collect();
+        assert_eq!(collected, m);
+        assert_eq!(m.rows().collect::<BinMatrix>(), m);
+        assert_eq!(m[(1, 3)], m.bit(1, 3));
+    }
+
+    #[test]
+    fn default_and_extend() {
+        let mut m = BinMatrix::default();
+        assert_eq!((m.nrows(), m.ncols()), (0, 0));
+        assert_eq!(m, Vec::<BinVector>::new().into_iter().collect());
+        assert_eq!(m.to_string(), "");
+        let rows = vec![BinVector::random(70), BinVector::random(70)];
+        m.extend(rows.clone());
+        assert_eq!(m, BinMatrix::new(rows.clone()));
+        m.extend(rows.clone());
+        assert_eq!(m.nrows(), 4);
+        assert_eq!(m.row_ref(3), rows[1]);
+        let mut no_rows = BinMatrix::zero(0, 5);
+        no_rows.extend(vec![BinVector::random(5)]);
+        assert_eq!(no_rows.dimensions(), (1, 5));
+    }
+
+    #[test]
+    #[should_panic(expected = "All rows need to have 70 bits")]
+    fn extend_wrong_length() {
+        let mut m = BinMatrix::random(2, 70);
+        m.extend(vec![BinVector::random(69)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "All rows need to have 70 bits")]
+    fn extend_no_rows_keeps_columns() {
+        let mut m = BinMatrix::zero(0, 70);
+        m.extend(vec![BinVector::random(69)]);
+    }
+
+    #[test]
+    fn word_access() {
+        let mut m = BinMatrix::zero(3, 130);
+        assert_eq!(m.get_word(2, 2), 0);
+        *m.get_word_mut(2, 1) = 0xff;
+        m.row_words_mut(1)[2] = 0b10;
+        assert_eq!(m.get_word(2, 1), 0xff);
+        assert!(m.bit(2, 64 + 7));
+        assert!(!m.bit(2, 64 + 8));
+        assert!(m.bit(1, 129));
+        assert_eq!(m.row_words(1), &[0, 0, 0b10]);
+
+        // Bits past the last column don't survive the borrow
+        *m.get_word_mut(0, 2) = !0;
+        m.row_words_mut(1)[2] |= !0b11;
+        assert_eq!(m.row_words(0), &[0, 0, 0b11]);
+        assert_eq!(m.row_words(1), &[0, 0, 0b10]);
+        let mut words = m.row_words_mut(2);
+        words[1] = !0;
+        assert_eq!(words[1], !0);
+        drop(words);
+        assert_eq!(m.get_word(2, 1), !0);
+        m.set_bit(0, 0, false);
+        unsafe {
+            *m.get_word_mut_unchecked(0, 0) = 1;
+            assert_eq!(m.get_word_unchecked(0, 0), 1);
+        }
+        assert!(m.bit(0, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn word_index_is_not_a_column() {
+        let m = BinMatrix::zero(3, 130);
+        m.get_word(0, 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn bit_out_of_bounds() {
+        let m = BinMatrix::zero(3, 10);
+        m.bit(3, 0);
+    }
+
+    #[test]
+    fn add_owned() {
+        let a = BinMatrix::random(10, 70);
+        let b = BinMatrix::random(10, 70);
+        let sum = &a + &b;
+        assert_eq!(a + b, sum);
+    }
+
+    #[test]
+    fn from_rows_iter() {
+        let rows: Vec<BinVector> = (0..100).map(|_| BinVector::random(129)).collect();
+        let m = BinMatrix::from_rows_iter(&rows, 129);
+        assert_eq!(m, BinMatrix::new(rows.clone()));
+        for (i, row) in rows.iter().enumerate() {
+            assert_eq!(m.row_ref(i), *row);
+        }
+
+        let copy = BinMatrix::from_rows_iter((0..m.nrows()).map(|i| m.row_ref(i)), 129);
+        assert_eq!(copy, m);
+
+        // excess bits are masked out
+        let m = BinMatrix::from_rows_iter(vec![[u64::MAX, u64::MAX]], 65);
+        assert_eq!(m.count_ones(), 65);
+    }
+
+    #[test]
+    fn test_as_vector_long_column() {
+        let m1 = BinMatrix::random(200, 1);
+        let vec = m1.as_vector();
+        assert_eq!(vec, m1.transposed().as_vector());
+        assert_eq!(m1, vec.as_column_matrix());
+    }
+
     #[test]
     fn test_random() {
         BinMatrix::random(10, 1);
     }
 
+    #[test]
+    fn solve_left_shapes() {
+        for m in test_matrices() {
+            let a = if m.ncols() <= m.nrows() {
+                m
+            } else {
+                m.transposed()
+            };
+            let x = BinMatrix::random(a.ncols(), 2);
+            let mut b = &a * &x;
+            assert!(solve_left(a.clone(), &mut b));
+            let solution = BinMatrix::from_rows_iter((0..a.ncols()).map(|row| b.row_words(row)), 2);
+            assert_eq!(&a * &solution, &a * &x);
+        }
+        let mut inconsistent = BinMatrix::zero(5, 1);
+        inconsistent.set_bit(4, 0, true);
+        assert!(!solve_left(BinMatrix::zero(5, 0), &mut inconsistent));
+        let mut zero = BinMatrix::zero(5, 2);
+        assert!(solve_left(BinMatrix::zero(5, 0), &mut zero));
+        let mut no_columns = BinMatrix::zero(5, 0);
+        assert!(solve_left(BinMatrix::random(5, 3), &mut no_columns));
+    }
+
+    #[test]
+    fn without_rows_or_columns() {
+        for &(nrows, ncols) in &[(0, 0), (0, 5), (5, 0)] {
+            let m = BinMatrix::random(nrows, ncols);
+            assert_eq!(m, BinMatrix::zero(nrows, ncols));
+            assert_eq!(m.clone().dimensions(), (nrows, ncols));
+            assert_eq!(m.rank(), 0);
+            assert_eq!(m.transposed().dimensions(), (ncols, nrows));
+            assert_eq!(&m + &m, m);
+            let mut sum = m.clone();
+            sum += &m;
+            assert_eq!(sum, m);
+        }
+        assert_eq!(BinMatrix::zero(0, 0).inverted(), BinMatrix::zero(0, 0));
+        // The product with an empty inner dimension is zero, not empty
+        let product = &BinMatrix::zero(3, 0) * &BinMatrix::zero(0, 4);
+        assert_eq!(product, BinMatrix::zero(3, 4));
+        assert_eq!(
+            &BinMatrix::zero(0, 3) * &BinMatrix::random(3, 4),
+            BinMatrix::zero(0, 4)
+        );
+        assert_ne!(BinMatrix::zero(0, 5), BinMatrix::zero(5, 0));
+        assert_ne!(BinMatrix::zero(5, 0), BinMatrix::zero(5, 1));
+    }
+
+    #[test]
+    fn partial_reduction() {
+        for m in test_matrices() {
+            let mut full = m.clone();
+            let rank = full.echelonize_reduced();
+            let mut partial = m.clone();
+            let pivots = partial.echelonize_reduced_partial(m.nrows(), m.ncols());
+            assert_eq!(pivots.len(), rank);
+            assert_eq!(partial, full);
+        }
+
+        let m = BinMatrix::random(20, 70);
+        let mut partial = m.clone();
+        let pivots = partial.echelonize_reduced_partial(5, 60);
+        assert_eq!(pivots.len(), 5);
+        assert!(pivots.iter().all(|&col| col < 60));
+        for (row, &col) in pivots.iter().enumerate() {
+            for other in 0..20 {
+                assert_eq!(partial.bit(other, col), other == row);
+            }
+        }
+        // Only row operations, so the row space stays the same
+        assert_eq!(partial.rank(), m.rank());
+        assert_eq!(partial.stacked(&m).rank(), m.rank());
+        assert!(BinMatrix::zero(3, 4)
+            .echelonize_reduced_partial(3, 4)
+            .is_empty());
+    }
+
+    #[test]
+    fn swap_columns_and_reduce() {
+        let mut m: BinMatrix = "1101; 0111".parse().unwrap();
+        m.swap_columns(0, 3);
+        assert_eq!(m, "1101; 1110".parse().unwrap());
+        m.swap_columns(2, 2);
+        assert_eq!(m.echelonize_reduced(), 2);
+        assert_eq!(m, "1101; 0011".parse().unwrap());
+
+        let wide = BinMatrix::random(3, 130);
+        let mut swapped = wide.clone();
+        swapped.swap_columns(1, 129);
+        for row in 0..3 {
+            assert_eq!(swapped.bit(row, 1), wide.bit(row, 129));
+            assert_eq!(swapped.bit(row, 129), wide.bit(row, 1));
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_mul_echelonize() {
+        let a = BinMatrix::random(300, 200);
+        let b = BinMatrix::random(200, 150);
+        let (product, rank) = ::parallel::with_threshold(100, || {
+            let product = &a * &b;
+            let rank = product.rank();
+            (product, rank)
+        });
+        ::parallel::with_threshold(usize::MAX, || {
+            assert_eq!(product, &a * &b);
+            assert_eq!(rank, product.rank());
+        });
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serialize() {
@@ -723,7 +2043,7 @@ mod test {
         let m1 = BinMatrix::zero(10, 3);
         for i in 0..10 {
             for j in 0..3 {
-                assert_eq!(m1.bit(i, j), false);
+                assert!(!m1.bit(i, j));
             }
         }
     }
@@ -734,7 +2054,7 @@ mod test {
         m1.set_window(5, 5, &BinMatrix::identity(5));
         for i in 0..5 {
             for j in 0..5 {
-                assert_eq!(m1.bit(i, j), false);
+                assert!(!m1.bit(i, j));
             }
         }
         for i in 5..10 {
@@ -761,6 +2081,7 @@ mod test {
         assert_ne!(m1, m2);
     }
 
+    #[cfg_attr(miri, ignore)]
     #[test]
     fn test_count_ones() {
         let rng = &mut rand::thread_rng();
@@ -771,4 +2092,47 @@ mod test {
             assert_eq!(v.count_ones(), v.as_column_matrix().count_ones());
         }
     }
+
+    #[test]
+    fn packed_buffers() {
+        for m in test_matrices() {
+            let (nrows, ncols) = m.dimensions();
+            for &layout in &[Layout::RowMajor, Layout::ColumnMajor] {
+                let (rows, cols) = match layout {
+                    Layout::RowMajor => (nrows, ncols),
+                    Layout::ColumnMajor => (ncols, nrows),
+                };
+                let words = m.to_words(layout);
+                assert_eq!(words.len(), rows * cols.div_ceil(64));
+                assert_eq!(BinMatrix::from_words(nrows, ncols, &words, layout), m);
+                for &order in &[BitOrder::LsbFirst, BitOrder::MsbFirst] {
+                    let bytes = m.to_bytes(layout, order);
+                    assert_eq!(bytes.len(), rows * cols.div_ceil(8));
+                    assert_eq!(
+                        BinMatrix::from_bytes(nrows, ncols, &bytes, layout, order),
+                        m
+                    );
+                }
+            }
+        }
+
+        // Padding bits in the input are ignored
+        let m = BinMatrix::from_words(2, 3, &[!0, 0b1000_0010], Layout::RowMajor);
+        assert_eq!(m.to_words(Layout::RowMajor), vec![0b111, 0b010]);
+    }
+
+    #[test]
+    #[should_panic(expected = "A 2x3 matrix needs 2 words")]
+    fn from_words_wrong_length() {
+        BinMatrix::from_words(2, 3, &[0], Layout::RowMajor);
+    }
+
+    #[test]
+    fn write_row_words() {
+        let mut m = BinMatrix::zero(3, 70);
+        m.write_row_words(1, &[!0, !0]);
+        assert_eq!(m.row_words(1), &[!0, 0b11_1111]);
+        assert_eq!(m.row_ref(0).count_ones(), 0);
+        assert_eq!(m.row_ref(1).count_ones(), 70);
+    }
 }
@@ -1,6 +1,12 @@
 /// Implement binary vectors to help implement functions on matrices
 ///
-/// Wraps the `vob` crate.
+/// Wraps the `vob` crate. The vectors are stored in `u64` words, just like the
+/// rows of a `BinMatrix`, so converting between the two is a matter of copying
+/// (or borrowing) words.
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::mem;
 use std::ops;
 use vob::Vob;
 
@@ -8,16 +14,108 @@ use rand;
 use rand::Rng;
 
 use friendly::binary_matrix::BinMatrix;
+use friendly::format::{DisplayStyle, Displayed};
+
+/// Number of bytes in a storage block of the underlying Vob
+const BYTES_PER_BLOCK: usize = mem::size_of::<u64>();
+
+/// The order of the bits within a byte (or a pair of hex digits)
+///
+/// The bit at index 0 of a vector is put at the position indicated by
+/// this value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// Index 0 is the least significant bit of the byte.
+    ///
+    /// This is the order in which M4RI and `BinMatrix` store bits in words.
+    LsbFirst,
+    /// Index 0 is the most significant bit of the byte.
+    ///
+    /// This is the order used by `Vob::from_bytes` and `BinVector::from_bytes`.
+    MsbFirst,
+}
+
+impl BitOrder {
+    /// Move bit 0 of the vector to the right position in the byte, or vice versa
+    #[inline]
+    fn apply(self, byte: u8) -> u8 {
+        match self {
+            BitOrder::LsbFirst => byte,
+            BitOrder::MsbFirst => byte.reverse_bits(),
+        }
+    }
+}
+
+/// Errors that can occur when converting from or to a `BinVector` or `BinMatrix`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The vector does not fit in the target type
+    TooLong {
+        /// Length of the vector
+        len: usize,
+        /// Number of bits available in the target type
+        max: usize,
+    },
+    /// The string contains a character that's not a hexadecimal digit
+    InvalidHexDigit {
+        /// Index of the character in the string
+        position: usize,
+        /// The offending character
+        character: char,
+    },
+    /// The hexadecimal string does not describe a whole number of bytes
+    OddHexLength,
+    /// An array contains a value that is neither 0 nor 1
+    NotABit {
+        /// Row of the value
+        row: usize,
+        /// Column of the value
+        col: usize,
+        /// The offending value
+        value: u8,
+    },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConversionError::TooLong { len, max } => write!(
+                f,
+                "can't convert a vector of {} bits to a {}-bit number",
+                len, max
+            ),
+            ConversionError::InvalidHexDigit {
+                position,
+                character,
+            } => write!(
+                f,
+                "invalid hexadecimal digit {:?} at position {}",
+                character, position
+            ),
+            ConversionError::OddHexLength => {
+                write!(f, "hexadecimal string has an odd number of digits")
+            }
+            ConversionError::NotABit { row, col, value } => {
+                write!(f, "value {} at ({}, {}) is not a bit", value, row, col)
+            }
+        }
+    }
+}
+
+impl error::Error for ConversionError {}
 
 /// Wrapper around vob::Vob
-#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+///
+/// The bits are stored in `u64` words, least significant bit first,
+/// which is the same layout as the rows of a `BinMatrix`.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BinVector {
-    vec: Vob,
+    vec: Vob<u64>,
 }
 
 impl ops::Deref for BinVector {
-    type Target = Vob;
+    type Target = Vob<u64>;
 
     #[inline]
     fn deref(&self) -> &Self::Target {
@@ -25,9 +123,26 @@ impl ops::Deref for BinVector {
     }
 }
 
+impl From<Vob<u64>> for BinVector {
+    /// Construct directly from a Vob
+    #[inline]
+    fn from(vec: Vob<u64>) -> BinVector {
+        BinVector { vec }
+    }
+}
+
 impl From<Vob> for BinVector {
-    fn from(v: Vob) -> BinVector {
-        BinVector::from(v)
+    /// Construct from a Vob with the default storage type
+    ///
+    /// This copies the storage into `u64` words.
+    fn from(vob: Vob) -> BinVector {
+        let bytes: Vec<u8> = vob
+            .iter_storage()
+            .flat_map(|block| block.to_le_bytes().to_vec())
+            .collect();
+        let mut result = BinVector::from_le_bytes(&bytes, BitOrder::LsbFirst);
+        result.truncate(vob.len());
+        result
     }
 }
 
@@ -42,25 +157,20 @@ impl BinVector {
     /// Create a new BinVector
     #[inline]
     pub fn new() -> Self {
-        BinVector::from(Vob::new())
-    }
-
-    //// Construct directly from a vec
-    #[inline]
-    pub fn from(vec: Vob) -> Self {
-        BinVector { vec }
+        BinVector::default()
     }
 
     /// Create with a certain length and all the same element
     #[inline]
     pub fn from_elem(len: usize, elem: bool) -> Self {
-        BinVector::from(Vob::from_elem(elem, len))
+        BinVector::from(Vob::<u64>::from_elem_with_storage_type(elem, len))
     }
 
     /// Set it up from bools
     #[inline]
     pub fn from_bools(bools: &[bool]) -> BinVector {
-        let vec = bools.iter().cloned().collect::<Vob>();
+        let mut vec = Vob::<u64>::new_with_storage_type(bools.len());
+        vec.extend(bools.iter().cloned());
         BinVector { vec }
     }
 
@@ -75,7 +185,7 @@ impl BinVector {
     /// ```
     #[inline]
     pub fn from_function(len: usize, f: fn(usize) -> bool) -> BinVector {
-        let mut vob = Vob::with_capacity(len);
+        let mut vob = Vob::<u64>::new_with_storage_type(len);
         for i in 0..len {
             vob.push(f(i));
         }
@@ -86,7 +196,7 @@ impl BinVector {
     #[inline]
     pub fn random(len: usize) -> BinVector {
         let mut rng = rand::thread_rng();
-        let mut vob = Vob::with_capacity(len);
+        let mut vob = Vob::<u64>::new_with_storage_type(len);
         for _ in 0..len {
             vob.push(rng.gen());
         }
@@ -96,17 +206,176 @@ impl BinVector {
     /// initialise with a set capacity
     #[inline]
     pub fn with_capacity(len: usize) -> Self {
-        BinVector::from(Vob::with_capacity(len))
+        BinVector::from(Vob::<u64>::new_with_storage_type(len))
+    }
+
+    /// Create a vector of length `len` from `u64` words
+    ///
+    /// The words are stored least significant bit first, like the rows of a
+    /// `BinMatrix`. Bits past `len` in the last word are ignored.
+    ///
+    /// **Panics** if there are not enough words to hold `len` bits.
+    pub fn from_words(words: &[u64], len: usize) -> BinVector {
+        let num_words = len.div_ceil(64);
+        assert!(
+            words.len() >= num_words,
+            "need {} words to store {} bits, got {}",
+            num_words,
+            len,
+            words.len()
+        );
+        let mut vec = Vob::<u64>::new_with_storage_type(len);
+        unsafe {
+            vec.get_storage_mut()
+                .extend_from_slice(&words[..num_words]);
+            vec.set_len(len);
+            vec.mask_last_block();
+        }
+        BinVector { vec }
+    }
+
+    /// Get the words in which this vector is stored
+    ///
+    /// Bits past `len()` in the last word are zero.
+    #[inline]
+    pub fn as_words(&self) -> &[u64] {
+        self.vec.get_storage()
+    }
+
+    /// Borrow this vector as a `BinVectorRef`
+    #[inline]
+    pub fn as_vector_ref(&self) -> BinVectorRef<'_> {
+        BinVectorRef::new(self.as_words(), self.len())
     }
 
     /// Create a new BinVector from an `&[u8]`.
+    ///
+    /// Follows `Vob::from_bytes`: the *most* significant bit of each byte comes first.
+    /// Use `BinVector::from_le_bytes` to choose the bit order explicitly.
     #[inline]
     pub fn from_bytes(bytes: &[u8]) -> BinVector {
-        let vec: Vob = Vob::from_bytes(bytes);
+        BinVector::from_le_bytes(bytes, BitOrder::MsbFirst)
+    }
 
+    /// Create a BinVector of length `8 * bytes.len()` from little-endian bytes
+    ///
+    /// The first byte contains the bits `0..8`, the second the bits `8..16`, etc.
+    /// Within a byte, the bits are ordered by `order`.
+    ///
+    /// # Example
+    /// ```
+    /// # use m4ri_rust::friendly::{BinVector, BitOrder};
+    /// let v = BinVector::from_le_bytes(&[0b0000_0001, 0b1000_0000], BitOrder::LsbFirst);
+    /// assert_eq!(v.len(), 16);
+    /// assert_eq!(v.iter_set_bits(..).collect::<Vec<_>>(), vec![0, 15]);
+    /// let v = BinVector::from_le_bytes(&[0b0000_0001, 0b1000_0000], BitOrder::MsbFirst);
+    /// assert_eq!(v.iter_set_bits(..).collect::<Vec<_>>(), vec![7, 8]);
+    /// ```
+    pub fn from_le_bytes(bytes: &[u8], order: BitOrder) -> BinVector {
+        let len = bytes.len() * 8;
+        let mut vec = Vob::<u64>::new_with_storage_type(len);
+        unsafe {
+            let storage = vec.get_storage_mut();
+            for chunk in bytes.chunks(BYTES_PER_BLOCK) {
+                let block = chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |block, (i, &byte)| {
+                        block | (order.apply(byte) as u64) << (i * 8)
+                    });
+                storage.push(block);
+            }
+            vec.set_len(len);
+        }
         BinVector { vec }
     }
 
+    /// Create a BinVector of length `8 * bytes.len()` from big-endian bytes
+    ///
+    /// The last byte contains the bits `0..8`, the one before it the bits `8..16`, etc.
+    /// Within a byte, the bits are ordered by `order`.
+    ///
+    /// With `BitOrder::LsbFirst` this agrees with `u64::to_be_bytes`:
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::{BinVector, BitOrder};
+    /// let v = BinVector::from_be_bytes(&0x1234u64.to_be_bytes(), BitOrder::LsbFirst);
+    /// assert_eq!(v.as_u64(), 0x1234);
+    /// ```
+    pub fn from_be_bytes(bytes: &[u8], order: BitOrder) -> BinVector {
+        let reversed: Vec<u8> = bytes.iter().rev().copied().collect();
+        BinVector::from_le_bytes(&reversed, order)
+    }
+
+    /// Convert to little-endian bytes
+    ///
+    /// This is the inverse of `BinVector::from_le_bytes`. If the length is
+    /// not a multiple of 8, the last byte is padded with zeros.
+    pub fn to_le_bytes(&self, order: BitOrder) -> Vec<u8> {
+        let num_bytes = self.len().div_ceil(8);
+        let mut bytes: Vec<u8> = self
+            .iter_storage()
+            .flat_map(|block| block.to_le_bytes().to_vec())
+            .map(|byte| order.apply(byte))
+            .collect();
+        bytes.truncate(num_bytes);
+        bytes
+    }
+
+    /// Convert to big-endian bytes
+    ///
+    /// This is the inverse of `BinVector::from_be_bytes`. If the length is
+    /// not a multiple of 8, the first byte is padded with zeros.
+    pub fn to_be_bytes(&self, order: BitOrder) -> Vec<u8> {
+        let mut bytes = self.to_le_bytes(order);
+        bytes.reverse();
+        bytes
+    }
+
+    /// Parse a hexadecimal string of little-endian bytes
+    ///
+    /// Every pair of digits is a byte, see `BinVector::from_le_bytes`.
+    /// An optional `0x` prefix is ignored.
+    pub fn from_le_hex(hex: &str, order: BitOrder) -> Result<BinVector, ConversionError> {
+        Ok(BinVector::from_le_bytes(&parse_hex(hex)?, order))
+    }
+
+    /// Parse a hexadecimal string of big-endian bytes
+    ///
+    /// Every pair of digits is a byte, see `BinVector::from_be_bytes`.
+    /// An optional `0x` prefix is ignored.
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::{BinVector, BitOrder};
+    /// let v = BinVector::from_be_hex("0x0102", BitOrder::LsbFirst).unwrap();
+    /// assert_eq!(v.as_u32(), 0x0102);
+    /// assert_eq!(v.to_be_hex(BitOrder::LsbFirst), "0102");
+    /// ```
+    pub fn from_be_hex(hex: &str, order: BitOrder) -> Result<BinVector, ConversionError> {
+        Ok(BinVector::from_be_bytes(&parse_hex(hex)?, order))
+    }
+
+    /// Convert to a lowercase hexadecimal string of little-endian bytes
+    pub fn to_le_hex(&self, order: BitOrder) -> String {
+        to_hex(&self.to_le_bytes(order))
+    }
+
+    /// Convert to a lowercase hexadecimal string of big-endian bytes
+    pub fn to_be_hex(&self, order: BitOrder) -> String {
+        to_hex(&self.to_be_bytes(order))
+    }
+
+    /// Show the vector in another style than `Display`
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::{BinVector, DisplayStyle};
+    /// let v = BinVector::from_bools(&[true, false, true]);
+    /// assert_eq!(v.display(DisplayStyle::Bits).to_string(), "101");
+    /// ```
+    pub fn display(&self, style: DisplayStyle) -> Displayed<'_> {
+        Displayed::vector(self.as_vector_ref(), style)
+    }
+
     /// Get the hamming weight
     #[inline]
     pub fn count_ones(&self) -> u32 {
@@ -122,37 +391,110 @@ impl BinVector {
 
     /// Obtain the inner Vob
     #[inline]
-    pub fn into_vob(self) -> Vob {
+    pub fn into_vob(self) -> Vob<u64> {
         self.vec
     }
 
     /// Obtain this as a row matrix
     pub fn as_matrix(&self) -> BinMatrix {
-        BinMatrix::new(vec![self.clone()] )
+        BinMatrix::from_rows_iter(Some(self), self.len())
     }
 
     /// Obtain this as a column matrix
     pub fn as_column_matrix(&self) -> BinMatrix {
-        self.as_matrix().transposed()
+        BinMatrix::from_rows_iter(self.iter().map(|bit| [bit as u64]), 1)
     }
 
     /// Get an u32 in the order as it's stored
+    ///
+    /// Bit 0 of the vector is the least significant bit.
+    ///
+    /// **Panics** if the vector is longer than 32 bits,
+    /// use `u32::try_from` to handle that case.
     pub fn as_u32(&self) -> u32 {
-        assert!(self.len() < 32, "Can't convert this to a >32 bit number");
-        self.iter_storage()
-            .next()
-            .expect("Can't convert None to a number") as u32
+        u32::try_from(self).unwrap_or_else(|e| panic!("{}", e))
     }
 
     /// Get an u64 in the order as it's stored
+    ///
+    /// Bit 0 of the vector is the least significant bit.
+    ///
+    /// **Panics** if the vector is longer than 64 bits,
+    /// use `u64::try_from` to handle that case.
     pub fn as_u64(&self) -> u64 {
-        assert!(self.len() < 64, "Can't convert this to a >32 bit number");
-        self.iter_storage()
-            .next()
-            .expect("Can't convert None to a number") as u64
+        u64::try_from(self).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+/// Decode a hex string into bytes
+fn parse_hex(hex: &str) -> Result<Vec<u8>, ConversionError> {
+    let (offset, digits) = if hex.starts_with("0x") || hex.starts_with("0X") {
+        (2, &hex[2..])
+    } else {
+        (0, hex)
+    };
+    let nibbles = digits
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            c.to_digit(16)
+                .map(|d| d as u8)
+                .ok_or(ConversionError::InvalidHexDigit {
+                    position: offset + i,
+                    character: c,
+                })
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    if nibbles.len() % 2 != 0 {
+        return Err(ConversionError::OddHexLength);
     }
+    Ok(nibbles.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
 }
 
+/// Encode bytes as a lowercase hex string
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+macro_rules! impl_int_conversions {
+    ($($t:ty),*) => {$(
+        impl<'a> TryFrom<&'a BinVector> for $t {
+            type Error = ConversionError;
+
+            /// Convert to an integer, bit 0 of the vector is the least significant bit
+            fn try_from(v: &BinVector) -> Result<$t, ConversionError> {
+                let max = mem::size_of::<$t>() * 8;
+                if v.len() > max {
+                    return Err(ConversionError::TooLong { len: v.len(), max });
+                }
+                Ok(v.iter_storage()
+                    .enumerate()
+                    .fold(0, |acc, (i, block)| acc | (block as $t) << (i * BYTES_PER_BLOCK * 8)))
+            }
+        }
+
+        impl TryFrom<BinVector> for $t {
+            type Error = ConversionError;
+
+            #[inline]
+            fn try_from(v: BinVector) -> Result<$t, ConversionError> {
+                <$t>::try_from(&v)
+            }
+        }
+
+        impl From<$t> for BinVector {
+            /// Create a vector of the bit width of the integer,
+            /// bit 0 of the vector is the least significant bit
+            #[inline]
+            fn from(value: $t) -> BinVector {
+                BinVector::from_le_bytes(&value.to_le_bytes(), BitOrder::LsbFirst)
+            }
+        }
+    )*};
+}
+
+impl_int_conversions!(u8, u16, u32, u64, u128);
+
 impl<'a> ops::Add<&'a BinVector> for &'a BinVector {
     type Output = BinVector;
     #[inline]
@@ -175,11 +517,11 @@ impl ops::Add<BinVector> for BinVector {
     }
 }
 
-impl<'a> ops::AddAssign<&'a BinVector> for BinVector {
+impl ops::AddAssign<&BinVector> for BinVector {
     #[inline]
     fn add_assign(&mut self, other: &BinVector) {
         assert_eq!(self.len(), other.len(), "unequal length vectors");
-        self.xor(&*other);
+        self.xor(other);
     }
 }
 
@@ -197,7 +539,7 @@ impl<'a> ops::Mul<&'a BinVector> for &'a BinVector {
     #[inline]
     fn mul(self, other: &BinVector) -> Self::Output {
         let mut vec = self.clone();
-        vec.and(&other);
+        vec.and(other);
         vec.count_ones() % 2 == 1
     }
 }
@@ -214,6 +556,167 @@ impl ops::Mul<BinVector> for BinVector {
     }
 }
 
+impl AsRef<[u64]> for BinVector {
+    #[inline]
+    fn as_ref(&self) -> &[u64] {
+        self.as_words()
+    }
+}
+
+/// A borrowed binary vector, for example a row of a `BinMatrix`
+///
+/// Obtained through `BinMatrix::row_ref` or `BinVector::as_vector_ref`.
+/// It does not copy the underlying words.
+#[derive(Clone, Copy)]
+pub struct BinVectorRef<'a> {
+    words: &'a [u64],
+    len: usize,
+}
+
+impl<'a> BinVectorRef<'a> {
+    /// Borrow `len` bits stored in `words`, least significant bit first
+    ///
+    /// **Panics** if there are not enough words to hold `len` bits.
+    #[inline]
+    pub fn new(words: &'a [u64], len: usize) -> BinVectorRef<'a> {
+        let num_words = len.div_ceil(64);
+        assert!(
+            words.len() >= num_words,
+            "need {} words to store {} bits, got {}",
+            num_words,
+            len,
+            words.len()
+        );
+        BinVectorRef {
+            words: &words[..num_words],
+            len,
+        }
+    }
+
+    /// The number of bits
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether this vector has length zero
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the bit at `index`, or `None` if it's out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            None
+        } else {
+            Some((self.words[index / 64] >> (index % 64)) & 1 == 1)
+        }
+    }
+
+    /// The words in which this vector is stored
+    ///
+    /// Bits past `len()` in the last word are not guaranteed to be zero.
+    #[inline]
+    pub fn as_words(&self) -> &'a [u64] {
+        self.words
+    }
+
+    /// Iterate over the words, with the bits past `len()` masked out
+    #[inline]
+    pub(crate) fn masked_words(&self) -> impl Iterator<Item = u64> + 'a {
+        let len = self.len;
+        let last = self.words.len().wrapping_sub(1);
+        self.words.iter().enumerate().map(move |(i, &word)| {
+            if i == last && !len.is_multiple_of(64) {
+                word & ((1 << (len % 64)) - 1)
+            } else {
+                word
+            }
+        })
+    }
+
+    /// Get the hamming weight
+    #[inline]
+    pub fn count_ones(&self) -> u32 {
+        self.masked_words().map(u64::count_ones).sum()
+    }
+
+    /// Iterate over the indices of the set bits
+    pub fn iter_set_bits(&self) -> impl Iterator<Item = usize> + 'a {
+        self.masked_words().enumerate().flat_map(|(i, mut word)| {
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    /// Copy this into an owned `BinVector`
+    ///
+    /// O(words)
+    #[inline]
+    pub fn to_vector(&self) -> BinVector {
+        BinVector::from_words(self.words, self.len)
+    }
+}
+
+impl<'a> From<&'a BinVector> for BinVectorRef<'a> {
+    #[inline]
+    fn from(vector: &'a BinVector) -> BinVectorRef<'a> {
+        vector.as_vector_ref()
+    }
+}
+
+impl<'a, 'b> PartialEq<BinVectorRef<'b>> for BinVectorRef<'a> {
+    fn eq(&self, other: &BinVectorRef<'b>) -> bool {
+        self.len == other.len && self.masked_words().eq(other.masked_words())
+    }
+}
+
+impl<'a> Eq for BinVectorRef<'a> {}
+
+impl<'a> PartialEq<BinVector> for BinVectorRef<'a> {
+    #[inline]
+    fn eq(&self, other: &BinVector) -> bool {
+        *self == other.as_vector_ref()
+    }
+}
+
+impl<'a> PartialEq<BinVectorRef<'a>> for BinVector {
+    #[inline]
+    fn eq(&self, other: &BinVectorRef<'a>) -> bool {
+        self.as_vector_ref() == *other
+    }
+}
+
+impl<'a> AsRef<[u64]> for BinVectorRef<'a> {
+    #[inline]
+    fn as_ref(&self) -> &[u64] {
+        self.words
+    }
+}
+
+impl<'a, 'b> ops::Mul<&'b BinVector> for BinVectorRef<'a> {
+    type Output = bool;
+
+    /// Compute the inner product between two vectors
+    #[inline]
+    fn mul(self, other: &BinVector) -> Self::Output {
+        assert_eq!(self.len(), other.len(), "unequal length vectors");
+        self.masked_words()
+            .zip(other.as_words())
+            .fold(0, |acc, (a, b)| acc ^ (a & b).count_ones())
+            % 2
+            == 1
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -236,6 +739,102 @@ mod test {
         assert_eq!(b.get(1), Some(false));
     }
 
+    #[test]
+    fn le_be_bytes() {
+        let bytes = [0b0000_0001, 0b0100_0000, 0xff, 0x00, 0x12, 0x34, 0x56, 0x78, 0x9a];
+        for &order in &[BitOrder::LsbFirst, BitOrder::MsbFirst] {
+            let le = BinVector::from_le_bytes(&bytes, order);
+            assert_eq!(le.len(), 72);
+            assert_eq!(le.to_le_bytes(order), bytes);
+            let be = BinVector::from_be_bytes(&bytes, order);
+            assert_eq!(be.to_be_bytes(order), bytes);
+        }
+
+        let v = BinVector::from_le_bytes(&bytes, BitOrder::LsbFirst);
+        assert_eq!(v.get(0), Some(true));
+        assert_eq!(v.get(14), Some(true));
+        let v = BinVector::from_le_bytes(&bytes, BitOrder::MsbFirst);
+        assert_eq!(v.get(7), Some(true));
+        assert_eq!(v.get(9), Some(true));
+        assert_eq!(v, BinVector::from_bytes(&bytes));
+
+        let v = BinVector::from_be_bytes(&[0b1000_0000, 0b0000_0010], BitOrder::LsbFirst);
+        assert_eq!(v.iter_set_bits(..).collect::<Vec<_>>(), vec![1, 15]);
+
+        // Padding
+        let v = BinVector::from_elem(9, true);
+        assert_eq!(v.to_le_bytes(BitOrder::LsbFirst), vec![0xff, 0x01]);
+        assert_eq!(v.to_le_bytes(BitOrder::MsbFirst), vec![0xff, 0x80]);
+        assert_eq!(v.to_be_bytes(BitOrder::LsbFirst), vec![0x01, 0xff]);
+        assert!(BinVector::new().to_le_bytes(BitOrder::LsbFirst).is_empty());
+    }
+
+    #[test]
+    fn hex() {
+        let v = BinVector::from_le_hex("0x01ff80", BitOrder::LsbFirst).unwrap();
+        assert_eq!(v.len(), 24);
+        assert_eq!(v.as_u32(), 0x80ff01);
+        assert_eq!(v.to_le_hex(BitOrder::LsbFirst), "01ff80");
+        assert_eq!(v.to_be_hex(BitOrder::LsbFirst), "80ff01");
+        assert_eq!(v.to_be_hex(BitOrder::MsbFirst), "01ff80");
+
+        let v = BinVector::from_be_hex("DEADbeef", BitOrder::LsbFirst).unwrap();
+        assert_eq!(v.as_u32(), 0xdead_beef);
+        assert_eq!(
+            BinVector::from_le_hex("DEADbeef", BitOrder::MsbFirst).unwrap(),
+            BinVector::from_bytes(&[0xde, 0xad, 0xbe, 0xef])
+        );
+
+        assert_eq!(
+            BinVector::from_le_hex("0x1g", BitOrder::LsbFirst),
+            Err(ConversionError::InvalidHexDigit {
+                position: 3,
+                character: 'g'
+            })
+        );
+        assert_eq!(
+            BinVector::from_be_hex("123", BitOrder::LsbFirst),
+            Err(ConversionError::OddHexLength)
+        );
+    }
+
+    #[test]
+    fn integers() {
+        let v = BinVector::from(0x8000_0000_0000_0001u64);
+        assert_eq!(v.len(), 64);
+        assert_eq!(v.get(0), Some(true));
+        assert_eq!(v.get(63), Some(true));
+        assert_eq!(v.as_u64(), 0x8000_0000_0000_0001);
+        assert_eq!(u64::try_from(&v), Ok(0x8000_0000_0000_0001));
+        assert_eq!(
+            u32::try_from(&v),
+            Err(ConversionError::TooLong { len: 64, max: 32 })
+        );
+
+        let v = BinVector::from(0xffff_ffffu32);
+        assert_eq!(v.len(), 32);
+        assert_eq!(v.count_ones(), 32);
+        assert_eq!(v.as_u32(), 0xffff_ffff);
+        assert_eq!(v.as_u64(), 0xffff_ffff);
+        assert_eq!(u16::try_from(v).map_err(|_| ()), Err(()));
+
+        let x = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210u128;
+        let v = BinVector::from(x);
+        assert_eq!(v.len(), 128);
+        assert_eq!(u128::try_from(&v), Ok(x));
+
+        assert_eq!(u8::try_from(&BinVector::from(0xa5u8)), Ok(0xa5));
+        assert_eq!(u16::try_from(&BinVector::from(0xa5a5u16)), Ok(0xa5a5));
+        assert_eq!(u8::try_from(&BinVector::from_bools(&[true, false, true])), Ok(5));
+        assert_eq!(BinVector::new().as_u32(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "can't convert a vector of 65 bits to a 64-bit number")]
+    fn as_u64_too_long() {
+        BinVector::from_elem(65, true).as_u64();
+    }
+
     #[test]
     fn add() {
         let a = BinVector::from(Vob::from_elem(false, 10));
@@ -244,10 +843,69 @@ mod test {
         let c = &a + &b;
 
         assert_eq!(c.len(), 10, "length incorrect");
-        assert_eq!(Vob::from_elem(false, 10), *c);
+        assert_eq!(Vob::<u64>::from_elem_with_storage_type(false, 10), *c);
         assert_eq!(c, a + b);
     }
 
+    #[test]
+    fn from_vob() {
+        let vob = Vob::from_bytes(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x11]);
+        let v = BinVector::from(vob.clone());
+        assert_eq!(v.len(), vob.len());
+        assert_eq!(v.iter().collect::<Vec<bool>>(), vob.iter().collect::<Vec<bool>>());
+        let mut short = vob.clone();
+        short.truncate(67);
+        let mut expected = v.clone();
+        expected.truncate(67);
+        assert_eq!(BinVector::from(short), expected);
+    }
+
+    #[test]
+    fn words() {
+        let v = BinVector::from_words(&[0xffff_0000_ffff_0000, 0xf0f0], 68);
+        assert_eq!(v.len(), 68);
+        assert_eq!(v.as_words(), &[0xffff_0000_ffff_0000, 0x0]);
+        assert_eq!(v.count_ones(), 32);
+        let v = BinVector::from_words(&[0, 0xff, 0xff], 72);
+        assert_eq!(v.as_words(), &[0, 0xff]);
+    }
+
+    #[test]
+    fn word_boundaries() {
+        // Words are always 64 bits wide, also on 32-bit targets
+        let mut v = BinVector::from_elem(130, false);
+        for &i in &[0, 31, 32, 63, 64, 129] {
+            v.set(i, true);
+        }
+        assert_eq!(v.as_words(), &[1 | 1 << 31 | 1 << 32 | 1 << 63, 1, 0b10]);
+        assert_eq!(BinVector::from_words(v.as_words(), 130), v);
+        let low = BinVector::from_words(&v.as_words()[..1], 64);
+        assert_eq!(u64::try_from(&low), Ok(v.as_words()[0]));
+        assert_eq!(BinVector::from(1u64 << 63).as_words(), &[1 << 63]);
+        assert_eq!(u128::try_from(&BinVector::from(1u128 << 64)), Ok(1 << 64));
+    }
+
+    #[test]
+    fn vector_ref() {
+        let words = [0b1011, u64::MAX];
+        let r = BinVectorRef::new(&words, 66);
+        assert_eq!(r.len(), 66);
+        assert_eq!(r.get(0), Some(true));
+        assert_eq!(r.get(2), Some(false));
+        assert_eq!(r.get(65), Some(true));
+        assert_eq!(r.get(66), None);
+        // bits past len are ignored
+        assert_eq!(r.count_ones(), 5);
+        let v = r.to_vector();
+        assert_eq!(v.as_words(), &[0b1011, 0b11]);
+        assert_eq!(r, v);
+        assert_eq!(v, r);
+        assert_eq!(v.as_vector_ref(), r);
+        assert!(r * &v);
+        assert!(r * &BinVector::from_elem(66, true));
+        assert!(!(r * &BinVector::from_words(&[0b1, 0b1], 66)));
+    }
+
     #[test]
     fn mul() {
         let a = BinVector::from(Vob::from_elem(true, 10));
@@ -255,7 +913,7 @@ mod test {
 
         let c = &a * &b;
 
-        assert_eq!(false, c);
+        assert!(!c);
         assert_eq!(c, a * b);
     }
 
@@ -285,4 +943,17 @@ mod test {
         assert_eq!(b.count_ones(), 0);
         assert_eq!(BinVector::from_bytes(&[0b1010_1000]).count_ones(), 3);
     }
+
+    #[test]
+    fn iter_set_bits() {
+        let mut v = BinVector::from_elem(130, false);
+        for &i in &[0, 63, 64, 129] {
+            v.set(i, true);
+        }
+        let set: Vec<usize> = v.as_vector_ref().iter_set_bits().collect();
+        assert_eq!(set, vec![0, 63, 64, 129]);
+        // Padding bits past the length are ignored
+        let words = [u64::MAX];
+        assert_eq!(BinVectorRef::new(&words, 3).iter_set_bits().count(), 3);
+    }
 }
@@ -0,0 +1,750 @@
+//! Text representations of matrices and vectors
+//!
+//! `Display` prints rows in the style of M4RI's `mzd_print`, `[1 0 1]`.
+//! `display` selects another `DisplayStyle`, including the matrix syntax of
+//! SageMath, Magma and MATLAB, and `FromStr` parses all of them.
+//! `Debug` shows the dimensions and the first bits only.
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use friendly::binary_matrix::BinMatrix;
+use friendly::binary_vector::{BinVector, BinVectorRef};
+
+/// Number of rows shown by `Debug`
+const PREVIEW_ROWS: usize = 8;
+
+/// Number of bits per row shown by `Debug`
+const PREVIEW_BITS: usize = 64;
+
+/// How the bits are shown by `BinMatrix::display` and `BinVector::display`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DisplayStyle {
+    /// Rows like `[1 0 1]`, the style of `mzd_print`. This is what `Display` uses.
+    Brackets,
+    /// Rows like `[1 . 1]`, which makes sparse matrices easier to read
+    Dots,
+    /// Compact bit strings like `101`
+    Bits,
+    /// SageMath syntax, `matrix(GF(2), [[1, 0], [0, 1]])` or `vector(GF(2), [1, 0])`
+    Sage,
+    /// Magma syntax, `Matrix(GF(2), 2, 2, [1, 0, 0, 1])` or `Vector(GF(2), [1, 0])`
+    Magma,
+    /// MATLAB and Octave syntax, `[1 0; 0 1]` or `[1 0]`,
+    /// and `zeros(0, 3)` for matrices without rows or columns
+    Matlab,
+}
+
+/// Shows a matrix or vector in a certain `DisplayStyle`
+///
+/// Returned by `BinMatrix::display` and `BinVector::display`.
+pub struct Displayed<'a> {
+    rows: Rows<'a>,
+    style: DisplayStyle,
+}
+
+enum Rows<'a> {
+    Matrix(&'a BinMatrix),
+    Vector(BinVectorRef<'a>),
+}
+
+impl<'a> Displayed<'a> {
+    pub(crate) fn matrix(matrix: &'a BinMatrix, style: DisplayStyle) -> Displayed<'a> {
+        Displayed {
+            rows: Rows::Matrix(matrix),
+            style,
+        }
+    }
+
+    pub(crate) fn vector(vector: BinVectorRef<'a>, style: DisplayStyle) -> Displayed<'a> {
+        Displayed {
+            rows: Rows::Vector(vector),
+            style,
+        }
+    }
+}
+
+fn write_bits(
+    f: &mut fmt::Formatter,
+    row: BinVectorRef,
+    separator: &str,
+    zero: char,
+) -> fmt::Result {
+    for i in 0..row.len() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        let bit = row.get(i) == Some(true);
+        write!(f, "{}", if bit { '1' } else { zero })?;
+    }
+    Ok(())
+}
+
+fn write_row(f: &mut fmt::Formatter, row: BinVectorRef, style: DisplayStyle) -> fmt::Result {
+    let (open, close, separator, zero) = match style {
+        DisplayStyle::Brackets | DisplayStyle::Matlab => ("[", "]", " ", '0'),
+        DisplayStyle::Dots => ("[", "]", " ", '.'),
+        DisplayStyle::Bits => ("", "", "", '0'),
+        DisplayStyle::Sage => ("vector(GF(2), [", "])", ", ", '0'),
+        DisplayStyle::Magma => ("Vector(GF(2), [", "])", ", ", '0'),
+    };
+    f.write_str(open)?;
+    write_bits(f, row, separator, zero)?;
+    f.write_str(close)
+}
+
+/// Write `rows` separated by `separator`, with `write` writing a single row
+fn write_rows<F>(
+    f: &mut fmt::Formatter,
+    matrix: &BinMatrix,
+    separator: &str,
+    write: F,
+) -> fmt::Result
+where
+    F: Fn(&mut fmt::Formatter, BinVectorRef) -> fmt::Result,
+{
+    for row in 0..matrix.nrows() {
+        if row > 0 {
+            f.write_str(separator)?;
+        }
+        write(f, matrix.row_ref(row))?;
+    }
+    Ok(())
+}
+
+impl<'a> fmt::Display for Displayed<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let matrix = match self.rows {
+            Rows::Vector(vector) => return write_row(f, vector, self.style),
+            Rows::Matrix(matrix) => matrix,
+        };
+        match self.style {
+            // A list of rows doesn't keep the dimensions of an empty matrix
+            DisplayStyle::Sage if matrix.nrows() == 0 || matrix.ncols() == 0 => write!(
+                f,
+                "matrix(GF(2), {}, {}, [])",
+                matrix.nrows(),
+                matrix.ncols()
+            ),
+            DisplayStyle::Sage => {
+                f.write_str("matrix(GF(2), [")?;
+                write_rows(f, matrix, ", ", |f, row| {
+                    f.write_str("[")?;
+                    write_bits(f, row, ", ", '0')?;
+                    f.write_str("]")
+                })?;
+                f.write_str("])")
+            }
+            DisplayStyle::Magma => {
+                write!(f, "Matrix(GF(2), {}, {}, [", matrix.nrows(), matrix.ncols())?;
+                let separator = if matrix.ncols() > 0 { ", " } else { "" };
+                write_rows(f, matrix, separator, |f, row| write_bits(f, row, ", ", '0'))?;
+                f.write_str("])")
+            }
+            // `[]` is always 0x0
+            DisplayStyle::Matlab if matrix.nrows() == 0 || matrix.ncols() == 0 => {
+                write!(f, "zeros({}, {})", matrix.nrows(), matrix.ncols())
+            }
+            DisplayStyle::Matlab => {
+                f.write_str("[")?;
+                write_rows(f, matrix, "; ", |f, row| write_bits(f, row, " ", '0'))?;
+                f.write_str("]")
+            }
+            style => write_rows(f, matrix, "\n", |f, row| write_row(f, row, style)),
+        }
+    }
+}
+
+impl fmt::Display for BinMatrix {
+    /// One `[1 0 1]` line per row
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.display(DisplayStyle::Brackets).fmt(f)
+    }
+}
+
+impl fmt::Display for BinVector {
+    /// Shows the vector as `[1 0 1]`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.display(DisplayStyle::Brackets).fmt(f)
+    }
+}
+
+impl<'a> fmt::Display for BinVectorRef<'a> {
+    /// Shows the vector as `[1 0 1]`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Displayed::vector(*self, DisplayStyle::Brackets).fmt(f)
+    }
+}
+
+/// The first `PREVIEW_BITS` bits of a row, for `Debug`
+struct Preview<'a>(BinVectorRef<'a>);
+
+impl<'a> fmt::Debug for Preview<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.0.len().min(PREVIEW_BITS) {
+            f.write_str(if self.0.get(i) == Some(true) {
+                "1"
+            } else {
+                "0"
+            })?;
+        }
+        if self.0.len() > PREVIEW_BITS {
+            write!(f, "... ({} more)", self.0.len() - PREVIEW_BITS)?;
+        }
+        Ok(())
+    }
+}
+
+/// The rows of a matrix, of which `Debug` shows the first `PREVIEW_ROWS`
+struct PreviewRows<'a>(&'a BinMatrix);
+
+impl<'a> fmt::Debug for PreviewRows<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let matrix = self.0;
+        let mut list = f.debug_list();
+        list.entries((0..matrix.nrows().min(PREVIEW_ROWS)).map(|row| Preview(matrix.row_ref(row))));
+        if matrix.nrows() > PREVIEW_ROWS {
+            list.entry(&format_args!(
+                "... ({} more rows)",
+                matrix.nrows() - PREVIEW_ROWS
+            ));
+        }
+        list.finish()
+    }
+}
+
+impl fmt::Debug for BinMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BinMatrix")
+            .field("nrows", &self.nrows())
+            .field("ncols", &self.ncols())
+            .field("rows", &PreviewRows(self))
+            .finish()
+    }
+}
+
+impl fmt::Debug for BinVector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BinVector")
+            .field("len", &self.len())
+            .field("bits", &Preview(self.as_vector_ref()))
+            .finish()
+    }
+}
+
+impl<'a> fmt::Debug for BinVectorRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BinVectorRef")
+            .field("len", &self.len())
+            .field("bits", &Preview(*self))
+            .finish()
+    }
+}
+
+/// Errors that can occur when parsing a matrix or vector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The row contains a character that's not a bit or a separator
+    InvalidCharacter {
+        /// Index of the row, ignoring empty lines
+        row: usize,
+        /// The offending character
+        character: char,
+    },
+    /// The row has a different length than the first row
+    RaggedRow {
+        /// Index of the row, ignoring empty lines
+        row: usize,
+        /// Number of bits in the row
+        len: usize,
+        /// Number of bits in the first row
+        expected: usize,
+    },
+    /// There are no rows or columns
+    Empty,
+    /// A SageMath, Magma or MATLAB constructor that is not of the form
+    /// `matrix(GF(2), [...])`, `matrix(GF(2), m, n, [...])`, `vector(GF(2), [...])`
+    /// or `zeros(m, n)` with `m` or `n` zero
+    InvalidConstructor,
+    /// The number of entries doesn't match the dimensions given to the constructor
+    WrongLength {
+        /// Number of entries in the list
+        len: usize,
+        /// Number of rows times the number of columns
+        expected: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::InvalidCharacter { row, character } => {
+                write!(f, "invalid character {:?} in row {}", character, row)
+            }
+            ParseError::RaggedRow { row, len, expected } => write!(
+                f,
+                "row {} has {} bits, but the first row has {}",
+                row, len, expected
+            ),
+            ParseError::Empty => write!(f, "the matrix has no rows or columns"),
+            ParseError::InvalidConstructor => {
+                write!(f, "invalid GF(2) matrix or vector constructor")
+            }
+            ParseError::WrongLength { len, expected } => {
+                write!(f, "expected {} entries, but the list has {}", expected, len)
+            }
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Parse one row in any `DisplayStyle`
+///
+/// `0` and `.` are zero bits, whitespace, `,` and `:` are ignored.
+fn parse_row(row: &str, index: usize) -> Result<BinVector, ParseError> {
+    let row = row.trim();
+    let row = match (row.strip_prefix('['), row.ends_with(']')) {
+        (Some(inner), true) => &inner[..inner.len() - 1],
+        _ => row,
+    };
+    let mut vector = BinVector::with_capacity(row.len());
+    for character in row.chars() {
+        match character {
+            '1' => vector.push(true),
+            '0' | '.' => vector.push(false),
+            ',' | ':' => {}
+            c if c.is_whitespace() => {}
+            character => {
+                return Err(ParseError::InvalidCharacter {
+                    row: index,
+                    character,
+                })
+            }
+        }
+    }
+    Ok(vector)
+}
+
+/// The arguments after `GF(2)` if `s` is a call to `name`, like `matrix(GF(2), ...)`
+fn constructor_arguments<'a>(s: &'a str, name: &str) -> Option<Result<&'a str, ParseError>> {
+    let arguments = s.strip_prefix(name)?.trim_start().strip_prefix('(')?;
+    Some(
+        arguments
+            .strip_suffix(')')
+            .and_then(|arguments| arguments.trim_start().strip_prefix("GF(2)"))
+            .and_then(|arguments| arguments.trim_start().strip_prefix(','))
+            .map(str::trim)
+            .ok_or(ParseError::InvalidConstructor),
+    )
+}
+
+/// The contents of a `[...]` list
+fn list_contents(list: &str) -> Result<&str, ParseError> {
+    list.strip_prefix('[')
+        .and_then(|list| list.strip_suffix(']'))
+        .ok_or(ParseError::InvalidConstructor)
+}
+
+/// Parse dimensions like `2, 3`
+fn parse_dimensions(dimensions: &str) -> Result<(usize, usize), ParseError> {
+    let dimensions = dimensions
+        .split(',')
+        .map(|n| n.trim().parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ParseError::InvalidConstructor)?;
+    match dimensions[..] {
+        [nrows, ncols] => Ok((nrows, ncols)),
+        _ => Err(ParseError::InvalidConstructor),
+    }
+}
+
+/// A matrix without rows or columns
+fn parse_empty(nrows: usize, ncols: usize) -> Result<BinMatrix, ParseError> {
+    if nrows != 0 && ncols != 0 {
+        return Err(ParseError::InvalidConstructor);
+    }
+    BinMatrix::try_zero(nrows, ncols).map_err(|_| ParseError::InvalidConstructor)
+}
+
+/// Parse the arguments of `matrix(GF(2), ...)` in SageMath or Magma
+///
+/// These are either a list of rows, or the dimensions followed by a list
+/// of rows or a flat list of entries. Only the latter can describe a matrix
+/// without rows or columns, as `matrix(GF(2), 0, 3, [])`.
+fn parse_constructor(arguments: &str) -> Result<BinMatrix, ParseError> {
+    let (dimensions, list) = match arguments.find('[') {
+        Some(start) => arguments.split_at(start),
+        None => return Err(ParseError::InvalidConstructor),
+    };
+    let dimensions = match dimensions.trim() {
+        "" => None,
+        dimensions => Some(parse_dimensions(
+            dimensions
+                .strip_suffix(',')
+                .ok_or(ParseError::InvalidConstructor)?,
+        )?),
+    };
+    let list = list_contents(list.trim())?;
+    if let Some((nrows, ncols)) = dimensions {
+        if list.trim().is_empty() && (nrows == 0 || ncols == 0) {
+            return parse_empty(nrows, ncols);
+        }
+    }
+
+    let rows = if list.contains('[') {
+        // A list of rows like `[1, 0], [0, 1]`
+        let mut rows = Vec::new();
+        for row in list.split(']') {
+            let row = row.trim().trim_start_matches(',').trim();
+            if row.is_empty() {
+                continue;
+            }
+            let row = row
+                .strip_prefix('[')
+                .ok_or(ParseError::InvalidConstructor)?;
+            rows.push(parse_row(row, rows.len())?);
+        }
+        rows
+    } else {
+        let entries = parse_row(list, 0)?;
+        match dimensions {
+            Some((nrows, ncols)) if nrows * ncols == entries.len() && ncols > 0 => {
+                let bits: Vec<bool> = entries.iter().collect();
+                bits.chunks(ncols).map(BinVector::from_bools).collect()
+            }
+            Some((nrows, ncols)) => {
+                return Err(ParseError::WrongLength {
+                    len: entries.len(),
+                    expected: nrows * ncols,
+                })
+            }
+            // Without dimensions a flat list is a single row
+            None => vec![entries],
+        }
+    };
+
+    let matrix = rows_to_matrix(rows)?;
+    match dimensions {
+        Some((nrows, ncols)) if (nrows, ncols) != (matrix.nrows(), matrix.ncols()) => {
+            Err(ParseError::WrongLength {
+                len: matrix.nrows() * matrix.ncols(),
+                expected: nrows * ncols,
+            })
+        }
+        _ => Ok(matrix),
+    }
+}
+
+/// Check that the rows are non-empty and have the same length
+fn rows_to_matrix(rows: Vec<BinVector>) -> Result<BinMatrix, ParseError> {
+    let expected = match rows.first() {
+        Some(row) if !row.is_empty() => row.len(),
+        _ => return Err(ParseError::Empty),
+    };
+    if let Some((row, vector)) = rows.iter().enumerate().find(|(_, v)| v.len() != expected) {
+        return Err(ParseError::RaggedRow {
+            row,
+            len: vector.len(),
+            expected,
+        });
+    }
+    Ok(BinMatrix::new(rows))
+}
+
+impl FromStr for BinMatrix {
+    type Err = ParseError;
+
+    /// Parse rows separated by newlines or `;`, in any `DisplayStyle`
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::BinMatrix;
+    /// let m: BinMatrix = "[1 0 1]\n[0 1 1]".parse().unwrap();
+    /// assert_eq!(m, "101; 011".parse().unwrap());
+    /// assert_eq!(m, "[1 0 1; 0 1 1]".parse().unwrap());
+    /// assert_eq!(m, "matrix(GF(2), [[1, 0, 1], [0, 1, 1]])".parse().unwrap());
+    /// assert_eq!(m, "Matrix(GF(2), 2, 3, [1, 0, 1, 0, 1, 1])".parse().unwrap());
+    /// assert_eq!(m.to_string(), "[1 0 1]\n[0 1 1]");
+    /// ```
+    fn from_str(s: &str) -> Result<BinMatrix, ParseError> {
+        let s = s.trim();
+        if let Some(arguments) =
+            constructor_arguments(s, "matrix").or_else(|| constructor_arguments(s, "Matrix"))
+        {
+            return parse_constructor(arguments?);
+        }
+        if let Some(arguments) = s.strip_prefix("zeros") {
+            let (nrows, ncols) = arguments
+                .trim_start()
+                .strip_prefix('(')
+                .and_then(|arguments| arguments.strip_suffix(')'))
+                .ok_or(ParseError::InvalidConstructor)
+                .and_then(parse_dimensions)?;
+            return parse_empty(nrows, ncols);
+        }
+        // MATLAB puts brackets around the whole matrix instead of around every row
+        let s = match s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            Some(inner) if !inner.contains(['[', ']']) => inner,
+            _ => s,
+        };
+        let rows = s
+            .split(['\n', ';'])
+            .filter(|row| !row.trim().is_empty())
+            .enumerate()
+            .map(|(index, row)| parse_row(row, index))
+            .collect::<Result<Vec<BinVector>, ParseError>>()?;
+        rows_to_matrix(rows)
+    }
+}
+
+impl FromStr for BinVector {
+    type Err = ParseError;
+
+    /// Parse a single row in any `DisplayStyle`
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::BinVector;
+    /// let v: BinVector = "vector(GF(2), [1, 0, 1])".parse().unwrap();
+    /// assert_eq!(v, BinVector::from_bools(&[true, false, true]));
+    /// ```
+    fn from_str(s: &str) -> Result<BinVector, ParseError> {
+        let s = s.trim();
+        match constructor_arguments(s, "vector").or_else(|| constructor_arguments(s, "Vector")) {
+            Some(arguments) => parse_row(list_contents(arguments?)?, 0),
+            None => parse_row(s, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display_styles() {
+        let m = BinMatrix::new(vec![
+            BinVector::from_bools(&[true, false, true]),
+            BinVector::from_bools(&[false, false, true]),
+        ]);
+        assert_eq!(m.to_string(), "[1 0 1]\n[0 0 1]");
+        assert_eq!(
+            m.display(DisplayStyle::Dots).to_string(),
+            "[1 . 1]\n[. . 1]"
+        );
+        assert_eq!(m.display(DisplayStyle::Bits).to_string(), "101\n001");
+        let v = m.row_ref(1).to_vector();
+        assert_eq!(v.to_string(), "[0 0 1]");
+        assert_eq!(m.row_ref(1).to_string(), "[0 0 1]");
+        assert_eq!(v.display(DisplayStyle::Bits).to_string(), "001");
+        assert_eq!(BinVector::new().to_string(), "[]");
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let m = BinMatrix::random(10, 70);
+        for &style in &[
+            DisplayStyle::Brackets,
+            DisplayStyle::Dots,
+            DisplayStyle::Bits,
+        ] {
+            let text = m.display(style).to_string();
+            assert_eq!(text.parse::<BinMatrix>(), Ok(m.clone()));
+            let row = m.row_ref(3).to_vector();
+            assert_eq!(row.display(style).to_string().parse(), Ok(row));
+        }
+        let semicolons: BinMatrix = "[1 0 1]; [0 1 1];".parse().unwrap();
+        assert_eq!(semicolons, "1 0 1\n\n0 1 1\n".parse().unwrap());
+        assert_eq!("1011:0010".parse::<BinVector>().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "101\n1x1".parse::<BinMatrix>(),
+            Err(ParseError::InvalidCharacter {
+                row: 1,
+                character: 'x'
+            })
+        );
+        assert_eq!(
+            "101;11".parse::<BinMatrix>(),
+            Err(ParseError::RaggedRow {
+                row: 1,
+                len: 2,
+                expected: 3
+            })
+        );
+        assert_eq!("".parse::<BinMatrix>(), Err(ParseError::Empty));
+        assert_eq!("[]".parse::<BinMatrix>(), Err(ParseError::Empty));
+        assert!("10;1".parse::<BinVector>().is_err());
+    }
+
+    #[test]
+    fn cas_syntax() {
+        let m: BinMatrix = "101; 011".parse().unwrap();
+        assert_eq!(
+            m.display(DisplayStyle::Sage).to_string(),
+            "matrix(GF(2), [[1, 0, 1], [0, 1, 1]])"
+        );
+        assert_eq!(
+            m.display(DisplayStyle::Magma).to_string(),
+            "Matrix(GF(2), 2, 3, [1, 0, 1, 0, 1, 1])"
+        );
+        assert_eq!(
+            m.display(DisplayStyle::Matlab).to_string(),
+            "[1 0 1; 0 1 1]"
+        );
+        let v: BinVector = "101".parse().unwrap();
+        assert_eq!(
+            v.display(DisplayStyle::Sage).to_string(),
+            "vector(GF(2), [1, 0, 1])"
+        );
+        assert_eq!(
+            v.display(DisplayStyle::Magma).to_string(),
+            "Vector(GF(2), [1, 0, 1])"
+        );
+        assert_eq!(v.display(DisplayStyle::Matlab).to_string(), "[1 0 1]");
+
+        // Other forms the constructors accept
+        for text in &[
+            "matrix(GF(2), 2, 3, [[1,0,1],[0,1,1]])",
+            "matrix(GF(2), 2, 3, [1, 0, 1, 0, 1, 1])",
+            "Matrix(GF(2), [[1, 0, 1], [0, 1, 1]])",
+            " Matrix( GF(2) , 2,3,\n[1,0,1,\n 0,1,1] ) ",
+            "[1, 0, 1; 0, 1, 1]",
+            "[1 0 1\n 0 1 1]",
+        ] {
+            assert_eq!(text.parse::<BinMatrix>(), Ok(m.clone()), "{}", text);
+        }
+        assert_eq!(
+            "matrix(GF(2), [1, 1])"
+                .parse::<BinMatrix>()
+                .unwrap()
+                .nrows(),
+            1
+        );
+    }
+
+    #[test]
+    fn cas_round_trip() {
+        let m = BinMatrix::random(10, 70);
+        let v = BinVector::random(70);
+        for &style in &[
+            DisplayStyle::Sage,
+            DisplayStyle::Magma,
+            DisplayStyle::Matlab,
+        ] {
+            assert_eq!(m.display(style).to_string().parse(), Ok(m.clone()));
+            assert_eq!(v.display(style).to_string().parse(), Ok(v.clone()));
+            let row = m.row_ref(0).to_vector();
+            let single_row = row.as_matrix();
+            assert_eq!(
+                single_row.display(style).to_string().parse(),
+                Ok(single_row)
+            );
+        }
+    }
+
+    #[test]
+    fn cas_without_rows_or_columns() {
+        for &(nrows, ncols) in &[(0, 3), (3, 0), (0, 0)] {
+            let m = BinMatrix::zero(nrows, ncols);
+            for &style in &[
+                DisplayStyle::Sage,
+                DisplayStyle::Magma,
+                DisplayStyle::Matlab,
+            ] {
+                assert_eq!(m.display(style).to_string().parse(), Ok(m.clone()));
+            }
+        }
+        assert_eq!(
+            BinMatrix::zero(0, 3)
+                .display(DisplayStyle::Sage)
+                .to_string(),
+            "matrix(GF(2), 0, 3, [])"
+        );
+        assert_eq!(
+            BinMatrix::zero(3, 0)
+                .display(DisplayStyle::Matlab)
+                .to_string(),
+            "zeros(3, 0)"
+        );
+    }
+
+    #[test]
+    fn cas_errors() {
+        let parse = |s: &str| s.parse::<BinMatrix>();
+        assert_eq!(
+            parse("matrix(GF(3), [[1]])"),
+            Err(ParseError::InvalidConstructor)
+        );
+        assert_eq!(
+            parse("matrix(GF(2), [[1]]"),
+            Err(ParseError::InvalidConstructor)
+        );
+        assert_eq!(
+            parse("matrix(GF(2), 1, [1])"),
+            Err(ParseError::InvalidConstructor)
+        );
+        assert_eq!(
+            parse("matrix(GF(2), [[1], 1])"),
+            Err(ParseError::InvalidConstructor)
+        );
+        assert_eq!(
+            parse("Matrix(GF(2), 2, 2, [1, 0, 1])"),
+            Err(ParseError::WrongLength {
+                len: 3,
+                expected: 4
+            })
+        );
+        assert_eq!(
+            parse("matrix(GF(2), 1, 2, [[1, 0], [0, 1]])"),
+            Err(ParseError::WrongLength {
+                len: 4,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            parse("matrix(GF(2), [[1, 0], [1]])"),
+            Err(ParseError::RaggedRow {
+                row: 1,
+                len: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(parse("matrix(GF(2), [])"), Err(ParseError::Empty));
+        assert_eq!(parse("zeros(2, 3)"), Err(ParseError::InvalidConstructor));
+        assert_eq!(parse("zeros(2)"), Err(ParseError::InvalidConstructor));
+        assert_eq!(
+            parse(&format!("zeros(0, {})", usize::MAX)),
+            Err(ParseError::InvalidConstructor)
+        );
+        assert_eq!(
+            parse("matrix(GF(2), 2, 3, [])"),
+            Err(ParseError::WrongLength {
+                len: 0,
+                expected: 6
+            })
+        );
+        assert_eq!(
+            "vector(GF(2), 1, 0)".parse::<BinVector>(),
+            Err(ParseError::InvalidConstructor)
+        );
+    }
+
+    #[test]
+    fn debug_preview() {
+        let m: BinMatrix = "101; 011".parse().unwrap();
+        assert_eq!(
+            format!("{:?}", m),
+            "BinMatrix { nrows: 2, ncols: 3, rows: [101, 011] }"
+        );
+        let v: BinVector = "0110".parse().unwrap();
+        assert_eq!(format!("{:?}", v), "BinVector { len: 4, bits: 0110 }");
+
+        let big = BinMatrix::zero(100, 200);
+        let debug = format!("{:?}", big);
+        assert!(debug.starts_with("BinMatrix { nrows: 100, ncols: 200, rows: [0000"));
+        assert!(debug.contains("... (136 more), ... (92 more rows)]"));
+    }
+}
@@ -0,0 +1,644 @@
+//! A common interface for matrices over GF(2)
+//!
+//! Generic algorithms can be written against `Gf2Matrix` and then work on
+//! `BinMatrix`, borrowed views like `BinMatrixView`, or other representations
+//! such as small fixed-size matrices alike.
+//! Implementations only need to provide the shape and bit access; every
+//! other operation has a (slow) default implementation in terms of those,
+//! which implementations can override with faster versions.
+use std::ops::Range;
+
+use friendly::binary_matrix::BinMatrix;
+use friendly::binary_vector::BinVector;
+
+/// Read access to a matrix over GF(2)
+pub trait Gf2Matrix {
+    /// The matrix type that is returned by operations that create a new matrix
+    type Owned: Gf2MatrixMut;
+
+    /// Get the number of rows
+    fn nrows(&self) -> usize;
+
+    /// Get the number of columns
+    fn ncols(&self) -> usize;
+
+    /// Get the bit at (row, col)
+    fn bit(&self, row: usize, col: usize) -> bool;
+
+    /// Get the shape as `(nrows, ncols)`
+    fn shape(&self) -> (usize, usize) {
+        (self.nrows(), self.ncols())
+    }
+
+    /// Get a copy of a row
+    fn row_vector(&self, row: usize) -> BinVector {
+        let mut result = BinVector::from_elem(self.ncols(), false);
+        for col in 0..self.ncols() {
+            if self.bit(row, col) {
+                result.set(col, true);
+            }
+        }
+        result
+    }
+
+    /// Copy this matrix into a new owned matrix
+    fn to_owned_matrix(&self) -> Self::Owned {
+        let mut result = Self::Owned::zero(self.nrows(), self.ncols());
+        for row in 0..self.nrows() {
+            for col in 0..self.ncols() {
+                if self.bit(row, col) {
+                    result.set_bit(row, col, true);
+                }
+            }
+        }
+        result
+    }
+
+    /// Compute the transpose of the matrix
+    fn transposed(&self) -> Self::Owned {
+        let mut result = Self::Owned::zero(self.ncols(), self.nrows());
+        for row in 0..self.nrows() {
+            for col in 0..self.ncols() {
+                if self.bit(row, col) {
+                    result.set_bit(col, row, true);
+                }
+            }
+        }
+        result
+    }
+
+    /// Compute `self + other`
+    ///
+    /// **Panics** if the shapes differ
+    fn add_matrix<M: Gf2Matrix + ?Sized>(&self, other: &M) -> Self::Owned {
+        assert_eq!(self.shape(), other.shape(), "Mismatched sizes");
+        let mut result = self.to_owned_matrix();
+        for row in 0..other.nrows() {
+            for col in 0..other.ncols() {
+                if other.bit(row, col) {
+                    let bit = result.bit(row, col);
+                    result.set_bit(row, col, !bit);
+                }
+            }
+        }
+        result
+    }
+
+    /// Compute `self * other`
+    ///
+    /// **Panics** if the number of columns of `self` is not the number of rows of `other`
+    fn mul_matrix<M: Gf2Matrix + ?Sized>(&self, other: &M) -> Self::Owned {
+        assert_eq!(self.ncols(), other.nrows(), "Mismatched sizes");
+        let mut result = Self::Owned::zero(self.nrows(), other.ncols());
+        for row in 0..self.nrows() {
+            for col in 0..other.ncols() {
+                let bit = (0..self.ncols())
+                    .filter(|&i| self.bit(row, i) && other.bit(i, col))
+                    .count()
+                    % 2
+                    == 1;
+                if bit {
+                    result.set_bit(row, col, true);
+                }
+            }
+        }
+        result
+    }
+
+    /// Compute `self * v`
+    ///
+    /// **Panics** if the length of `v` is not the number of columns
+    fn mul_vector(&self, v: &BinVector) -> BinVector {
+        assert_eq!(self.ncols(), v.len(), "Mismatched sizes");
+        let mut result = BinVector::from_elem(self.nrows(), false);
+        for row in 0..self.nrows() {
+            let bit = v
+                .iter_set_bits(..)
+                .filter(|&col| self.bit(row, col))
+                .count()
+                % 2
+                == 1;
+            result.set(row, bit);
+        }
+        result
+    }
+
+    /// Get the rank of the matrix
+    fn rank(&self) -> usize {
+        self.to_owned_matrix().echelonize()
+    }
+
+    /// Solve `self * x = b` for `x`
+    ///
+    /// Free variables are set to zero. Returns `None` if there is no solution.
+    ///
+    /// **Panics** if the length of `b` is not the number of rows
+    fn solve(&self, b: &BinVector) -> Option<BinVector> {
+        assert_eq!(self.nrows(), b.len(), "Mismatched sizes");
+        let ncols = self.ncols();
+        let mut rows: Vec<BinVector> = (0..self.nrows())
+            .map(|row| {
+                let mut row_vector = self.row_vector(row);
+                row_vector.push(b[row]);
+                row_vector
+            })
+            .collect();
+        let pivots = reduce_rows(&mut rows, ncols);
+        if rows[pivots.len()..].iter().any(|row| row[ncols]) {
+            return None;
+        }
+        let mut solution = BinVector::from_elem(ncols, false);
+        for (row, &col) in rows.iter().zip(pivots.iter()) {
+            solution.set(col, row[ncols]);
+        }
+        Some(solution)
+    }
+
+    /// Compute a basis of the (right) kernel: all `x` with `self * x = 0`
+    fn kernel(&self) -> Vec<BinVector> {
+        let ncols = self.ncols();
+        let mut rows: Vec<BinVector> = (0..self.nrows()).map(|row| self.row_vector(row)).collect();
+        let pivots = reduce_rows(&mut rows, ncols);
+        let mut is_pivot = vec![false; ncols];
+        for &col in &pivots {
+            is_pivot[col] = true;
+        }
+        (0..ncols)
+            .filter(|&free| !is_pivot[free])
+            .map(|free| {
+                let mut x = BinVector::from_elem(ncols, false);
+                x.set(free, true);
+                for (row, &col) in rows.iter().zip(pivots.iter()) {
+                    x.set(col, row[free]);
+                }
+                x
+            })
+            .collect()
+    }
+}
+
+/// Write access to a matrix over GF(2)
+pub trait Gf2MatrixMut: Gf2Matrix {
+    /// Create a zero matrix
+    ///
+    /// **Panics** if the implementation does not support this shape
+    fn zero(nrows: usize, ncols: usize) -> Self
+    where
+        Self: Sized;
+
+    /// Set the bit at (row, col)
+    fn set_bit(&mut self, row: usize, col: usize, bit: bool);
+
+    /// Swap two rows
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for col in 0..self.ncols() {
+            let (bit_a, bit_b) = (self.bit(a, col), self.bit(b, col));
+            self.set_bit(a, col, bit_b);
+            self.set_bit(b, col, bit_a);
+        }
+    }
+
+    /// Add row `src` to row `dst`
+    fn add_row(&mut self, src: usize, dst: usize) {
+        for col in 0..self.ncols() {
+            if self.bit(src, col) {
+                let bit = self.bit(dst, col);
+                self.set_bit(dst, col, !bit);
+            }
+        }
+    }
+
+    /// Bring the matrix in row echelon form, which need not be reduced
+    ///
+    /// Return: the rank of the matrix
+    fn echelonize(&mut self) -> usize {
+        let mut rank = 0;
+        for col in 0..self.ncols() {
+            if rank == self.nrows() {
+                break;
+            }
+            if let Some(pivot) = (rank..self.nrows()).find(|&row| self.bit(row, col)) {
+                self.swap_rows(pivot, rank);
+                for row in rank + 1..self.nrows() {
+                    if self.bit(row, col) {
+                        self.add_row(rank, row);
+                    }
+                }
+                rank += 1;
+            }
+        }
+        rank
+    }
+}
+
+/// Bring the rows in reduced row echelon form, only looking for pivots in the first `ncols` columns
+///
+/// Returns the pivot columns
+fn reduce_rows(rows: &mut [BinVector], ncols: usize) -> Vec<usize> {
+    let mut pivots = Vec::new();
+    for col in 0..ncols {
+        let rank = pivots.len();
+        if rank == rows.len() {
+            break;
+        }
+        if let Some(pivot) = (rank..rows.len()).find(|&row| rows[row][col]) {
+            rows.swap(pivot, rank);
+            let pivot_row = rows[rank].clone();
+            for (row_index, row) in rows.iter_mut().enumerate() {
+                if row_index != rank && row[col] {
+                    *row += &pivot_row;
+                }
+            }
+            pivots.push(col);
+        }
+    }
+    pivots
+}
+
+impl Gf2Matrix for BinMatrix {
+    type Owned = BinMatrix;
+
+    #[inline]
+    fn nrows(&self) -> usize {
+        BinMatrix::nrows(self)
+    }
+
+    #[inline]
+    fn ncols(&self) -> usize {
+        BinMatrix::ncols(self)
+    }
+
+    #[inline]
+    fn bit(&self, row: usize, col: usize) -> bool {
+        BinMatrix::bit(self, row, col)
+    }
+
+    #[inline]
+    fn row_vector(&self, row: usize) -> BinVector {
+        self.row_ref(row).to_vector()
+    }
+
+    #[inline]
+    fn to_owned_matrix(&self) -> BinMatrix {
+        self.clone()
+    }
+
+    #[inline]
+    fn transposed(&self) -> BinMatrix {
+        BinMatrix::transposed(self)
+    }
+
+    fn add_matrix<M: Gf2Matrix + ?Sized>(&self, other: &M) -> BinMatrix {
+        assert_eq!(Gf2Matrix::shape(self), other.shape(), "Mismatched sizes");
+        let other = BinMatrix::from_rows_iter(
+            (0..other.nrows()).map(|r| other.row_vector(r)),
+            self.ncols(),
+        );
+        self + &other
+    }
+
+    fn mul_matrix<M: Gf2Matrix + ?Sized>(&self, other: &M) -> BinMatrix {
+        let other = BinMatrix::from_rows_iter(
+            (0..other.nrows()).map(|r| other.row_vector(r)),
+            other.ncols(),
+        );
+        self * &other
+    }
+
+    #[inline]
+    fn mul_vector(&self, v: &BinVector) -> BinVector {
+        self * v
+    }
+
+    #[inline]
+    fn rank(&self) -> usize {
+        BinMatrix::rank(self)
+    }
+
+    /// Echelonizes `[self | b]` with M4RI
+    fn solve(&self, b: &BinVector) -> Option<BinVector> {
+        assert_eq!(BinMatrix::nrows(self), b.len(), "Mismatched sizes");
+        let ncols = BinMatrix::ncols(self);
+        if BinMatrix::nrows(self) == 0 || ncols == 0 {
+            // Only `b = 0` is in the column space
+            return if b.count_ones() == 0 {
+                Some(BinVector::from_elem(ncols, false))
+            } else {
+                None
+            };
+        }
+        let mut augmented = self.augmented(&b.as_column_matrix());
+        let rank = augmented.echelonize_reduced();
+        let mut solution = BinVector::from_elem(ncols, false);
+        for row in 0..rank {
+            let pivot = augmented.row_ref(row).iter_set_bits().next();
+            let pivot = pivot.expect("rows within the rank are nonzero");
+            // A pivot in `b` makes the system inconsistent
+            if pivot == ncols {
+                return None;
+            }
+            solution.set(pivot, augmented.bit(row, ncols));
+        }
+        Some(solution)
+    }
+
+    /// Uses M4RI's PLUQ-based kernel computation
+    fn kernel(&self) -> Vec<BinVector> {
+        self.kernel_rows()
+            .rows()
+            .map(|row| row.to_vector())
+            .collect()
+    }
+}
+
+impl Gf2MatrixMut for BinMatrix {
+    #[inline]
+    fn zero(nrows: usize, ncols: usize) -> BinMatrix {
+        BinMatrix::zero(nrows, ncols)
+    }
+
+    #[inline]
+    fn set_bit(&mut self, row: usize, col: usize, bit: bool) {
+        BinMatrix::set_bit(self, row, col, bit)
+    }
+
+    #[inline]
+    fn echelonize(&mut self) -> usize {
+        BinMatrix::echelonize(self)
+    }
+}
+
+/// A rectangular part of a `BinMatrix`, borrowed without copying
+///
+/// Created with `BinMatrix::view`. Operations that produce a new matrix,
+/// such as `rank`, `solve` and `kernel`, copy the view into a `BinMatrix` first
+/// and then use M4RI.
+#[derive(Clone, Debug)]
+pub struct BinMatrixView<'a> {
+    matrix: &'a BinMatrix,
+    rows: Range<usize>,
+    cols: Range<usize>,
+}
+
+impl BinMatrix {
+    /// Borrow the rows `rows` and the columns `cols` of this matrix
+    ///
+    /// **Panics** if the ranges are out of bounds
+    ///
+    /// # Example
+    /// ```
+    /// # use m4ri_rust::friendly::{BinMatrix, Gf2Matrix};
+    /// let m: BinMatrix = "1101; 0111; 1010".parse().unwrap();
+    /// let view = m.view(1..3, 1..4);
+    /// assert_eq!(view.shape(), (2, 3));
+    /// assert_eq!(view.to_owned_matrix(), "111; 010".parse().unwrap());
+    /// ```
+    pub fn view(&self, rows: Range<usize>, cols: Range<usize>) -> BinMatrixView<'_> {
+        assert!(
+            rows.start <= rows.end && rows.end <= self.nrows(),
+            "Rows {:?} out of bounds for a matrix with {} rows",
+            rows,
+            self.nrows()
+        );
+        assert!(
+            cols.start <= cols.end && cols.end <= self.ncols(),
+            "Columns {:?} out of bounds for a matrix with {} columns",
+            cols,
+            self.ncols()
+        );
+        BinMatrixView {
+            matrix: self,
+            rows,
+            cols,
+        }
+    }
+}
+
+impl<'a> Gf2Matrix for BinMatrixView<'a> {
+    type Owned = BinMatrix;
+
+    #[inline]
+    fn nrows(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[inline]
+    fn ncols(&self) -> usize {
+        self.cols.len()
+    }
+
+    fn bit(&self, row: usize, col: usize) -> bool {
+        assert!(
+            row < self.nrows() && col < self.ncols(),
+            "Position ({}, {}) out of bounds for a {}x{} view",
+            row,
+            col,
+            self.nrows(),
+            self.ncols()
+        );
+        self.matrix
+            .bit(self.rows.start + row, self.cols.start + col)
+    }
+
+    fn row_vector(&self, row: usize) -> BinVector {
+        assert!(row < self.nrows(), "row {} out of bounds", row);
+        let row = self.matrix.row_ref(self.rows.start + row);
+        let bits: Vec<bool> = self
+            .cols
+            .clone()
+            .map(|col| row.get(col) == Some(true))
+            .collect();
+        BinVector::from_bools(&bits)
+    }
+
+    fn to_owned_matrix(&self) -> BinMatrix {
+        let rows = (0..self.nrows()).map(|row| self.row_vector(row));
+        BinMatrix::from_rows_iter(rows, self.ncols())
+    }
+
+    fn transposed(&self) -> BinMatrix {
+        self.to_owned_matrix().transposed()
+    }
+
+    fn rank(&self) -> usize {
+        self.to_owned_matrix().rank()
+    }
+
+    fn solve(&self, b: &BinVector) -> Option<BinVector> {
+        self.to_owned_matrix().solve(b)
+    }
+
+    fn kernel(&self) -> Vec<BinVector> {
+        self.to_owned_matrix().kernel()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use friendly::test_matrices;
+
+    /// A fixed-size 8x8 matrix stored in one byte per row
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+    struct Small([u8; 8]);
+
+    impl Gf2Matrix for Small {
+        type Owned = Small;
+
+        fn nrows(&self) -> usize {
+            8
+        }
+
+        fn ncols(&self) -> usize {
+            8
+        }
+
+        fn bit(&self, row: usize, col: usize) -> bool {
+            (self.0[row] >> col) & 1 == 1
+        }
+
+        fn to_owned_matrix(&self) -> Small {
+            *self
+        }
+    }
+
+    impl Gf2MatrixMut for Small {
+        fn zero(nrows: usize, ncols: usize) -> Small {
+            assert_eq!((nrows, ncols), (8, 8), "Only 8x8 matrices are supported");
+            Small::default()
+        }
+
+        fn set_bit(&mut self, row: usize, col: usize, bit: bool) {
+            if bit {
+                self.0[row] |= 1 << col;
+            } else {
+                self.0[row] &= !(1 << col);
+            }
+        }
+
+        fn swap_rows(&mut self, a: usize, b: usize) {
+            self.0.swap(a, b);
+        }
+
+        fn add_row(&mut self, src: usize, dst: usize) {
+            self.0[dst] ^= self.0[src];
+        }
+    }
+
+    /// Generic code that only uses the trait
+    fn is_invertible<M: Gf2Matrix>(m: &M) -> bool {
+        m.nrows() == m.ncols() && m.rank() == m.nrows()
+    }
+
+    #[test]
+    fn small_matrix() {
+        let identity = Small([1, 2, 4, 8, 16, 32, 64, 128]);
+        assert!(is_invertible(&identity));
+        let m = Small([0b11, 0b110, 0b1100, 0, 0, 0, 0, 0b1000_0001]);
+        assert_eq!(m.rank(), 4);
+        assert!(!is_invertible(&m));
+        assert_eq!(m.mul_matrix(&identity), m);
+        assert_eq!(m.add_matrix(&m), Small::default());
+        assert_eq!(m.transposed().transposed(), m);
+        assert_eq!(m.transposed().0[0], 0b1000_0001);
+
+        let kernel = m.kernel();
+        assert_eq!(kernel.len(), 4);
+        for x in &kernel {
+            assert_eq!(m.mul_vector(x).count_ones(), 0);
+        }
+    }
+
+    #[test]
+    fn solve_and_kernel() {
+        let m = BinMatrix::random(20, 30);
+        let x = BinVector::random(30);
+        let b = &m * &x;
+        let solution = Gf2Matrix::solve(&m, &b).expect("There is a solution");
+        assert_eq!(&m * &solution, b);
+
+        let kernel = m.kernel();
+        assert_eq!(kernel.len(), 30 - Gf2Matrix::rank(&m));
+        for x in &kernel {
+            assert_eq!((&m * x).count_ones(), 0);
+        }
+
+        let zero_rows = BinMatrix::zero(2, 3);
+        assert_eq!(
+            Gf2Matrix::solve(&zero_rows, &BinVector::from_bools(&[true, false])),
+            None
+        );
+    }
+
+    #[test]
+    fn solve_and_kernel_without_rows_or_columns() {
+        for m in test_matrices() {
+            let (nrows, ncols) = (m.nrows(), m.ncols());
+            let x = BinVector::random(ncols);
+            let b = &m * &x;
+            let solution = Gf2Matrix::solve(&m, &b).expect("There is a solution");
+            assert_eq!(&m * &solution, b);
+            let kernel = m.kernel();
+            assert_eq!(kernel.len(), ncols - Gf2Matrix::rank(&m));
+            for x in &kernel {
+                assert_eq!((&m * x).count_ones(), 0);
+            }
+            if ncols == 0 && nrows > 0 {
+                let b = BinVector::from_elem(nrows, true);
+                assert_eq!(Gf2Matrix::solve(&m, &b), None);
+            }
+        }
+    }
+
+    #[test]
+    fn views() {
+        let m = BinMatrix::random(20, 150);
+        let view = m.view(3..13, 60..130);
+        let window = m.get_window(3, 60, 13, 130);
+        assert_eq!(view.shape(), (10, 70));
+        assert_eq!(view.to_owned_matrix(), window);
+        assert_eq!(view.row_vector(4), window.row_ref(4).to_vector());
+        assert!((0..10).all(|r| (0..70).all(|c| view.bit(r, c) == window.bit(r, c))));
+        assert_eq!(view.transposed(), window.transposed());
+        assert_eq!(view.rank(), window.rank());
+        assert_eq!(
+            view.mul_matrix(&window.transposed()),
+            &window * &window.transposed()
+        );
+
+        let x = BinVector::random(70);
+        let b = &window * &x;
+        assert_eq!(&window * &view.solve(&b).unwrap(), b);
+        assert_eq!(view.kernel(), window.kernel());
+        assert_eq!(m.view(5..5, 0..150).shape(), (0, 150));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn view_out_of_bounds() {
+        BinMatrix::zero(2, 3).view(0..2, 1..4);
+    }
+
+    #[test]
+    fn binmatrix_matches_defaults() {
+        let small = Small([0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0]);
+        let m = BinMatrix::from_rows_iter((0..8).map(|r| small.row_vector(r)), 8);
+        for r in 0..8 {
+            for c in 0..8 {
+                assert_eq!(Gf2Matrix::bit(&m, r, c), small.bit(r, c));
+            }
+        }
+        assert_eq!(Gf2Matrix::rank(&m), small.rank());
+        assert_eq!(
+            m.mul_matrix(&small),
+            BinMatrix::from_rows_iter((0..8).map(|r| small.mul_matrix(&small).row_vector(r)), 8)
+        );
+        let mut sum = m.add_matrix(&small);
+        assert_eq!(Gf2MatrixMut::echelonize(&mut sum), 0);
+        let mut copy = m.clone();
+        copy.set_bit(3, 7, true);
+        assert!(copy.bit(3, 7));
+        copy.set_bit(3, 7, false);
+        assert!(!copy.bit(3, 7));
+    }
+}
@@ -0,0 +1,284 @@
+//! Import and export of matrices as 1-bit images
+//!
+//! Set bits are black pixels, as in the PBM format. Row `i` of the matrix
+//! is line `i` of the image, so the image is `ncols()` pixels wide.
+//! PNG support needs the `png` feature.
+use std::io;
+use std::io::{Read, Write};
+
+use friendly::binary_matrix::BinMatrix;
+use friendly::binary_vector::{BinVector, BinVectorRef, BitOrder};
+
+/// The two variants of the portable bitmap format
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PbmFormat {
+    /// `P1`: the pixels as ASCII `0` and `1`, one line per row
+    Plain,
+    /// `P4`: eight pixels per byte, most significant bit first
+    Raw,
+}
+
+pub(crate) fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(
+    error: E,
+) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// The bytes of a row with eight pixels per byte, the first pixel in the most significant bit
+fn row_to_bytes(row: BinVectorRef) -> Vec<u8> {
+    let mut bytes: Vec<u8> = row
+        .masked_words()
+        .flat_map(|word| word.to_le_bytes().to_vec())
+        .map(u8::reverse_bits)
+        .collect();
+    bytes.truncate(row.len().div_ceil(8));
+    bytes
+}
+
+/// The inverse of `row_to_bytes`
+fn bytes_to_row(bytes: &[u8], len: usize) -> BinVector {
+    let mut row = BinVector::from_le_bytes(bytes, BitOrder::MsbFirst);
+    row.truncate(len);
+    row
+}
+
+/// Reads the whitespace-separated tokens of a PBM header, skipping comments
+struct Header<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Header<'a> {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&byte) = self.data.get(self.position) {
+            if byte == b'#' {
+                while self.position < self.data.len() && self.data[self.position] != b'\n' {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self, what: &str) -> io::Result<usize> {
+        self.skip_whitespace_and_comments();
+        let start = self.position;
+        while self
+            .data
+            .get(self.position)
+            .is_some_and(|byte| byte.is_ascii_digit())
+        {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.position])
+            .unwrap()
+            .parse()
+            .map_err(|_| invalid_data(format!("invalid PBM {}", what)))
+    }
+}
+
+impl BinMatrix {
+    /// Write the matrix as a portable bitmap
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::{BinMatrix, PbmFormat};
+    /// let m = BinMatrix::identity(2);
+    /// let mut pbm = Vec::new();
+    /// m.write_pbm(&mut pbm, PbmFormat::Plain).unwrap();
+    /// assert_eq!(pbm, b"P1\n2 2\n10\n01\n");
+    /// assert_eq!(BinMatrix::read_pbm(&pbm[..]).unwrap(), m);
+    /// ```
+    pub fn write_pbm<W: Write>(&self, writer: W, format: PbmFormat) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(writer);
+        let magic = match format {
+            PbmFormat::Plain => "P1",
+            PbmFormat::Raw => "P4",
+        };
+        writeln!(writer, "{}\n{} {}", magic, self.ncols(), self.nrows())?;
+        for row in self {
+            match format {
+                PbmFormat::Plain => {
+                    // Lines of PBM files should not be longer than 70 characters
+                    for i in 0..row.len() {
+                        if i > 0 && i % 70 == 0 {
+                            writer.write_all(b"\n")?;
+                        }
+                        let bit = row.get(i) == Some(true);
+                        writer.write_all(if bit { b"1" } else { b"0" })?;
+                    }
+                    writer.write_all(b"\n")?;
+                }
+                PbmFormat::Raw => writer.write_all(&row_to_bytes(row))?,
+            }
+        }
+        writer.flush()
+    }
+
+    /// Read a matrix from a portable bitmap in either format
+    pub fn read_pbm<R: Read>(mut reader: R) -> io::Result<BinMatrix> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let format = match data.get(..2) {
+            Some(b"P1") => PbmFormat::Plain,
+            Some(b"P4") => PbmFormat::Raw,
+            _ => return Err(invalid_data("not a PBM file")),
+        };
+        let mut header = Header {
+            data: &data,
+            position: 2,
+        };
+        let ncols = header.number("width")?;
+        let nrows = header.number("height")?;
+        if format == PbmFormat::Raw
+            && !data
+                .get(header.position)
+                .is_some_and(|byte| byte.is_ascii_whitespace())
+        {
+            // A single whitespace character separates the header from the data
+            return Err(invalid_data("invalid PBM header"));
+        }
+        // Check the size in the header against the data before allocating the matrix
+        let row_bytes = match format {
+            PbmFormat::Plain => ncols,
+            PbmFormat::Raw => ncols.div_ceil(8),
+        };
+        let available = data.len() - header.position.min(data.len());
+        if nrows
+            .checked_mul(row_bytes)
+            .is_none_or(|needed| needed > available)
+        {
+            return Err(invalid_data("PBM data is too short"));
+        }
+        let mut matrix = BinMatrix::try_zero(nrows, ncols).map_err(invalid_data)?;
+
+        match format {
+            PbmFormat::Plain => {
+                let mut bits = Vec::with_capacity(ncols);
+                for row in 0..nrows {
+                    bits.clear();
+                    while bits.len() < ncols {
+                        header.skip_whitespace_and_comments();
+                        match data.get(header.position) {
+                            Some(b'0') => bits.push(false),
+                            Some(b'1') => bits.push(true),
+                            Some(_) => return Err(invalid_data("invalid pixel in PBM data")),
+                            None => return Err(invalid_data("PBM data is too short")),
+                        }
+                        header.position += 1;
+                    }
+                    matrix.set_row(row, &BinVector::from_bools(&bits));
+                }
+            }
+            PbmFormat::Raw => {
+                let pixels = &data[header.position + 1..];
+                if pixels.len() < nrows * row_bytes {
+                    return Err(invalid_data("PBM data is too short"));
+                }
+                for row in 0..nrows {
+                    let bytes = &pixels[row * row_bytes..(row + 1) * row_bytes];
+                    matrix.set_row(row, &bytes_to_row(bytes, ncols));
+                }
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// Write the matrix as a black and white PNG image
+    #[cfg(feature = "png")]
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let to_u32 = |n: usize| {
+            std::convert::TryFrom::try_from(n).map_err(|_| invalid_data("matrix is too large"))
+        };
+        let mut encoder = png::Encoder::new(writer, to_u32(self.ncols())?, to_u32(self.nrows())?);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+        let mut writer = encoder.write_header()?;
+        // In grayscale zero is black
+        let data: Vec<u8> = self
+            .rows()
+            .flat_map(|row| row_to_bytes(row).into_iter().map(|byte| !byte))
+            .collect();
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Read a matrix from a PNG image
+    ///
+    /// Pixels of which the gray level, or the red channel for color images,
+    /// is below half of the maximum are set bits. Transparency is ignored.
+    #[cfg(feature = "png")]
+    pub fn read_png<R: Read>(reader: R) -> io::Result<BinMatrix> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data)?;
+        let (nrows, ncols) = (info.height as usize, info.width as usize);
+        let samples = info.color_type.samples();
+        let mut matrix = BinMatrix::try_zero(nrows, ncols).map_err(invalid_data)?;
+        let mut bits = Vec::with_capacity(ncols);
+        for (row, line) in data.chunks(info.line_size).take(nrows).enumerate() {
+            bits.clear();
+            bits.extend(
+                line.chunks(samples)
+                    .take(ncols)
+                    .map(|pixel| pixel[0] < 0x80),
+            );
+            matrix.set_row(row, &BinVector::from_bools(&bits));
+        }
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use friendly::test_matrices;
+
+    #[test]
+    fn pbm_round_trip() {
+        for m in test_matrices() {
+            for &format in &[PbmFormat::Plain, PbmFormat::Raw] {
+                let mut pbm = Vec::new();
+                m.write_pbm(&mut pbm, format).unwrap();
+                assert_eq!(BinMatrix::read_pbm(&pbm[..]).unwrap(), m);
+            }
+        }
+    }
+
+    #[test]
+    fn read_pbm() {
+        let plain = b"P1\n# identity\n3 2 # width, height\n1 0 0\n0 1 0\n";
+        let m = BinMatrix::read_pbm(&plain[..]).unwrap();
+        assert_eq!(m, "100; 010".parse().unwrap());
+
+        let raw = b"P4\n10 2\n\x80\x40\xff\xc0";
+        let m = BinMatrix::read_pbm(&raw[..]).unwrap();
+        assert_eq!(m, "1000000001; 1111111111".parse().unwrap());
+        let mut written = Vec::new();
+        m.write_pbm(&mut written, PbmFormat::Raw).unwrap();
+        assert_eq!(&written[..], b"P4\n10 2\n\x80\x40\xff\xc0");
+
+        assert!(BinMatrix::read_pbm(&b"P2\n1 1\n0\n"[..]).is_err());
+        assert!(BinMatrix::read_pbm(&b"P1\n2 2\n1 0 1\n"[..]).is_err());
+        assert!(BinMatrix::read_pbm(&b"P1\n1 1\n2\n"[..]).is_err());
+        assert!(BinMatrix::read_pbm(&b"P4\n9 2\n\x00\x00\x00"[..]).is_err());
+        // Headers of huge images are rejected before allocating
+        let error = BinMatrix::read_pbm(&b"P4\n100000000 100000000\n"[..]).unwrap_err();
+        assert_eq!(error.to_string(), "PBM data is too short");
+        assert!(BinMatrix::read_pbm(&b"P1\n100000000 100000000\n1"[..]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn png_round_trip() {
+        let m = BinMatrix::random(13, 70);
+        let mut png = Vec::new();
+        m.write_png(&mut png).unwrap();
+        assert_eq!(BinMatrix::read_png(&png[..]).unwrap(), m);
+    }
+}
@@ -0,0 +1,299 @@
+//! Conversions from and to the types of other crates
+//!
+//! Each crate has its own feature: `ndarray`, `bitvec` and `bit-vec`.
+//! The conversions copy whole words where the layouts allow it.
+
+#[cfg(feature = "ndarray")]
+mod ndarray_conversions {
+    use std::convert::TryFrom;
+
+    use ndarray::{Array2, ArrayView1, ArrayView2};
+
+    use friendly::binary_matrix::BinMatrix;
+    use friendly::binary_vector::ConversionError;
+
+    /// Pack a row of an array into words, least significant bit first
+    fn pack_row<T: Copy, F: Fn(T) -> bool>(row: ArrayView1<T>, words: &mut [u64], is_set: F) {
+        for word in words.iter_mut() {
+            *word = 0;
+        }
+        for (i, &value) in row.iter().enumerate() {
+            words[i / 64] |= (is_set(value) as u64) << (i % 64);
+        }
+    }
+
+    /// Unpack the rows of a matrix into a row-major array
+    fn unpack<T, F: Fn(bool) -> T>(matrix: &BinMatrix, value: F) -> Array2<T> {
+        let mut values = Vec::with_capacity(matrix.nrows() * matrix.ncols());
+        for row in matrix.rows() {
+            let len = row.len();
+            for (i, word) in row.masked_words().enumerate() {
+                let bits = (len - i * 64).min(64);
+                values.extend((0..bits).map(|bit| value((word >> bit) & 1 == 1)));
+            }
+        }
+        Array2::from_shape_vec((matrix.nrows(), matrix.ncols()), values)
+            .expect("the values match the shape")
+    }
+
+    impl<'a> From<ArrayView2<'a, bool>> for BinMatrix {
+        fn from(array: ArrayView2<'a, bool>) -> BinMatrix {
+            let (nrows, ncols) = array.dim();
+            let mut matrix = BinMatrix::zero(nrows, ncols);
+            let mut words = vec![0; ncols.div_ceil(64)];
+            for (i, row) in array.rows().into_iter().enumerate() {
+                pack_row(row, &mut words, |bit| bit);
+                matrix.write_row_words(i, &words);
+            }
+            matrix
+        }
+    }
+
+    impl<'a> From<&'a Array2<bool>> for BinMatrix {
+        fn from(array: &'a Array2<bool>) -> BinMatrix {
+            BinMatrix::from(array.view())
+        }
+    }
+
+    impl<'a> TryFrom<ArrayView2<'a, u8>> for BinMatrix {
+        type Error = ConversionError;
+
+        /// Fails if the array contains values other than 0 and 1
+        fn try_from(array: ArrayView2<'a, u8>) -> Result<BinMatrix, ConversionError> {
+            if let Some(((row, col), &value)) = array.indexed_iter().find(|(_, &value)| value > 1) {
+                return Err(ConversionError::NotABit { row, col, value });
+            }
+            let (nrows, ncols) = array.dim();
+            let mut matrix = BinMatrix::zero(nrows, ncols);
+            let mut words = vec![0; ncols.div_ceil(64)];
+            for (i, row) in array.rows().into_iter().enumerate() {
+                pack_row(row, &mut words, |value| value == 1);
+                matrix.write_row_words(i, &words);
+            }
+            Ok(matrix)
+        }
+    }
+
+    impl<'a> TryFrom<&'a Array2<u8>> for BinMatrix {
+        type Error = ConversionError;
+
+        /// Fails if the array contains values other than 0 and 1
+        fn try_from(array: &'a Array2<u8>) -> Result<BinMatrix, ConversionError> {
+            BinMatrix::try_from(array.view())
+        }
+    }
+
+    impl<'a> From<&'a BinMatrix> for Array2<bool> {
+        fn from(matrix: &'a BinMatrix) -> Array2<bool> {
+            unpack(matrix, |bit| bit)
+        }
+    }
+
+    impl<'a> From<&'a BinMatrix> for Array2<u8> {
+        fn from(matrix: &'a BinMatrix) -> Array2<u8> {
+            unpack(matrix, u8::from)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use friendly::binary_vector::BinVector;
+        use friendly::test_matrices;
+
+        #[test]
+        fn round_trip() {
+            for m in test_matrices() {
+                let (nrows, ncols) = (m.nrows(), m.ncols());
+                let bools = Array2::<bool>::from(&m);
+                assert_eq!(bools.dim(), (nrows, ncols));
+                assert_eq!(BinMatrix::from(&bools), m);
+                let bytes = Array2::<u8>::from(&m);
+                assert_eq!(BinMatrix::try_from(&bytes), Ok(m.clone()));
+                for ((row, col), &value) in bytes.indexed_iter() {
+                    assert_eq!(value == 1, m.bit(row, col));
+                }
+            }
+        }
+
+        #[test]
+        fn views() {
+            let m: BinMatrix = "101; 011".parse().unwrap();
+            let bools = Array2::<bool>::from(&m);
+            // Transposed views have non-standard strides
+            assert_eq!(BinMatrix::from(bools.t()), m.transposed());
+            let column: Vec<bool> = bools.column(2).iter().cloned().collect();
+            assert_eq!(BinVector::from_bools(&column), m.transposed().row_ref(2));
+        }
+
+        #[test]
+        fn not_a_bit() {
+            let array = Array2::from_shape_vec((2, 2), vec![0, 1, 2, 1]).unwrap();
+            assert_eq!(
+                BinMatrix::try_from(&array),
+                Err(ConversionError::NotABit {
+                    row: 1,
+                    col: 0,
+                    value: 2
+                })
+            );
+        }
+    }
+}
+
+#[cfg(feature = "bitvec")]
+mod bitvec_conversions {
+    use bitvec::order::{BitOrder, Lsb0};
+    use bitvec::slice::BitSlice;
+    use bitvec::store::BitStore;
+    use bitvec::vec::BitVec;
+    use bitvec::view::BitView;
+
+    use friendly::binary_vector::BinVector;
+
+    impl From<BitVec<u64, Lsb0>> for BinVector {
+        /// Uses the words of the `BitVec`, which have the same layout
+        fn from(mut bits: BitVec<u64, Lsb0>) -> BinVector {
+            bits.force_align();
+            BinVector::from_words(bits.as_raw_slice(), bits.len())
+        }
+    }
+
+    impl<'a, T: BitStore, O: BitOrder> From<&'a BitSlice<T, O>> for BinVector {
+        fn from(bits: &'a BitSlice<T, O>) -> BinVector {
+            let mut words = BitVec::<u64, Lsb0>::with_capacity(bits.len());
+            words.extend_from_bitslice(bits);
+            BinVector::from(words)
+        }
+    }
+
+    impl<'a, T: BitStore, O: BitOrder> From<&'a BinVector> for BitVec<T, O> {
+        fn from(vector: &'a BinVector) -> BitVec<T, O> {
+            let mut bits = BitVec::with_capacity(vector.len());
+            bits.extend_from_bitslice(&vector.as_words().view_bits::<Lsb0>()[..vector.len()]);
+            bits
+        }
+    }
+
+    impl<T: BitStore, O: BitOrder> From<BinVector> for BitVec<T, O> {
+        fn from(vector: BinVector) -> BitVec<T, O> {
+            BitVec::from(&vector)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use bitvec::order::Msb0;
+
+        #[test]
+        fn round_trip() {
+            for &len in &[0, 1, 64, 70, 130] {
+                let v = BinVector::random(len);
+                let lsb: BitVec<u64, Lsb0> = BitVec::from(&v);
+                assert_eq!(lsb.len(), len);
+                assert!(lsb.iter().by_vals().eq(v.iter()));
+                assert_eq!(BinVector::from(lsb), v);
+
+                let msb: BitVec<u8, Msb0> = BitVec::from(v.clone());
+                assert!(msb.iter().by_vals().eq(v.iter()));
+                assert_eq!(BinVector::from(&msb[..]), v);
+            }
+        }
+
+        #[test]
+        fn unaligned() {
+            let mut bits: BitVec<u64, Lsb0> = BitVec::repeat(true, 3);
+            bits.extend_from_bitslice(0b1011u8.view_bits::<Lsb0>());
+            let tail = BinVector::from(&bits[2..]);
+            assert_eq!(
+                tail,
+                BinVector::from_bools(&[true, true, true, false, true, false, false, false, false])
+            );
+        }
+    }
+}
+
+#[cfg(feature = "bit-vec")]
+mod bit_vec_conversions {
+    use bit_vec::BitVec;
+
+    use friendly::binary_vector::BinVector;
+
+    impl<'a> From<&'a BitVec<u32>> for BinVector {
+        /// Combines pairs of `u32` blocks into words
+        fn from(bits: &'a BitVec<u32>) -> BinVector {
+            let words: Vec<u64> = bits
+                .storage()
+                .chunks(2)
+                .map(|blocks| u64::from(blocks[0]) | u64::from(*blocks.get(1).unwrap_or(&0)) << 32)
+                .collect();
+            BinVector::from_words(&words, bits.len())
+        }
+    }
+
+    impl<'a> From<&'a BitVec<u64>> for BinVector {
+        /// Uses the blocks of the `BitVec`, which have the same layout
+        fn from(bits: &'a BitVec<u64>) -> BinVector {
+            BinVector::from_words(bits.storage(), bits.len())
+        }
+    }
+
+    impl<'a> From<&'a BinVector> for BitVec<u32> {
+        fn from(vector: &'a BinVector) -> BitVec<u32> {
+            let mut bits = BitVec::from_elem(vector.len(), false);
+            // Safety: this writes exactly the blocks needed for `len()` bits,
+            // and the bits past `len()` stay zero
+            let blocks = unsafe { bits.storage_mut() };
+            for (blocks, &word) in blocks.chunks_mut(2).zip(vector.as_words()) {
+                blocks[0] = word as u32;
+                if let Some(block) = blocks.get_mut(1) {
+                    *block = (word >> 32) as u32;
+                }
+            }
+            bits
+        }
+    }
+
+    impl<'a> From<&'a BinVector> for BitVec<u64> {
+        fn from(vector: &'a BinVector) -> BitVec<u64> {
+            let mut bits = BitVec::default();
+            // Safety: the words hold exactly `len()` bits and the bits past `len()` are zero
+            unsafe {
+                bits.storage_mut().extend_from_slice(vector.as_words());
+                bits.set_len(vector.len());
+            }
+            bits
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            for &len in &[0, 1, 32, 33, 64, 70, 130] {
+                let v = BinVector::random(len);
+                let small = BitVec::<u32>::from(&v);
+                assert_eq!(small.len(), len);
+                assert!(small.iter().eq(v.iter()));
+                assert_eq!(BinVector::from(&small), v);
+
+                let large = BitVec::<u64>::from(&v);
+                assert!(large.iter().eq(v.iter()));
+                assert_eq!(BinVector::from(&large), v);
+            }
+        }
+
+        #[test]
+        fn padding() {
+            let mut bits = BitVec::from_elem(40, true);
+            bits.truncate(35);
+            let v = BinVector::from(&bits);
+            assert_eq!(v.len(), 35);
+            assert_eq!(v.count_ones(), 35);
+            assert_eq!(BitVec::<u32>::from(&v), bits);
+        }
+    }
+}
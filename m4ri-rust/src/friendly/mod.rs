@@ -1,6 +1,32 @@
 //! Friendly interfaces on the M4RI constructs
 
+mod arbitrary;
 mod binary_matrix;
 mod binary_vector;
+mod format;
+mod gf2_matrix;
+mod image;
+mod interop;
+mod sparse;
+mod storage;
+#[cfg(feature = "proptest")]
+pub use self::arbitrary::strategy;
 pub use self::binary_matrix::*;
 pub use self::binary_vector::*;
+pub use self::format::*;
+pub use self::gf2_matrix::*;
+pub use self::image::*;
+pub use self::sparse::*;
+#[cfg(feature = "mmap")]
+pub use self::storage::MappedMatrix;
+
+/// Random matrices with shapes around word boundaries, including matrices without rows or columns
+///
+/// For the round-trip tests of the file formats and conversions.
+#[cfg(test)]
+pub(crate) fn test_matrices() -> Vec<BinMatrix> {
+    [(1, 1), (10, 70), (3, 64), (65, 3), (64, 128), (0, 5), (5, 0)]
+        .iter()
+        .map(|&(nrows, ncols)| BinMatrix::random(nrows, ncols))
+        .collect()
+}
@@ -0,0 +1,296 @@
+//! Text formats that list the matrix entries by their coordinates
+//!
+//! Both the Matrix Market and the triplet readers and writers work line by
+//! line, so only the matrix itself has to fit in memory.
+use std::io;
+use std::io::{BufRead, Write};
+
+use friendly::binary_matrix::BinMatrix;
+use friendly::image::invalid_data;
+
+/// Which entries the coordinate writers emit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Entries {
+    /// Only the positions of the set bits
+    NonZero,
+    /// Every position, followed by its value `0` or `1`
+    All,
+}
+
+/// Parses one `row col [value]` entry
+///
+/// `base` is the index of the first row and column, `line` is only used for error messages.
+fn parse_entry(
+    text: &str,
+    line: usize,
+    base: usize,
+    dimensions: (usize, usize),
+) -> io::Result<(usize, usize, Option<bool>)> {
+    let error = |what: &str| invalid_data(format!("line {}: {}", line, what));
+    let mut fields = text.split_whitespace();
+    let mut index = |limit: usize| -> io::Result<usize> {
+        let index = fields
+            .next()
+            .ok_or_else(|| error("expected a row and a column"))?
+            .parse::<usize>()
+            .map_err(|_| error("invalid index"))?;
+        match index.checked_sub(base) {
+            Some(index) if index < limit => Ok(index),
+            _ => Err(error("index out of bounds")),
+        }
+    };
+    let row = index(dimensions.0)?;
+    let col = index(dimensions.1)?;
+    let value = match fields.next() {
+        // Values other than 0 and 1 are reduced mod 2
+        Some(value) => Some(value.parse::<i64>().map_err(|_| error("invalid value"))? % 2 != 0),
+        None => None,
+    };
+    if fields.next().is_some() {
+        return Err(error("too many fields"));
+    }
+    Ok((row, col, value))
+}
+
+impl BinMatrix {
+    /// Iterate over the positions of the set bits in row-major order
+    fn nonzero_positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.rows()
+            .enumerate()
+            .flat_map(|(i, row)| row.iter_set_bits().map(move |j| (i, j)))
+    }
+
+    /// Write the matrix in the Matrix Market coordinate format
+    ///
+    /// With `Entries::NonZero` this writes a `coordinate pattern general` file,
+    /// with `Entries::All` a `coordinate integer general` file with all positions.
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::{BinMatrix, Entries};
+    /// let m = BinMatrix::identity(2);
+    /// let mut mtx = Vec::new();
+    /// m.write_matrix_market(&mut mtx, Entries::NonZero).unwrap();
+    /// assert_eq!(mtx, b"%%MatrixMarket matrix coordinate pattern general\n2 2 2\n1 1\n2 2\n");
+    /// assert_eq!(BinMatrix::read_matrix_market(&mtx[..]).unwrap(), m);
+    /// ```
+    pub fn write_matrix_market<W: Write>(&self, writer: W, entries: Entries) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(writer);
+        match entries {
+            Entries::NonZero => {
+                let count: usize = self.rows().map(|row| row.count_ones() as usize).sum();
+                writeln!(writer, "%%MatrixMarket matrix coordinate pattern general")?;
+                writeln!(writer, "{} {} {}", self.nrows(), self.ncols(), count)?;
+                for (i, j) in self.nonzero_positions() {
+                    writeln!(writer, "{} {}", i + 1, j + 1)?;
+                }
+            }
+            Entries::All => {
+                writeln!(writer, "%%MatrixMarket matrix coordinate integer general")?;
+                let count = self.nrows() * self.ncols();
+                writeln!(writer, "{} {} {}", self.nrows(), self.ncols(), count)?;
+                for (i, row) in self.rows().enumerate() {
+                    for j in 0..row.len() {
+                        writeln!(
+                            writer,
+                            "{} {} {}",
+                            i + 1,
+                            j + 1,
+                            (row.get(j) == Some(true)) as u8
+                        )?;
+                    }
+                }
+            }
+        }
+        writer.flush()
+    }
+
+    /// Read a matrix in the Matrix Market coordinate format
+    ///
+    /// Supports `general` matrices with the `pattern` and `integer` fields.
+    /// Integer entries are reduced mod 2.
+    pub fn read_matrix_market<R: BufRead>(reader: R) -> io::Result<BinMatrix> {
+        let mut lines = reader.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        let header = match lines.next() {
+            Some((_, line)) => line?,
+            None => return Err(invalid_data("empty Matrix Market file")),
+        };
+        let header: Vec<String> = header
+            .split_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect();
+        if header.len() != 5 || header[0] != "%%matrixmarket" || header[1] != "matrix" {
+            return Err(invalid_data("not a Matrix Market file"));
+        }
+        let pattern = match (&header[2][..], &header[3][..], &header[4][..]) {
+            ("coordinate", "pattern", "general") => true,
+            ("coordinate", "integer", "general") => false,
+            _ => {
+                return Err(invalid_data(format!(
+                    "unsupported Matrix Market type: {} {} {}",
+                    header[2], header[3], header[4]
+                )))
+            }
+        };
+
+        let mut matrix = None;
+        let mut remaining = 0;
+        for (number, line) in lines {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('%') {
+                continue;
+            }
+            let matrix = match matrix {
+                Some(ref mut matrix) => matrix,
+                None => {
+                    let size: Vec<usize> = line
+                        .split_whitespace()
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid_data(format!("line {}: invalid size", number)))?;
+                    if size.len() != 3 {
+                        return Err(invalid_data(format!("line {}: invalid size", number)));
+                    }
+                    remaining = size[2];
+                    matrix = Some(BinMatrix::try_zero(size[0], size[1]).map_err(invalid_data)?);
+                    continue;
+                }
+            };
+            if remaining == 0 {
+                return Err(invalid_data(format!("line {}: too many entries", number)));
+            }
+            remaining -= 1;
+            let dimensions = (matrix.nrows(), matrix.ncols());
+            match parse_entry(line, number, 1, dimensions)? {
+                (i, j, None) if pattern => matrix.set_bit(i, j, true),
+                (i, j, Some(bit)) if !pattern => matrix.set_bit(i, j, bit),
+                _ => {
+                    return Err(invalid_data(format!(
+                        "line {}: wrong number of fields",
+                        number
+                    )))
+                }
+            }
+        }
+        match matrix {
+            Some(_) if remaining > 0 => Err(invalid_data("Matrix Market file is too short")),
+            Some(matrix) => Ok(matrix),
+            None => Err(invalid_data("Matrix Market file has no size line")),
+        }
+    }
+
+    /// Write the matrix as `row col` lines with zero-based indices
+    ///
+    /// With `Entries::All` every position is written as `row col value`.
+    ///
+    /// ```
+    /// # use m4ri_rust::friendly::{BinMatrix, Entries};
+    /// let m = BinMatrix::identity(2);
+    /// let mut triplets = Vec::new();
+    /// m.write_triplets(&mut triplets, Entries::NonZero).unwrap();
+    /// assert_eq!(triplets, b"0 0\n1 1\n");
+    /// assert_eq!(BinMatrix::read_triplets(&triplets[..], 2, 2).unwrap(), m);
+    /// ```
+    pub fn write_triplets<W: Write>(&self, writer: W, entries: Entries) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(writer);
+        match entries {
+            Entries::NonZero => {
+                for (i, j) in self.nonzero_positions() {
+                    writeln!(writer, "{} {}", i, j)?;
+                }
+            }
+            Entries::All => {
+                for (i, row) in self.rows().enumerate() {
+                    for j in 0..row.len() {
+                        writeln!(writer, "{} {} {}", i, j, (row.get(j) == Some(true)) as u8)?;
+                    }
+                }
+            }
+        }
+        writer.flush()
+    }
+
+    /// Read a `nrows` by `ncols` matrix from `row col` or `row col value` lines
+    ///
+    /// The indices are zero-based and values are reduced mod 2. Empty lines
+    /// and lines starting with `#` or `%` are skipped.
+    pub fn read_triplets<R: BufRead>(
+        reader: R,
+        nrows: usize,
+        ncols: usize,
+    ) -> io::Result<BinMatrix> {
+        let mut matrix = BinMatrix::try_zero(nrows, ncols).map_err(invalid_data)?;
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+                continue;
+            }
+            let (row, col, value) = parse_entry(line, i + 1, 0, (nrows, ncols))?;
+            matrix.set_bit(row, col, value.unwrap_or(true));
+        }
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use friendly::test_matrices;
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn round_trip() {
+        for m in test_matrices() {
+            let (nrows, ncols) = (m.nrows(), m.ncols());
+            for &entries in &[Entries::NonZero, Entries::All] {
+                let mut mtx = Vec::new();
+                m.write_matrix_market(&mut mtx, entries).unwrap();
+                assert_eq!(BinMatrix::read_matrix_market(&mtx[..]).unwrap(), m);
+
+                let mut triplets = Vec::new();
+                m.write_triplets(&mut triplets, entries).unwrap();
+                assert_eq!(
+                    BinMatrix::read_triplets(&triplets[..], nrows, ncols).unwrap(),
+                    m
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn read_matrix_market() {
+        let mtx = "%%MatrixMarket matrix coordinate integer general\n\
+                   % a comment\n\
+                   2 3 3\n\
+                   1 3 1\n\
+                   2 1 3\n\
+                   2 2 -2\n";
+        let m = BinMatrix::read_matrix_market(mtx.as_bytes()).unwrap();
+        assert_eq!(m, "001; 100".parse().unwrap());
+
+        let wrong_type = "%%MatrixMarket matrix array integer general\n1 1\n1\n";
+        assert!(BinMatrix::read_matrix_market(wrong_type.as_bytes()).is_err());
+        let out_of_bounds = "%%MatrixMarket matrix coordinate pattern general\n1 1 1\n1 2\n";
+        assert!(BinMatrix::read_matrix_market(out_of_bounds.as_bytes()).is_err());
+        let too_short = "%%MatrixMarket matrix coordinate pattern general\n2 2 2\n1 1\n";
+        assert!(BinMatrix::read_matrix_market(too_short.as_bytes()).is_err());
+        let too_long = "%%MatrixMarket matrix coordinate pattern general\n2 2 1\n1 1\n2 2\n";
+        assert!(BinMatrix::read_matrix_market(too_long.as_bytes()).is_err());
+        let value = "%%MatrixMarket matrix coordinate pattern general\n2 2 1\n1 1 1\n";
+        assert!(BinMatrix::read_matrix_market(value.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn read_triplets() {
+        let triplets = "# row col\n0 2\n\n1 0 1\n1 1 0\n";
+        let m = BinMatrix::read_triplets(triplets.as_bytes(), 2, 3).unwrap();
+        assert_eq!(m, "001; 100".parse().unwrap());
+
+        assert!(BinMatrix::read_triplets("2 0\n".as_bytes(), 2, 3).is_err());
+        assert!(BinMatrix::read_triplets("0\n".as_bytes(), 2, 3).is_err());
+        assert!(BinMatrix::read_triplets("0 1 1 1\n".as_bytes(), 2, 3).is_err());
+        assert!(BinMatrix::read_triplets("0 x\n".as_bytes(), 2, 3).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! Compact binary file format for matrices
+//!
+//! The format is described at `BinMatrix::write_binary`.
+use std::convert::TryInto;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use friendly::binary_matrix::BinMatrix;
+use friendly::image::invalid_data;
+
+const MAGIC: [u8; 8] = *b"M4RIMAT\0";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 40;
+
+/// The fields of the header that describe the data
+struct Header {
+    nrows: usize,
+    ncols: usize,
+    checksum: u32,
+}
+
+impl Header {
+    fn width(&self) -> usize {
+        self.ncols.div_ceil(64)
+    }
+
+    fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0; HEADER_LEN];
+        bytes[0..8].copy_from_slice(&MAGIC);
+        bytes[8..12].copy_from_slice(&VERSION.to_le_bytes());
+        bytes[16..24].copy_from_slice(&(self.nrows as u64).to_le_bytes());
+        bytes[24..32].copy_from_slice(&(self.ncols as u64).to_le_bytes());
+        bytes[32..36].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; HEADER_LEN]) -> io::Result<Header> {
+        if bytes[0..8] != MAGIC {
+            return Err(invalid_data("not a matrix file"));
+        }
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+        let version = u32_at(8);
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "unsupported matrix file version {}",
+                version
+            )));
+        }
+        let to_usize = |n: u64| -> io::Result<usize> {
+            n.try_into()
+                .map_err(|_| invalid_data("matrix is too large"))
+        };
+        Ok(Header {
+            nrows: to_usize(u64_at(16))?,
+            ncols: to_usize(u64_at(24))?,
+            checksum: u32_at(32),
+        })
+    }
+
+    /// Number of bytes of the data after the header
+    fn data_len(&self) -> io::Result<usize> {
+        self.nrows
+            .checked_mul(self.width())
+            .and_then(|words| words.checked_mul(8))
+            .ok_or_else(|| invalid_data("matrix is too large"))
+    }
+}
+
+impl BinMatrix {
+    fn checksum(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        for word in self.rows().flat_map(|row| row.masked_words()) {
+            hasher.update(&word.to_le_bytes());
+        }
+        hasher.finalize()
+    }
+
+    /// Write the matrix in a compact binary format
+    ///
+    /// A file starts with a 40-byte header, all integers little-endian:
+    ///
+    /// | offset | size | contents                                   |
+    /// |--------|------|--------------------------------------------|
+    /// | 0      | 8    | magic bytes `M4RIMAT\0`                    |
+    /// | 8      | 4    | format version, currently 1                |
+    /// | 12     | 4    | reserved, zero                             |
+    /// | 16     | 8    | number of rows                             |
+    /// | 24     | 8    | number of columns                          |
+    /// | 32     | 4    | CRC-32 of the data that follows the header |
+    /// | 36     | 4    | reserved, zero                             |
+    ///
+    /// followed by the rows as `ncols().div_ceil(64)` 64-bit words each, least
+    /// significant bit first, with the bits past the last column set to zero.
+    /// This is the layout of `row_words`, so the data can be used in place
+    /// through a memory map with the `mmap` feature.
+    pub fn write_binary<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(writer);
+        let header = Header {
+            nrows: self.nrows(),
+            ncols: self.ncols(),
+            checksum: self.checksum(),
+        };
+        writer.write_all(&header.to_bytes())?;
+        for word in self.rows().flat_map(|row| row.masked_words()) {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        writer.flush()
+    }
+
+    /// Read a matrix written by `write_binary`
+    ///
+    /// Fails with `InvalidData` if the header is invalid or the checksum doesn't match.
+    pub fn read_binary<R: Read>(reader: R) -> io::Result<BinMatrix> {
+        BinMatrix::read_binary_sized(reader, None)
+    }
+
+    /// Read a matrix, checking the header against the size of the input if it's known
+    ///
+    /// The data is read before the matrix is allocated, so a header that
+    /// claims a huge matrix fails with `UnexpectedEof` when the data runs out
+    /// instead of allocating the memory first.
+    fn read_binary_sized<R: Read>(reader: R, len: Option<u64>) -> io::Result<BinMatrix> {
+        let mut reader = io::BufReader::new(reader);
+        let mut header = [0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        let header = Header::from_bytes(&header)?;
+        let data_len = header.data_len()?;
+        if len.is_some_and(|len| len.checked_sub(HEADER_LEN as u64) != Some(data_len as u64)) {
+            return Err(invalid_data("matrix file has the wrong size"));
+        }
+
+        let mut bytes = Vec::new();
+        reader.take(data_len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != data_len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if crc32fast::hash(&bytes) != header.checksum {
+            return Err(invalid_data("checksum mismatch in matrix file"));
+        }
+        let mut matrix = BinMatrix::try_zero(header.nrows, header.ncols).map_err(invalid_data)?;
+        let mut words = vec![0; header.width()];
+        if !words.is_empty() {
+            for (row, row_bytes) in bytes.chunks(words.len() * 8).enumerate() {
+                for (word, chunk) in words.iter_mut().zip(row_bytes.chunks(8)) {
+                    *word = u64::from_le_bytes(chunk.try_into().unwrap());
+                }
+                matrix.copy_words_into_row(row, &words);
+            }
+        }
+        Ok(matrix)
+    }
+
+    /// Save the matrix to a file in the format of `write_binary`
+    ///
+    /// ```no_run
+    /// # use m4ri_rust::friendly::BinMatrix;
+    /// let m = BinMatrix::random(1000, 1000);
+    /// m.save("generator.m4ri").unwrap();
+    /// assert_eq!(BinMatrix::load("generator.m4ri").unwrap(), m);
+    /// ```
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_binary(File::create(path)?)
+    }
+
+    /// Load a matrix saved by `save`
+    ///
+    /// Fails with `InvalidData` without allocating the matrix if the file
+    /// size doesn't match the dimensions in the header.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<BinMatrix> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        BinMatrix::read_binary_sized(file, Some(len))
+    }
+}
+
+#[cfg(feature = "mmap")]
+pub use self::mapped::MappedMatrix;
+
+#[cfg(feature = "mmap")]
+mod mapped {
+    use std::fs::File;
+    use std::io;
+    use std::path::Path;
+    use std::slice;
+
+    use memmap2::Mmap;
+
+    use super::{Header, HEADER_LEN};
+    use friendly::binary_matrix::BinMatrix;
+    use friendly::binary_vector::BinVectorRef;
+    use friendly::image::invalid_data;
+
+    /// A read-only matrix that is memory-mapped from a file written by `BinMatrix::save`
+    ///
+    /// Rows are read from the file on demand, so this works for matrices that
+    /// don't fit in memory. Opening fails on big-endian targets.
+    pub struct MappedMatrix {
+        mmap: Mmap,
+        header: Header,
+    }
+
+    impl MappedMatrix {
+        /// Map a matrix file
+        ///
+        /// This checks the header and the file size, but not the checksum,
+        /// as that would read the whole file. Use `verify` for that.
+        ///
+        /// # Safety
+        /// The file must not be modified or truncated, by this or any other
+        /// process, while the `MappedMatrix` exists. The rows borrow the mapped
+        /// memory directly, so a change shows up as data changing under a shared
+        /// reference, and truncation makes reads fault.
+        pub unsafe fn open<P: AsRef<Path>>(path: P) -> io::Result<MappedMatrix> {
+            if cfg!(target_endian = "big") {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "memory-mapped matrices need a little-endian target",
+                ));
+            }
+            let file = File::open(path)?;
+            // Safety: the file is only read, and the caller guarantees it isn't modified
+            let mmap = Mmap::map(&file)?;
+            let mut header = [0; HEADER_LEN];
+            match mmap.get(..HEADER_LEN) {
+                Some(bytes) => header.copy_from_slice(bytes),
+                None => return Err(invalid_data("matrix file is too short")),
+            }
+            let header = Header::from_bytes(&header)?;
+            if mmap.len() - HEADER_LEN != header.data_len()? {
+                return Err(invalid_data("matrix file has the wrong size"));
+            }
+            Ok(MappedMatrix { mmap, header })
+        }
+
+        /// Check the checksum of the data, reading the whole file
+        pub fn verify(&self) -> io::Result<()> {
+            if crc32fast::hash(&self.mmap[HEADER_LEN..]) == self.header.checksum {
+                Ok(())
+            } else {
+                Err(invalid_data("checksum mismatch in matrix file"))
+            }
+        }
+
+        /// Get the number of rows
+        pub fn nrows(&self) -> usize {
+            self.header.nrows
+        }
+
+        /// Get the number of columns
+        pub fn ncols(&self) -> usize {
+            self.header.ncols
+        }
+
+        fn words(&self) -> &[u64] {
+            let data = &self.mmap[HEADER_LEN..];
+            // Maps are page-aligned and the header is a multiple of 8 bytes long
+            debug_assert!((data.as_ptr() as usize).is_multiple_of(8));
+            unsafe { slice::from_raw_parts(data.as_ptr() as *const u64, data.len() / 8) }
+        }
+
+        /// Get a row without copying it
+        ///
+        /// **Panics** if the row is out of bounds
+        pub fn row_ref(&self, row: usize) -> BinVectorRef<'_> {
+            assert!(row < self.nrows(), "row {} out of bounds", row);
+            let width = self.header.width();
+            BinVectorRef::new(&self.words()[row * width..(row + 1) * width], self.ncols())
+        }
+
+        /// Iterate over the rows without copying them
+        pub fn rows(
+            &self,
+        ) -> impl DoubleEndedIterator<Item = BinVectorRef<'_>> + ExactSizeIterator {
+            (0..self.nrows()).map(move |row| self.row_ref(row))
+        }
+
+        /// Copy the rows `start..end` into a new matrix
+        ///
+        /// **Panics** if the range is out of bounds
+        pub fn row_range(&self, start: usize, end: usize) -> BinMatrix {
+            assert!(
+                start <= end && end <= self.nrows(),
+                "rows {}..{} out of bounds",
+                start,
+                end
+            );
+            let mut matrix = BinMatrix::zero(end - start, self.ncols());
+            for row in start..end {
+                matrix.copy_words_into_row(row - start, self.row_ref(row).as_words());
+            }
+            matrix
+        }
+
+        /// Copy the whole matrix into memory
+        pub fn to_matrix(&self) -> BinMatrix {
+            self.row_range(0, self.nrows())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use friendly::test_matrices;
+
+    #[test]
+    fn round_trip() {
+        for m in test_matrices() {
+            let (nrows, ncols) = (m.nrows(), m.ncols());
+            let mut data = Vec::new();
+            m.write_binary(&mut data).unwrap();
+            assert_eq!(data.len(), HEADER_LEN + nrows * ncols.div_ceil(64) * 8);
+            assert_eq!(BinMatrix::read_binary(&data[..]).unwrap(), m);
+        }
+    }
+
+    #[test]
+    fn layout() {
+        let m: BinMatrix = "101; 011".parse().unwrap();
+        let mut data = Vec::new();
+        m.write_binary(&mut data).unwrap();
+        assert_eq!(&data[..8], b"M4RIMAT\0");
+        assert_eq!(data[8], 1);
+        assert_eq!((data[16], data[24]), (2, 3));
+        assert_eq!(
+            &data[HEADER_LEN..],
+            &[5, 0, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn invalid_files() {
+        let m = BinMatrix::random(4, 100);
+        let mut data = Vec::new();
+        m.write_binary(&mut data).unwrap();
+
+        let mut corrupted = data.clone();
+        corrupted[HEADER_LEN + 3] ^= 1;
+        let error = BinMatrix::read_binary(&corrupted[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let mut version = data.clone();
+        version[8] = 2;
+        assert!(BinMatrix::read_binary(&version[..]).is_err());
+        assert!(BinMatrix::read_binary(&data[1..]).is_err());
+        let truncated = BinMatrix::read_binary(&data[..data.len() - 1]).unwrap_err();
+        assert_eq!(truncated.kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut huge = data.clone();
+        huge[23] = 0xff;
+        assert!(BinMatrix::read_binary(&huge[..]).is_err());
+        // 2^40 rows of 16 bytes, which fail when the data runs out
+        let mut large = data.clone();
+        large[21] = 1;
+        let error = BinMatrix::read_binary(&large[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("m4ri-rust-test-{}.m4ri", std::process::id()));
+        let m = BinMatrix::random(20, 130);
+        m.save(&path).unwrap();
+        assert_eq!(BinMatrix::load(&path).unwrap(), m);
+
+        let mut data = Vec::new();
+        m.write_binary(&mut data).unwrap();
+        data[21] = 1;
+        std::fs::write(&path, &data).unwrap();
+        let error = BinMatrix::load(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        m.save(&path).unwrap();
+
+        #[cfg(feature = "mmap")]
+        {
+            // Safety: the file belongs to this test and isn't changed while it's mapped
+            let mapped = unsafe { MappedMatrix::open(&path) }.unwrap();
+            mapped.verify().unwrap();
+            assert_eq!((mapped.nrows(), mapped.ncols()), (20, 130));
+            assert_eq!(mapped.row_ref(7), m.row_ref(7));
+            assert!(mapped.rows().eq(m.rows()));
+            assert_eq!(mapped.row_range(5, 8), m.get_window(5, 0, 8, 130));
+            assert_eq!(mapped.to_matrix(), m);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
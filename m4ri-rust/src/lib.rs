@@ -1,7 +1,6 @@
 #![cfg_attr(feature = "system_alloc", feature(alloc_system))]
 //! Fast binary matrix operations
 //! Interfaces the M4RI library and provides friendly abstractions
-extern crate libc;
 #[cfg_attr(test, macro_use)]
 extern crate vob;
 
@@ -17,6 +16,41 @@ extern crate serde;
 #[cfg(feature = "serde")]
 extern crate serde_json;
 
+extern crate crc32fast;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(feature = "png")]
+extern crate png;
+#[cfg(feature = "ndarray")]
+extern crate ndarray;
+#[cfg(feature = "bitvec")]
+extern crate bitvec;
+#[cfg(feature = "bit-vec")]
+extern crate bit_vec;
+#[cfg(feature = "proptest")]
+extern crate proptest;
+#[cfg(feature = "quickcheck")]
+extern crate quickcheck;
+
+#[cfg(not(any(feature = "pure-rust", feature = "m4ri-sys")))]
+compile_error!("Enable either the default `m4ri-sys` feature or the `pure-rust` feature");
+#[cfg(all(feature = "pure-rust", feature = "parallel"))]
+compile_error!("The `parallel` feature needs the C backend, it can't be combined with `pure-rust`");
+
+#[cfg(not(feature = "pure-rust"))]
 extern crate m4ri_sys as ffi;
+// Only used to cross-test the pure-Rust backend
+#[cfg(all(feature = "pure-rust", feature = "m4ri-sys"))]
+extern crate m4ri_sys;
+
+#[cfg(feature = "pure-rust")]
+pub mod pure;
+#[cfg(feature = "pure-rust")]
+use pure as ffi;
 
+pub mod codes;
+pub mod decoding;
 pub mod friendly;
+pub mod lpn;
+#[cfg(feature = "parallel")]
+pub mod parallel;
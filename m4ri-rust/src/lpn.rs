@@ -0,0 +1,614 @@
+//! Learning Parity with Noise
+//!
+//! An LPN sample for a secret `s` of length `k` is a uniformly random `a`
+//! with label `b = <a, s> + e`, where the noise `e` is 1 with probability
+//! `tau`. This module has an oracle that generates samples, the BKW
+//! reductions that trade samples for dimension, and solvers for the
+//! reduced problems.
+//!
+//! Samples are handled in batches: the vectors `a` are the rows of a
+//! `BinMatrix` and the labels are a `BinVector`.
+//!
+//! ```
+//! # extern crate m4ri_rust;
+//! # fn main() {
+//! use m4ri_rust::lpn::{bkw, LpnOracle, Reduction};
+//!
+//! let mut oracle = LpnOracle::new(24, 0.01, 7);
+//! let samples = oracle.samples(6000);
+//! // Two LF1 steps cancel the last 2 * 6 bits, leaving the first 12 bits of the secret
+//! let first = bkw(&samples, 6, 2, Reduction::Lf1);
+//! let expected: Vec<bool> = oracle.secret().iter().take(12).collect();
+//! assert_eq!(first.iter().collect::<Vec<bool>>(), expected);
+//! # }
+//! ```
+use std::collections::BTreeMap;
+use std::f64::consts::LN_2;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use friendly::{BinMatrix, BinVector, BinVectorRef};
+
+/// Largest dimension that `majority` accepts
+pub const MAX_MAJORITY_DIMENSION: usize = 30;
+
+/// Generates LPN samples for a fixed secret
+#[derive(Clone, Debug)]
+pub struct LpnOracle {
+    secret: BinVector,
+    tau: f64,
+    rng: StdRng,
+    queries: u64,
+}
+
+impl LpnOracle {
+    /// Create an oracle with a random secret of length `k`
+    ///
+    /// The secret and all samples are drawn from a `StdRng` with this seed.
+    ///
+    /// **Panics** if `tau` is not in `[0, 0.5)`
+    pub fn new(k: usize, tau: f64, seed: u64) -> LpnOracle {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let secret = random_vector(&mut rng, k);
+        LpnOracle::from_parts(secret, tau, rng)
+    }
+
+    /// Create an oracle for the given secret
+    ///
+    /// **Panics** if `tau` is not in `[0, 0.5)`
+    pub fn with_secret(secret: BinVector, tau: f64, seed: u64) -> LpnOracle {
+        LpnOracle::from_parts(secret, tau, StdRng::seed_from_u64(seed))
+    }
+
+    fn from_parts(secret: BinVector, tau: f64, rng: StdRng) -> LpnOracle {
+        assert!(
+            (0.0..0.5).contains(&tau),
+            "The noise rate needs to be in [0, 0.5), got {}",
+            tau
+        );
+        LpnOracle {
+            secret,
+            tau,
+            rng,
+            queries: 0,
+        }
+    }
+
+    /// The secret
+    pub fn secret(&self) -> &BinVector {
+        &self.secret
+    }
+
+    /// The length of the secret
+    pub fn dimension(&self) -> usize {
+        self.secret.len()
+    }
+
+    /// The noise rate
+    pub fn tau(&self) -> f64 {
+        self.tau
+    }
+
+    /// The number of samples generated so far
+    pub fn queries(&self) -> u64 {
+        self.queries
+    }
+
+    /// Generate a single sample `(a, b)`
+    pub fn sample(&mut self) -> (BinVector, bool) {
+        let a = random_vector(&mut self.rng, self.secret.len());
+        let noise = self.rng.gen_bool(self.tau);
+        self.queries += 1;
+        let b = (&a * &self.secret) ^ noise;
+        (a, b)
+    }
+
+    /// Generate a batch of `n` samples
+    pub fn samples(&mut self, n: usize) -> Samples {
+        let k = self.secret.len();
+        let rows: Vec<Vec<u64>> = (0..n)
+            .map(|_| (0..k.div_ceil(64)).map(|_| self.rng.gen()).collect())
+            .collect();
+        let a = BinMatrix::from_slices(&rows, k);
+        let mut b = &a * &self.secret;
+        for i in 0..n {
+            if self.rng.gen_bool(self.tau) {
+                let flipped = !b[i];
+                b.set(i, flipped);
+            }
+        }
+        self.queries += n as u64;
+        Samples::new(a, b)
+    }
+}
+
+fn random_vector<R: Rng + ?Sized>(rng: &mut R, len: usize) -> BinVector {
+    let words: Vec<u64> = (0..len.div_ceil(64)).map(|_| rng.gen()).collect();
+    BinVector::from_words(&words, len)
+}
+
+/// A batch of LPN samples: the rows of `a` with the labels in `b`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Samples {
+    /// The vectors `a`, one sample per row
+    pub a: BinMatrix,
+    /// The labels `b`
+    pub b: BinVector,
+}
+
+impl Samples {
+    /// Combine vectors and labels into a batch
+    ///
+    /// **Panics** if there isn't a label for every row
+    pub fn new(a: BinMatrix, b: BinVector) -> Samples {
+        assert_eq!(a.nrows(), b.len(), "Mismatched sizes");
+        Samples { a, b }
+    }
+
+    /// The number of samples
+    pub fn len(&self) -> usize {
+        self.b.len()
+    }
+
+    /// Check if there are no samples
+    pub fn is_empty(&self) -> bool {
+        self.b.is_empty()
+    }
+
+    /// The length of the vectors `a`
+    pub fn dimension(&self) -> usize {
+        self.a.ncols()
+    }
+
+    /// Count the samples whose label doesn't match `secret`
+    ///
+    /// **Panics** if the length of `secret` is not the dimension
+    pub fn errors(&self, secret: &BinVector) -> usize {
+        let mut mismatches = &self.a * secret;
+        mismatches += &self.b;
+        mismatches.count_ones() as usize
+    }
+
+    /// Group the samples by the last `block` bits of `a`, in the order of the values of those bits
+    fn classes(&self, block: usize) -> BTreeMap<u64, Vec<usize>> {
+        let dimension = self.dimension();
+        assert!(
+            block <= 64 && block <= dimension,
+            "Can't reduce {} bits of a {}-dimensional problem",
+            block,
+            dimension
+        );
+        let mut classes: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+        for (i, row) in self.a.rows().enumerate() {
+            classes
+                .entry(block_value(row, dimension - block, block))
+                .or_default()
+                .push(i);
+        }
+        classes
+    }
+
+    /// The sum of two samples, without the last `block` bits
+    fn combined(&self, first: usize, second: usize, block: usize) -> (BinVector, bool) {
+        let mut a = self.a.row_ref(first).to_vector();
+        a += &self.a.row_ref(second).to_vector();
+        a.truncate(self.dimension() - block);
+        (a, self.b[first] ^ self.b[second])
+    }
+
+    fn from_pairs(pairs: Vec<(BinVector, bool)>, dimension: usize) -> Samples {
+        let b: Vec<bool> = pairs.iter().map(|&(_, b)| b).collect();
+        let a = BinMatrix::from_rows_iter(pairs.iter().map(|(a, _)| a), dimension);
+        Samples::new(a, BinVector::from_bools(&b))
+    }
+
+    /// The LF1 reduction: cancel the last `block` bits of `a`
+    ///
+    /// In every group of samples that agree on those bits, the first sample
+    /// is added to the others and then dropped. This loses at most
+    /// `2^block` samples and squares the bias `1 - 2 tau` of the noise.
+    ///
+    /// **Panics** if `block` is larger than 64 or than the dimension
+    pub fn lf1(&self, block: usize) -> Samples {
+        let mut pairs = Vec::with_capacity(self.len());
+        for class in self.classes(block).values() {
+            pairs.extend(
+                class[1..]
+                    .iter()
+                    .map(|&i| self.combined(class[0], i, block)),
+            );
+        }
+        Samples::from_pairs(pairs, self.dimension() - block)
+    }
+
+    /// The LF2 reduction: cancel the last `block` bits of `a` using all pairs
+    ///
+    /// Every pair of samples that agree on those bits gives a new sample,
+    /// until there are `max_samples` of them. Like LF1 this squares the
+    /// bias, but with enough samples it doesn't shrink the batch.
+    ///
+    /// **Panics** if `block` is larger than 64 or than the dimension
+    pub fn lf2(&self, block: usize, max_samples: usize) -> Samples {
+        let mut pairs = Vec::with_capacity(max_samples.min(self.len()));
+        'classes: for class in self.classes(block).values() {
+            for (index, &first) in class.iter().enumerate() {
+                for &second in &class[index + 1..] {
+                    if pairs.len() == max_samples {
+                        break 'classes;
+                    }
+                    pairs.push(self.combined(first, second, block));
+                }
+            }
+        }
+        Samples::from_pairs(pairs, self.dimension() - block)
+    }
+
+    /// Transform to a problem whose secret is distributed like the noise
+    ///
+    /// The first `k` linearly independent samples `(A0, b0)` are used up:
+    /// every other sample `(a, b)` becomes `(a A0^-1, b + <a A0^-1, b0>)`,
+    /// which is a sample for the secret `A0 s + b0`. Returns `None` if the
+    /// vectors `a` don't have full rank.
+    pub fn sparse_secret(&self) -> Option<(SparseSecret, Samples)> {
+        let k = self.dimension();
+        let basis = independent_rows(&self.a)?;
+        let basis_matrix = BinMatrix::from_rows_iter(basis.iter().map(|&i| self.a.row_ref(i)), k);
+        let inverse = basis_matrix.try_inverted().ok()?;
+        let offset = BinVector::from_bools(&basis.iter().map(|&i| self.b[i]).collect::<Vec<_>>());
+
+        let mut used = vec![false; self.len()];
+        for &i in &basis {
+            used[i] = true;
+        }
+        let rest: Vec<usize> = (0..self.len()).filter(|&i| !used[i]).collect();
+        let a = BinMatrix::from_rows_iter(rest.iter().map(|&i| self.a.row_ref(i)), k);
+        let mut b = BinVector::from_bools(&rest.iter().map(|&i| self.b[i]).collect::<Vec<_>>());
+        let a = if rest.is_empty() {
+            a
+        } else {
+            let a = &a * &inverse;
+            b += &a * &offset;
+            a
+        };
+        let transform = SparseSecret {
+            basis: basis_matrix,
+            inverse,
+            offset,
+        };
+        Some((transform, Samples::new(a, b)))
+    }
+}
+
+/// The value of `len` bits of a row starting at `start`, the first bit least significant
+fn block_value(row: BinVectorRef, start: usize, len: usize) -> u64 {
+    (0..len).fold(0, |value, bit| {
+        value | (u64::from(row.get(start + bit).unwrap()) << bit)
+    })
+}
+
+/// Indices of the first rows that span the row space, if that is all of it
+fn independent_rows(matrix: &BinMatrix) -> Option<Vec<usize>> {
+    let k = matrix.ncols();
+    let mut reduced: Vec<(usize, BinVector)> = Vec::with_capacity(k);
+    let mut indices = Vec::with_capacity(k);
+    for (i, row) in matrix.rows().enumerate() {
+        if indices.len() == k {
+            break;
+        }
+        let mut row = row.to_vector();
+        for (pivot, basis_row) in &reduced {
+            if row[*pivot] {
+                row += basis_row;
+            }
+        }
+        if let Some(pivot) = row.iter_set_bits(..).next() {
+            reduced.push((pivot, row));
+            indices.push(i);
+        }
+    }
+    if indices.len() == k {
+        Some(indices)
+    } else {
+        None
+    }
+}
+
+/// The change of secret made by `Samples::sparse_secret`
+#[derive(Clone, Debug)]
+pub struct SparseSecret {
+    basis: BinMatrix,
+    inverse: BinMatrix,
+    offset: BinVector,
+}
+
+impl SparseSecret {
+    /// The secret of the transformed problem, `A0 s + b0`
+    pub fn transform_secret(&self, secret: &BinVector) -> BinVector {
+        let mut sparse = &self.basis * secret;
+        sparse += &self.offset;
+        sparse
+    }
+
+    /// The original secret `s = A0^-1 (s' + b0)` for a secret of the transformed problem
+    pub fn original_secret(&self, sparse: &BinVector) -> BinVector {
+        let mut shifted = sparse.clone();
+        shifted += &self.offset;
+        &self.inverse * &shifted
+    }
+}
+
+/// Find the secret by solving `k` random samples that are assumed to be noise-free
+///
+/// Every candidate is checked against all samples: it is accepted if fewer
+/// than a fraction `(tau + 1/2) / 2` of them mismatch. Returns `None` if no
+/// candidate passed within `max_iterations` attempts.
+pub fn gauss<R: Rng + ?Sized>(
+    samples: &Samples,
+    tau: f64,
+    max_iterations: u64,
+    rng: &mut R,
+) -> Option<BinVector> {
+    let k = samples.dimension();
+    if samples.len() < k {
+        return None;
+    }
+    let threshold = samples.len() as f64 * (tau + 0.5) / 2.0;
+    for _ in 0..max_iterations {
+        let chosen = rand::seq::index::sample(rng, samples.len(), k);
+        let a = BinMatrix::from_rows_iter(chosen.iter().map(|i| samples.a.row_ref(i)), k);
+        let inverse = match a.try_inverted() {
+            Ok(inverse) => inverse,
+            Err(_) => continue,
+        };
+        let b = BinVector::from_bools(&chosen.iter().map(|i| samples.b[i]).collect::<Vec<_>>());
+        let candidate = &inverse * &b;
+        if (samples.errors(&candidate) as f64) < threshold {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Find the secret that agrees with the most samples
+///
+/// This takes the majority vote over all `2^k` candidates at once with a
+/// Walsh–Hadamard transform, in `O(n + k 2^k)` time.
+///
+/// **Panics** if the dimension is larger than `MAX_MAJORITY_DIMENSION`
+pub fn majority(samples: &Samples) -> BinVector {
+    let k = samples.dimension();
+    assert!(
+        k <= MAX_MAJORITY_DIMENSION,
+        "Majority voting over a {}-dimensional secret is too expensive",
+        k
+    );
+    let mut votes = vec![0i64; 1 << k];
+    for (i, row) in samples.a.rows().enumerate() {
+        let value = row.masked_words().next().unwrap_or(0) as usize;
+        votes[value] += if samples.b[i] { -1 } else { 1 };
+    }
+    let mut half = 1;
+    while half < votes.len() {
+        for block in votes.chunks_mut(2 * half) {
+            let (low, high) = block.split_at_mut(half);
+            for (x, y) in low.iter_mut().zip(high.iter_mut()) {
+                let (sum, difference) = (*x + *y, *x - *y);
+                *x = sum;
+                *y = difference;
+            }
+        }
+        half *= 2;
+    }
+    let best = (0..votes.len()).max_by_key(|&s| votes[s]).unwrap();
+    BinVector::from_words(&[best as u64], k)
+}
+
+/// The BKW reductions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    /// `Samples::lf1`
+    Lf1,
+    /// `Samples::lf2`, keeping the number of samples
+    Lf2,
+}
+
+/// Recover the first bits of the secret with the BKW algorithm
+///
+/// Cancels `reductions` blocks of `block` bits from the end of `a` and then
+/// takes the majority vote on the remaining `k - reductions * block` bits,
+/// which are returned.
+///
+/// **Panics** if more bits are cancelled than there are, or if more than
+/// `MAX_MAJORITY_DIMENSION` bits remain
+pub fn bkw(samples: &Samples, block: usize, reductions: usize, reduction: Reduction) -> BinVector {
+    let mut reduced = samples.clone();
+    for _ in 0..reductions {
+        reduced = match reduction {
+            Reduction::Lf1 => reduced.lf1(block),
+            Reduction::Lf2 => reduced.lf2(block, samples.len()),
+        };
+    }
+    majority(&reduced)
+}
+
+/// Estimated cost of an attack
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complexity {
+    /// Number of samples needed
+    pub samples: f64,
+    /// Number of bit operations
+    pub time: f64,
+}
+
+impl Complexity {
+    /// Estimate the cost of `bkw` on a `k`-dimensional problem with noise rate `tau`
+    ///
+    /// Follows Levieil and Fouque: the final majority vote over the remaining
+    /// `k'` bits needs `8 k' ln(2) / delta^2` samples for the final bias
+    /// `delta = (1 - 2 tau)^(2^reductions)`. LF1 loses up to `2^block` samples
+    /// per reduction, LF2 needs `3 * 2^block` samples to keep its batch size.
+    ///
+    /// **Panics** if more bits are cancelled than there are
+    pub fn bkw(
+        k: usize,
+        tau: f64,
+        block: usize,
+        reductions: usize,
+        reduction: Reduction,
+    ) -> Complexity {
+        assert!(
+            block * reductions <= k,
+            "Can't cancel more bits than there are"
+        );
+        let remaining = (k - block * reductions) as f64;
+        let bias = (1.0 - 2.0 * tau).powf(2f64.powi(reductions as i32));
+        let final_samples = (8.0 * remaining.max(1.0) * LN_2 / (bias * bias)).ceil();
+        let class_count = 2f64.powi(block as i32);
+        let samples = match reduction {
+            Reduction::Lf1 => final_samples + reductions as f64 * class_count,
+            Reduction::Lf2 => final_samples.max(3.0 * class_count),
+        };
+        Complexity {
+            samples,
+            time: reductions as f64 * k as f64 * samples
+                + final_samples * remaining
+                + remaining * 2f64.powf(remaining),
+        }
+    }
+
+    /// Estimate the cost of `gauss` on a `k`-dimensional problem with noise rate `tau`
+    ///
+    /// An attempt succeeds if the `k` chosen samples are noise-free, which
+    /// happens with probability `(1 - tau)^k`. Every attempt costs an
+    /// inversion and a check against `8 k ln(2) / (1 - 2 tau)^2` samples.
+    pub fn gauss(k: usize, tau: f64) -> Complexity {
+        let k = k as f64;
+        let bias = 1.0 - 2.0 * tau;
+        let test_samples = (8.0 * k.max(1.0) * LN_2 / (bias * bias)).ceil();
+        let attempts = (1.0 - tau).powf(-k);
+        Complexity {
+            samples: k + test_samples,
+            time: attempts * (k * k * k + k * test_samples),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn class_sizes(samples: &Samples, block: usize) -> Vec<usize> {
+        samples.classes(block).values().map(Vec::len).collect()
+    }
+
+    /// The first `len` bits of `vector`
+    fn prefix(vector: &BinVector, len: usize) -> BinVector {
+        let mut prefix = vector.clone();
+        prefix.truncate(len);
+        prefix
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn oracle() {
+        let mut oracle = LpnOracle::new(70, 0.125, 1);
+        assert_eq!(oracle.dimension(), 70);
+        let (a, b) = oracle.sample();
+        assert_eq!(a.len(), 70);
+        let samples = oracle.samples(4000);
+        assert_eq!(oracle.queries(), 4001);
+        assert_eq!((samples.len(), samples.dimension()), (4000, 70));
+        let errors = samples.errors(oracle.secret());
+        assert!(400 < errors && errors < 600, "{} errors", errors);
+
+        let mut noiseless = LpnOracle::with_secret(oracle.secret().clone(), 0.0, 2);
+        assert_eq!(noiseless.samples(100).errors(oracle.secret()), 0);
+        let (a2, b2) = noiseless.sample();
+        assert_eq!(&a2 * oracle.secret(), b2);
+
+        let mut again = LpnOracle::new(70, 0.125, 1);
+        assert_eq!(again.sample(), (a, b));
+    }
+
+    #[test]
+    #[should_panic(expected = "noise rate")]
+    fn invalid_noise() {
+        LpnOracle::new(10, 0.5, 0);
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn reductions() {
+        let mut oracle = LpnOracle::new(20, 0.0, 3);
+        let samples = oracle.samples(500);
+        let sizes = class_sizes(&samples, 4);
+        assert!(sizes.len() <= 16);
+
+        let lf1 = samples.lf1(4);
+        assert_eq!(lf1.dimension(), 16);
+        assert_eq!(lf1.len(), 500 - sizes.len());
+        assert_eq!(lf1.errors(&prefix(oracle.secret(), 16)), 0);
+
+        let lf2 = samples.lf2(4, 500);
+        assert_eq!((lf2.len(), lf2.dimension()), (500, 16));
+        assert_eq!(lf2.errors(&prefix(oracle.secret(), 16)), 0);
+        let all_pairs: usize = sizes.iter().map(|&n| n * (n - 1) / 2).sum();
+        assert_eq!(samples.lf2(4, usize::MAX).len(), all_pairs);
+    }
+
+    #[test]
+    fn sparse_secret() {
+        let mut oracle = LpnOracle::new(30, 0.05, 4);
+        let samples = oracle.samples(300);
+        let (transform, transformed) = samples.sparse_secret().unwrap();
+        assert_eq!(transformed.len(), 270);
+        let sparse = transform.transform_secret(oracle.secret());
+        assert_eq!(&transform.original_secret(&sparse), oracle.secret());
+        assert!(sparse.count_ones() < 10);
+        // The remaining samples keep their noise
+        assert!(transformed.errors(&sparse) <= samples.errors(oracle.secret()));
+
+        let low_rank = Samples::new(BinMatrix::zero(50, 10), BinVector::from_elem(50, false));
+        assert!(low_rank.sparse_secret().is_none());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn solvers() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut oracle = LpnOracle::new(24, 0.05, 5);
+        let samples = oracle.samples(500);
+        assert_eq!(
+            gauss(&samples, 0.05, 1000, &mut rng).as_ref(),
+            Some(oracle.secret())
+        );
+        assert_eq!(gauss(&samples, 0.05, 0, &mut rng), None);
+
+        let mut small = LpnOracle::new(12, 0.2, 6);
+        assert_eq!(&majority(&small.samples(2000)), small.secret());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn bkw_recovers_prefix() {
+        let mut oracle = LpnOracle::new(20, 0.02, 8);
+        let samples = oracle.samples(3000);
+        let expected = prefix(oracle.secret(), 12);
+        assert_eq!(bkw(&samples, 4, 2, Reduction::Lf1), expected);
+        assert_eq!(bkw(&samples, 4, 2, Reduction::Lf2), expected);
+    }
+
+    #[test]
+    fn complexity() {
+        let lf1 = Complexity::bkw(32, 0.125, 8, 2, Reduction::Lf1);
+        let lf2 = Complexity::bkw(32, 0.125, 8, 2, Reduction::Lf2);
+        assert!(lf1.samples > 2.0 * 256.0);
+        assert!(lf2.samples <= lf1.samples);
+        let deeper = Complexity::bkw(32, 0.125, 8, 3, Reduction::Lf1);
+        assert!(deeper.samples > lf1.samples);
+        let noiseless = Complexity::gauss(32, 0.0);
+        assert_eq!(noiseless.samples, 32.0 + (8.0 * 32.0 * LN_2).ceil());
+        assert!(Complexity::gauss(32, 0.125).time > noiseless.time);
+    }
+}
@@ -0,0 +1,96 @@
+//! Control over the multicore code paths of M4RI
+//!
+//! With the `parallel` feature, M4RI is built with OpenMP and large
+//! operations on `BinMatrix` use its parallel implementations:
+//!
+//! * multiplication uses `mzd_mul_mp`,
+//! * echelonization uses the PLUQ-based `mzd_echelonize_pluq`,
+//!   which spends most of its time in (parallel) block multiplications.
+//!
+//! An operation counts as large if all dimensions involved are at least
+//! the threshold set with `set_threshold`.
+use ffi::{omp_get_max_threads, omp_set_num_threads};
+#[cfg(test)]
+use std::cell::Cell;
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Default minimal dimension for using the parallel code
+pub const DEFAULT_THRESHOLD: usize = 1024;
+
+static THRESHOLD: AtomicUsize = AtomicUsize::new(DEFAULT_THRESHOLD);
+
+#[cfg(test)]
+thread_local! {
+    /// Threshold for the current test, so tests don't change it for each other
+    static TEST_THRESHOLD: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Set the number of threads used by M4RI for operations started on the calling thread
+///
+/// OpenMP keeps the thread count per thread: this only applies to
+/// operations on the thread that calls it, including any other OpenMP code
+/// running there. Operations on other threads keep using their own setting,
+/// which starts out as `OMP_NUM_THREADS` or the number of cores.
+///
+/// **Panics** if `num_threads` is zero
+pub fn set_num_threads(num_threads: usize) {
+    assert!(num_threads > 0, "Need at least one thread");
+    unsafe { omp_set_num_threads(num_threads as c_int) }
+}
+
+/// Get the number of threads used by M4RI for operations on the calling thread
+pub fn num_threads() -> usize {
+    unsafe { omp_get_max_threads() as usize }
+}
+
+/// Set the minimal dimension of matrices for which the parallel code is used
+pub fn set_threshold(threshold: usize) {
+    THRESHOLD.store(threshold, Ordering::Relaxed);
+}
+
+/// Get the minimal dimension of matrices for which the parallel code is used
+pub fn threshold() -> usize {
+    THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// Check if an operation on these dimensions should use the parallel code
+#[inline]
+pub(crate) fn use_parallel(dimensions: &[usize]) -> bool {
+    #[cfg(test)]
+    let threshold = TEST_THRESHOLD.with(Cell::get).unwrap_or_else(threshold);
+    #[cfg(not(test))]
+    let threshold = threshold();
+    dimensions.iter().all(|&dim| dim >= threshold)
+}
+
+/// Run `f` with the threshold set to `threshold` on this thread only
+#[cfg(test)]
+pub(crate) fn with_threshold<T, F: FnOnce() -> T>(threshold: usize, f: F) -> T {
+    let previous = TEST_THRESHOLD.with(|cell| cell.replace(Some(threshold)));
+    let result = f();
+    TEST_THRESHOLD.with(|cell| cell.set(previous));
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn threads() {
+        set_num_threads(2);
+        assert_eq!(num_threads(), 2);
+    }
+
+    #[test]
+    fn threshold_dimensions() {
+        assert!(use_parallel(&[usize::MAX, usize::MAX]));
+        assert!(!use_parallel(&[0, usize::MAX]));
+        with_threshold(10, || {
+            assert!(use_parallel(&[10, 20]));
+            assert!(!use_parallel(&[9, 20]));
+        });
+        assert_eq!(TEST_THRESHOLD.with(Cell::get), None);
+    }
+}
@@ -0,0 +1,890 @@
+//! Pure-Rust implementation of the parts of M4RI used by this crate
+//!
+//! Selected with the `pure-rust` feature, for targets without a C toolchain
+//! and for running the tests under Miri. It mirrors the `m4ri-sys` API, so
+//! `friendly` works unchanged on top of it: matrices are handed out as raw
+//! pointers, and every row is stored least significant bit first in `u64`
+//! words, with the bits past the last column set to zero, just like M4RI does.
+//!
+//! The algorithms are straightforward: Gaussian elimination for echelonization,
+//! inversion and solving, and the method of the four russians for multiplication.
+//! `mzd_mul`, which is Strassen multiplication in M4RI, uses M4RM as well.
+//! The C library is much faster for large matrices.
+//!
+//! # Safety
+//!
+//! All functions expect pointers to matrices created by `mzd_init` or one of
+//! the functions that allocate a result, and which have not been freed.
+#![allow(clippy::missing_safety_doc)]
+use rand::prelude::*;
+use std::cmp;
+use std::os::raw::{c_int, c_uint};
+use std::ptr;
+use std::slice;
+
+/// Row and column indices, matches `m4ri_sys::Rci`
+pub type Rci = c_int;
+/// Word indices, matches `m4ri_sys::Wi`
+pub type Wi = c_int;
+/// The type the bits are packed in
+pub type Word = u64;
+/// A single bit, matches `m4ri_sys::BIT`
+pub type BIT = c_int;
+
+/// The number of bits in a word
+const RADIX: usize = 64;
+
+/// Default table size for M4RM multiplication
+const DEFAULT_M4RM_K: usize = 8;
+
+/// Represents a dense matrix over GF(2)
+///
+/// The public fields have the same meaning as their counterparts in `m4ri_sys::Mzd`.
+/// Rows are stored contiguously without padding words, so the row stride is `width`.
+pub struct Mzd {
+    /// Number of rows
+    pub nrows: Rci,
+    /// Number of columns
+    pub ncols: Rci,
+    /// Number of words with valid bits: ``width = ceil(ncols / 64)``
+    pub width: Wi,
+    /// Mask for valid bits in the word with the highest index (width - 1)
+    pub high_bitmask: Word,
+    /// Address of first word in each row, so the first word of `row [i]` is in `m->rows[i]`
+    pub rows: *const *mut Word,
+    row_pointers: Vec<*mut Word>,
+    data: *mut Word,
+    len: usize,
+}
+
+impl Drop for Mzd {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                self.data, self.len,
+            )));
+        }
+    }
+}
+
+#[inline]
+unsafe fn nrows(m: *const Mzd) -> usize {
+    (*m).nrows as usize
+}
+
+#[inline]
+unsafe fn ncols(m: *const Mzd) -> usize {
+    (*m).ncols as usize
+}
+
+#[inline]
+unsafe fn width(m: *const Mzd) -> usize {
+    (*m).width as usize
+}
+
+#[inline]
+unsafe fn row_ptr(m: *const Mzd, row: usize) -> *mut Word {
+    debug_assert!(row < nrows(m));
+    *(*m).rows.add(row)
+}
+
+/// The words of a row
+///
+/// The caller needs to make sure the row is not mutated while the slice lives.
+#[inline]
+unsafe fn row<'a>(m: *const Mzd, row: usize) -> &'a [Word] {
+    slice::from_raw_parts(row_ptr(m, row), width(m))
+}
+
+/// The words of a row, mutably
+///
+/// The caller needs to make sure no other reference to the row exists while the slice lives.
+#[inline]
+unsafe fn row_mut<'a>(m: *mut Mzd, row: usize) -> &'a mut [Word] {
+    slice::from_raw_parts_mut(row_ptr(m, row), width(m))
+}
+
+/// Read `n <= 64` bits starting at bit `offset` of `words`
+#[inline]
+fn read_bits(words: &[Word], offset: usize, n: usize) -> Word {
+    let block = offset / RADIX;
+    let spot = offset % RADIX;
+    let mut value = words[block] >> spot;
+    if spot != 0 && spot + n > RADIX {
+        value |= words[block + 1] << (RADIX - spot);
+    }
+    if n < RADIX {
+        value & ((1 << n) - 1)
+    } else {
+        value
+    }
+}
+
+/// Set `dst` to the `len` bits of `src` starting at bit `offset`
+///
+/// The words of `dst` past `len` bits are cleared.
+fn copy_bits(src: &[Word], offset: usize, dst: &mut [Word], len: usize) {
+    for (i, word) in dst.iter_mut().enumerate() {
+        let start = i * RADIX;
+        *word = if start < len {
+            read_bits(src, offset + start, cmp::min(RADIX, len - start))
+        } else {
+            0
+        };
+    }
+}
+
+/// XOR the bits of `src` into `dst`, starting at bit `offset` of `dst`
+///
+/// `src` must not have bits set past the end of `dst`.
+fn xor_bits_at(dst: &mut [Word], offset: usize, src: &[Word]) {
+    let spot = offset % RADIX;
+    for (i, &value) in src.iter().enumerate() {
+        let block = offset / RADIX + i;
+        if block < dst.len() {
+            dst[block] ^= value << spot;
+        }
+        if spot != 0 && block + 1 < dst.len() {
+            dst[block + 1] ^= value >> (RADIX - spot);
+        }
+    }
+}
+
+/// `dst ^= src`
+#[inline]
+fn xor_into(dst: &mut [Word], src: &[Word]) {
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= s;
+    }
+}
+
+/// Swap two rows of m
+unsafe fn swap_rows(m: *mut Mzd, a: usize, b: usize) {
+    if a != b {
+        row_mut(m, a).swap_with_slice(row_mut(m, b));
+    }
+}
+
+/// Gaussian elimination on the first `col_limit` columns of m
+///
+/// Row operations are applied to entire rows. If `full` is set, the
+/// reduced row echelon form is computed.
+///
+/// Returns the pivot columns, the number of which is the rank.
+unsafe fn gauss(m: *mut Mzd, full: bool, col_limit: usize) -> Vec<usize> {
+    let nrows = nrows(m);
+    let mut pivots = Vec::new();
+    for col in 0..col_limit {
+        let rank = pivots.len();
+        if rank == nrows {
+            break;
+        }
+        let block = col / RADIX;
+        let bit = 1 << (col % RADIX);
+        let pivot = match (rank..nrows).find(|&r| row(m, r)[block] & bit != 0) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        swap_rows(m, pivot, rank);
+        // All bits before col are zero in the pivot row
+        let pivot_row = &row(m, rank)[block..];
+        let start = if full { 0 } else { rank + 1 };
+        for r in start..nrows {
+            if r != rank && row(m, r)[block] & bit != 0 {
+                xor_into(&mut row_mut(m, r)[block..], pivot_row);
+            }
+        }
+        pivots.push(col);
+    }
+    pivots
+}
+
+/// Use `dst` or allocate a new matrix if it is null, and check its size
+unsafe fn prepare_target(dst: *mut Mzd, nrows: usize, ncols: usize) -> *mut Mzd {
+    if dst.is_null() {
+        mzd_init(nrows as Rci, ncols as Rci)
+    } else {
+        assert!(
+            self::nrows(dst) == nrows && self::ncols(dst) == ncols,
+            "Target matrix has the wrong size: expected {}x{}, got {}x{}",
+            nrows,
+            ncols,
+            self::nrows(dst),
+            self::ncols(dst)
+        );
+        dst
+    }
+}
+
+/// Errors are reported by panicking already, so there is nothing to install
+pub fn m4ri_install_error_hook() -> bool {
+    true
+}
+
+/// Errors are always reported by panicking
+pub fn m4ri_error_hook_installed() -> bool {
+    true
+}
+
+/// Failed allocations make `mzd_init` return a null pointer instead of panicking
+pub fn m4ri_is_allocation_failure(_payload: &(dyn std::any::Any + Send)) -> bool {
+    false
+}
+
+/// Create a new rows x columns matrix
+///
+/// Returns a null pointer if the memory can't be allocated.
+pub unsafe fn mzd_init(rows: Rci, columns: Rci) -> *mut Mzd {
+    let nrows = rows as usize;
+    let ncols = columns as usize;
+    let width = ncols.div_ceil(RADIX);
+    let len = match nrows.checked_mul(width) {
+        Some(len) => len,
+        None => return ptr::null_mut(),
+    };
+    let mut words: Vec<Word> = Vec::new();
+    let mut row_pointers: Vec<*mut Word> = Vec::new();
+    if words.try_reserve_exact(len).is_err() || row_pointers.try_reserve_exact(nrows).is_err() {
+        return ptr::null_mut();
+    }
+    words.resize(len, 0);
+    let data = Box::into_raw(words.into_boxed_slice()) as *mut Word;
+    row_pointers.extend((0..nrows).map(|i| data.add(i * width)));
+    let excess = ncols % RADIX;
+    let high_bitmask = if excess == 0 { !0 } else { (1 << excess) - 1 };
+    let mut mzd = Box::new(Mzd {
+        nrows: rows,
+        ncols: columns,
+        width: width as Wi,
+        high_bitmask,
+        rows: ptr::null(),
+        row_pointers,
+        data,
+        len,
+    });
+    mzd.rows = mzd.row_pointers.as_ptr();
+    Box::into_raw(mzd)
+}
+
+/// Free a matrix created with mzd_init.
+pub unsafe fn mzd_free(matrix: *mut Mzd) {
+    drop(Box::from_raw(matrix));
+}
+
+/// Fill the matrix m with uniformly distributed bits.
+pub unsafe fn mzd_randomize(m: *mut Mzd) {
+    let mut rng = thread_rng();
+    let high_bitmask = (*m).high_bitmask;
+    for r in 0..nrows(m) {
+        let words = row_mut(m, r);
+        rng.fill(words);
+        if let Some(last) = words.last_mut() {
+            *last &= high_bitmask;
+        }
+    }
+}
+
+/// Set the matrix M to the value equivalent to the integer value
+///
+/// Only the lowest bit of the value is used:
+/// a zero matrix for even values, an identity matrix for odd values.
+pub unsafe fn mzd_set_ui(m: *mut Mzd, value: c_uint) {
+    for r in 0..nrows(m) {
+        for word in row_mut(m, r).iter_mut() {
+            *word = 0;
+        }
+    }
+    if value & 1 == 1 {
+        for i in 0..cmp::min(nrows(m), ncols(m)) {
+            row_mut(m, i)[i / RADIX] |= 1 << (i % RADIX);
+        }
+    }
+}
+
+/// Read the bit at position M[row, col]
+#[inline]
+pub unsafe fn mzd_read_bit(m: *const Mzd, row: Rci, col: Rci) -> BIT {
+    let (row, col) = (row as usize, col as usize);
+    debug_assert!(col < ncols(m));
+    ((*row_ptr(m, row).add(col / RADIX) >> (col % RADIX)) & 1) as BIT
+}
+
+/// Write the bit value to position M[row, col]
+#[inline]
+pub unsafe fn mzd_write_bit(m: *mut Mzd, row: Rci, col: Rci, value: BIT) {
+    let (row, col) = (row as usize, col as usize);
+    debug_assert!(col < ncols(m));
+    let word = row_ptr(m, row).add(col / RADIX);
+    let mask = 1 << (col % RADIX);
+    if value & 1 == 1 {
+        *word |= mask;
+    } else {
+        *word &= !mask;
+    }
+}
+
+/// Swap the columns `cola` and `colb`
+pub unsafe fn mzd_col_swap(m: *mut Mzd, cola: Rci, colb: Rci) {
+    for r in 0..nrows(m) as Rci {
+        let a = mzd_read_bit(m, r, cola);
+        mzd_write_bit(m, r, cola, mzd_read_bit(m, r, colb));
+        mzd_write_bit(m, r, colb, a);
+    }
+}
+
+/// Return 1 if A == B, 0 otherwise
+pub unsafe fn mzd_equal(a: *const Mzd, b: *const Mzd) -> c_int {
+    if nrows(a) != nrows(b) || ncols(a) != ncols(b) {
+        return 0;
+    }
+    // Like M4RI, ignore the bits past the last column
+    let mask = (*a).high_bitmask;
+    (0..nrows(a)).all(|r| match (row(a, r).split_last(), row(b, r).split_last()) {
+        (Some((last_a, a)), Some((last_b, b))) => a == b && (last_a ^ last_b) & mask == 0,
+        _ => true,
+    }) as c_int
+}
+
+/// Copy matrix A to DST.
+///
+/// DST may be null for automatic creation, otherwise it needs to be
+/// at least as large as A. Only the top-left part of DST is overwritten.
+pub unsafe fn mzd_copy(dst: *mut Mzd, a: *const Mzd) -> *mut Mzd {
+    if ptr::eq(dst, a) {
+        return dst;
+    }
+    let dst = if dst.is_null() {
+        mzd_init((*a).nrows, (*a).ncols)
+    } else {
+        assert!(
+            nrows(dst) >= nrows(a) && ncols(dst) >= ncols(a),
+            "Target matrix is too small"
+        );
+        dst
+    };
+    let high_bitmask = (*a).high_bitmask;
+    for r in 0..nrows(a) {
+        let src = row(a, r);
+        let target = &mut row_mut(dst, r)[..src.len()];
+        if let Some((last, init)) = target.split_last_mut() {
+            init.copy_from_slice(&src[..init.len()]);
+            *last = (*last & !high_bitmask) | src[src.len() - 1];
+        }
+    }
+    dst
+}
+
+/// Transpose a matrix
+///
+/// DST may be null for automatic creation
+pub unsafe fn mzd_transpose(dst: *mut Mzd, a: *const Mzd) -> *mut Mzd {
+    let dst = prepare_target(dst, ncols(a), nrows(a));
+    mzd_set_ui(dst, 0);
+    for r in 0..nrows(a) {
+        for (block, &word) in row(a, r).iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let c = block * RADIX + word.trailing_zeros() as usize;
+                row_mut(dst, c)[r / RADIX] |= 1 << (r % RADIX);
+                word &= word - 1;
+            }
+        }
+    }
+    dst
+}
+
+/// Set C = A + B
+///
+/// C may be null for automatic creation, and may be equal to A or B.
+pub unsafe fn mzd_add(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd {
+    assert!(
+        nrows(a) == nrows(b) && ncols(a) == ncols(b),
+        "Can't add matrices of different sizes"
+    );
+    let c = prepare_target(c, nrows(a), ncols(a));
+    for r in 0..nrows(a) {
+        // C may alias A or B, so only use raw pointers here
+        let (cw, aw, bw) = (row_ptr(c, r), row_ptr(a, r), row_ptr(b, r));
+        for i in 0..width(a) {
+            *cw.add(i) = *aw.add(i) ^ *bw.add(i);
+        }
+    }
+    c
+}
+
+/// Concatenate B to A and write the result to C: ``[A] [B] => [A B]``
+///
+/// C may be null for automatic creation
+pub unsafe fn mzd_concat(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd {
+    assert_eq!(nrows(a), nrows(b), "The number of rows needs to be equal");
+    let c = prepare_target(c, nrows(a), ncols(a) + ncols(b));
+    for r in 0..nrows(a) {
+        let target = row_mut(c, r);
+        for word in target.iter_mut() {
+            *word = 0;
+        }
+        target[..width(a)].copy_from_slice(row(a, r));
+        xor_bits_at(target, ncols(a), row(b, r));
+    }
+    c
+}
+
+/// Stack A on top of B into C
+///
+/// C may be null for automatic creation
+pub unsafe fn mzd_stack(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd {
+    assert_eq!(
+        ncols(a),
+        ncols(b),
+        "The number of columns needs to be equal"
+    );
+    let c = prepare_target(c, nrows(a) + nrows(b), ncols(a));
+    for r in 0..nrows(a) {
+        row_mut(c, r).copy_from_slice(row(a, r));
+    }
+    for r in 0..nrows(b) {
+        row_mut(c, nrows(a) + r).copy_from_slice(row(b, r));
+    }
+    c
+}
+
+/// Check the sizes for C = AB and prepare C
+unsafe fn prepare_product(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd {
+    assert_eq!(
+        ncols(a),
+        nrows(b),
+        "Mismatched sizes: ({}x{}) * ({}x{})",
+        nrows(a),
+        ncols(a),
+        nrows(b),
+        ncols(b)
+    );
+    let c = prepare_target(c, nrows(a), ncols(b));
+    mzd_set_ui(c, 0);
+    c
+}
+
+/// Naive cubic matrix multiplication: C = AB
+///
+/// C may be null for automatic creation
+pub unsafe fn mzd_mul_naive(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd {
+    let c = prepare_product(c, a, b);
+    for r in 0..nrows(a) {
+        let target = row_mut(c, r);
+        for (block, &word) in row(a, r).iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let i = block * RADIX + word.trailing_zeros() as usize;
+                xor_into(target, row(b, i));
+                word &= word - 1;
+            }
+        }
+    }
+    c
+}
+
+/// Matrix multiplication using the method of the four russians: C = AB
+///
+/// C may be null for automatic creation
+/// k: table size parameter, may be 0 for automatic choice
+pub unsafe fn mzd_mul_m4rm(c: *mut Mzd, a: *const Mzd, b: *const Mzd, k: c_int) -> *mut Mzd {
+    let c = prepare_product(c, a, b);
+    let k = if k <= 0 {
+        DEFAULT_M4RM_K
+    } else {
+        cmp::min(k as usize, 16)
+    };
+    let width = width(b);
+    let mut table = vec![0 as Word; (1 << k) * width];
+    for start in (0..ncols(a)).step_by(k) {
+        let k = cmp::min(k, ncols(a) - start);
+        // Entry i of the table is the sum of the rows of B selected by the bits of i
+        for i in 1..(1usize << k) {
+            let low = i.trailing_zeros() as usize;
+            let (done, todo) = table.split_at_mut(i * width);
+            let previous = i ^ (1 << low);
+            todo[..width].copy_from_slice(&done[previous * width..(previous + 1) * width]);
+            xor_into(&mut todo[..width], row(b, start + low));
+        }
+        for r in 0..nrows(a) {
+            let index = read_bits(row(a, r), start, k) as usize;
+            if index != 0 {
+                xor_into(row_mut(c, r), &table[index * width..(index + 1) * width]);
+            }
+        }
+    }
+    c
+}
+
+/// Matrix multiplication: C = AB
+///
+/// Uses M4RM, as Strassen multiplication is not implemented.
+/// C may be null for automatic creation
+pub unsafe fn mzd_mul(c: *mut Mzd, a: *const Mzd, b: *const Mzd, _cutoff: c_int) -> *mut Mzd {
+    mzd_mul_m4rm(c, a, b, 0)
+}
+
+/// Gaussian elimination on the matrix
+///
+/// full: return the reduced row echelon form, not only upper triangular form
+///
+/// Return the rank of M
+pub unsafe fn mzd_echelonize(m: *mut Mzd, full: c_int) -> Rci {
+    gauss(m, full != 0, ncols(m)).len() as Rci
+}
+
+/// Gaussian elimination on the matrix, same as `mzd_echelonize`
+pub unsafe fn mzd_echelonize_naive(m: *mut Mzd, full: c_int) -> Rci {
+    mzd_echelonize(m, full)
+}
+
+/// Invert the matrix by Gauss-Jordan elimination of ``[A I]``
+///
+/// dst: Matrix to hold the inverse (may be Null)
+/// src: Matrix to be inverted
+/// k: ignored
+///
+/// Like M4RI, this does not check that A is invertible:
+/// the result is undefined if it isn't.
+pub unsafe fn mzd_inv_m4ri(dst: *mut Mzd, src: *const Mzd, _k: c_int) -> *mut Mzd {
+    let n = nrows(src);
+    assert_eq!(n, ncols(src), "Can only invert square matrices");
+    let identity = mzd_init(n as Rci, n as Rci);
+    mzd_set_ui(identity, 1);
+    let big = mzd_concat(ptr::null_mut(), src, identity);
+    mzd_free(identity);
+    gauss(big, true, n);
+    let dst = prepare_target(dst, n, n);
+    for r in 0..n {
+        copy_bits(row(big, r), n, row_mut(dst, r), n);
+    }
+    mzd_free(big);
+    dst
+}
+
+/// Solves A X = B with A and B matrices.
+///
+/// The solution X is stored in the first rows of B, the other rows are cleared.
+/// Free variables are set to zero.
+///
+/// param A Input matrix, needs at most as many columns as rows
+/// param B Input matrix, being overwritten by the solution matrix X
+/// param cutoff ignored
+/// param inconsistency_check check if the system has a solution
+/// return 0 if a solution was found, -1 otherwise (B is left unchanged)
+pub unsafe fn mzd_solve_left(
+    a: *mut Mzd,
+    b: *mut Mzd,
+    _cutoff: c_int,
+    inconsistency_check: c_int,
+) -> c_int {
+    let (m, n, k) = (nrows(a), ncols(a), ncols(b));
+    assert_eq!(m, nrows(b), "A and B need the same number of rows");
+    assert!(n <= m, "A needs at most as many columns as rows");
+    let augmented = mzd_concat(ptr::null_mut(), a, b);
+    let pivots = gauss(augmented, true, n);
+    if inconsistency_check != 0 && (pivots.len()..m).any(|r| read_row_bits(augmented, r, n, k)) {
+        mzd_free(augmented);
+        return -1;
+    }
+    mzd_set_ui(b, 0);
+    for (r, &col) in pivots.iter().enumerate() {
+        copy_bits(row(augmented, r), n, row_mut(b, col), k);
+    }
+    mzd_free(augmented);
+    0
+}
+
+/// Solve X for A X = 0
+///
+/// Returns the nc x (nc - r) matrix X whose columns are a basis of the kernel,
+/// or NULL if the kernel is trivial.
+///
+/// param A Input matrix (overwritten)
+/// param cutoff ignored
+pub unsafe fn mzd_kernel_left_pluq(a: *mut Mzd, _cutoff: c_int) -> *mut Mzd {
+    let n = ncols(a);
+    let pivots = gauss(a, true, n);
+    if pivots.len() == n {
+        return ptr::null_mut();
+    }
+    let mut is_pivot = vec![false; n];
+    for &col in &pivots {
+        is_pivot[col] = true;
+    }
+    let free: Vec<usize> = (0..n).filter(|&col| !is_pivot[col]).collect();
+    let x = mzd_init(n as Rci, free.len() as Rci);
+    for (k, &col) in free.iter().enumerate() {
+        mzd_write_bit(x, col as Rci, k as Rci, 1);
+        for (r, &pivot) in pivots.iter().enumerate() {
+            let bit = mzd_read_bit(a, r as Rci, col as Rci);
+            mzd_write_bit(x, pivot as Rci, k as Rci, bit);
+        }
+    }
+    x
+}
+
+/// Check if any of the `len` bits starting at column `offset` of the row is set
+unsafe fn read_row_bits(m: *const Mzd, r: usize, offset: usize, len: usize) -> bool {
+    let words = row(m, r);
+    (0..len)
+        .step_by(RADIX)
+        .any(|start| read_bits(words, offset + start, cmp::min(RADIX, len - start)) != 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    unsafe fn from_rows(rows: &[&[u8]]) -> *mut Mzd {
+        let m = mzd_init(rows.len() as Rci, rows[0].len() as Rci);
+        for (i, r) in rows.iter().enumerate() {
+            for (j, &bit) in r.iter().enumerate() {
+                mzd_write_bit(m, i as Rci, j as Rci, bit as BIT);
+            }
+        }
+        m
+    }
+
+    #[test]
+    fn init_layout() {
+        unsafe {
+            let m = mzd_init(3, 70);
+            assert_eq!((*m).width, 2);
+            assert_eq!((*m).high_bitmask, 0b11_1111);
+            mzd_write_bit(m, 2, 69, 1);
+            assert_eq!(*(*(*m).rows.add(2)).add(1), 1 << 5);
+            assert_eq!(mzd_read_bit(m, 2, 69), 1);
+            mzd_randomize(m);
+            for r in 0..3 {
+                assert_eq!(row(m, r)[1] & !(*m).high_bitmask, 0);
+            }
+            mzd_free(m);
+        }
+    }
+
+    #[test]
+    fn bits_helpers() {
+        let words = [0xf000_0000_0000_0001, 0b1011];
+        assert_eq!(read_bits(&words, 60, 8), 0b1011_1111);
+        let mut dst = [0; 1];
+        copy_bits(&words, 60, &mut dst, 6);
+        assert_eq!(dst, [0b11_1111]);
+        let mut dst = [0; 2];
+        xor_bits_at(&mut dst, 62, &[0b111]);
+        assert_eq!(dst, [0b11 << 62, 1]);
+    }
+
+    #[test]
+    fn mul_algorithms_agree() {
+        unsafe {
+            let a = mzd_init(37, 150);
+            let b = mzd_init(150, 70);
+            mzd_randomize(a);
+            mzd_randomize(b);
+            let naive = mzd_mul_naive(ptr::null_mut(), a, b);
+            let m4rm = mzd_mul_m4rm(ptr::null_mut(), a, b, 0);
+            let m4rm_odd = mzd_mul_m4rm(ptr::null_mut(), a, b, 5);
+            assert_eq!(mzd_equal(naive, m4rm), 1);
+            assert_eq!(mzd_equal(naive, m4rm_odd), 1);
+            for i in 0..37 {
+                for j in 0..70 {
+                    let expected = (0..150).fold(0, |acc, k| {
+                        acc ^ (mzd_read_bit(a, i, k) & mzd_read_bit(b, k, j))
+                    });
+                    assert_eq!(mzd_read_bit(naive, i, j), expected);
+                }
+            }
+            for m in &[a, b, naive, m4rm, m4rm_odd] {
+                mzd_free(*m);
+            }
+        }
+    }
+
+    #[test]
+    fn transpose_concat_stack() {
+        unsafe {
+            let a = from_rows(&[&[1, 0, 1], &[0, 1, 1]]);
+            let t = mzd_transpose(ptr::null_mut(), a);
+            let expected = from_rows(&[&[1, 0], &[0, 1], &[1, 1]]);
+            assert_eq!(mzd_equal(t, expected), 1);
+
+            let big = mzd_init(2, 100);
+            mzd_randomize(big);
+            let concat = mzd_concat(ptr::null_mut(), a, big);
+            let stack = mzd_stack(ptr::null_mut(), a, a);
+            for i in 0..2 {
+                for j in 0..103 {
+                    let expected = if j < 3 {
+                        mzd_read_bit(a, i, j)
+                    } else {
+                        mzd_read_bit(big, i, j - 3)
+                    };
+                    assert_eq!(mzd_read_bit(concat, i, j), expected);
+                }
+                for j in 0..3 {
+                    assert_eq!(mzd_read_bit(stack, i + 2, j), mzd_read_bit(a, i, j));
+                }
+            }
+            let sum = mzd_add(ptr::null_mut(), stack, stack);
+            let zero = mzd_init(4, 3);
+            assert_eq!(mzd_equal(sum, zero), 1);
+            for m in &[a, t, expected, big, concat, stack, sum, zero] {
+                mzd_free(*m);
+            }
+        }
+    }
+
+    #[test]
+    fn echelonize_inverse_solve() {
+        unsafe {
+            let a = from_rows(&[&[1, 1, 0], &[1, 0, 1], &[0, 1, 1]]);
+            let copy = mzd_copy(ptr::null_mut(), a);
+            assert_eq!(mzd_echelonize(copy, 1), 2);
+            let expected = from_rows(&[&[1, 0, 1], &[0, 1, 1], &[0, 0, 0]]);
+            assert_eq!(mzd_equal(copy, expected), 1);
+
+            let invertible = from_rows(&[&[1, 1, 0], &[0, 1, 0], &[1, 0, 1]]);
+            let inverse = mzd_inv_m4ri(ptr::null_mut(), invertible, 0);
+            let product = mzd_mul_naive(ptr::null_mut(), invertible, inverse);
+            let identity = mzd_init(3, 3);
+            mzd_set_ui(identity, 1);
+            assert_eq!(mzd_equal(product, identity), 1);
+
+            // The rows of A add up to zero, so A x = [1 0 0]^T has no solution
+            let inconsistent = from_rows(&[&[1], &[0], &[0]]);
+            assert_eq!(mzd_solve_left(a, inconsistent, 0, 1), -1);
+            // but x = [1 0 0]^T is a solution for [1 1 0]^T
+            let b = from_rows(&[&[1], &[1], &[0]]);
+            let b_copy = mzd_copy(ptr::null_mut(), b);
+            assert_eq!(mzd_solve_left(a, b, 0, 1), 0);
+            let check = mzd_mul_naive(ptr::null_mut(), a, b);
+            assert_eq!(mzd_equal(check, b_copy), 1);
+
+            // The kernel of A is spanned by [1 1 1]^T, and trivial for an invertible matrix
+            let reduced = mzd_copy(ptr::null_mut(), a);
+            let kernel = mzd_kernel_left_pluq(reduced, 0);
+            let ones = from_rows(&[&[1], &[1], &[1]]);
+            assert_eq!(mzd_equal(kernel, ones), 1);
+            let trivial = mzd_copy(ptr::null_mut(), invertible);
+            assert!(mzd_kernel_left_pluq(trivial, 0).is_null());
+
+            for m in &[
+                a,
+                copy,
+                expected,
+                invertible,
+                inverse,
+                product,
+                identity,
+                inconsistent,
+                b,
+                b_copy,
+                check,
+                reduced,
+                kernel,
+                ones,
+                trivial,
+            ] {
+                mzd_free(*m);
+            }
+        }
+    }
+}
+
+/// Compares the results with M4RI, if the C backend is available as well
+#[cfg(all(test, feature = "m4ri-sys"))]
+mod cross_test {
+    use super::*;
+    use m4ri_sys as c;
+
+    unsafe fn to_c(m: *const Mzd) -> *mut c::Mzd {
+        let result = c::mzd_init((*m).nrows, (*m).ncols);
+        for r in 0..nrows(m) {
+            slice::from_raw_parts_mut(c::mzd_row(result, r as Rci), width(m))
+                .copy_from_slice(row(m, r));
+        }
+        result
+    }
+
+    unsafe fn assert_same(m: *const Mzd, other: *const c::Mzd) {
+        assert_eq!((*m).nrows, (*other).nrows);
+        assert_eq!((*m).ncols, (*other).ncols);
+        for r in 0..nrows(m) {
+            let words = slice::from_raw_parts(c::mzd_row(other, r as Rci), width(m));
+            assert_eq!(row(m, r), words, "row {} differs", r);
+        }
+    }
+
+    unsafe fn random(rows: Rci, cols: Rci) -> (*mut Mzd, *mut c::Mzd) {
+        let m = mzd_init(rows, cols);
+        mzd_randomize(m);
+        let other = to_c(m);
+        (m, other)
+    }
+
+    #[test]
+    fn mul_transpose_add() {
+        unsafe {
+            let (a, ca) = random(100, 130);
+            let (b, cb) = random(130, 67);
+            let product = mzd_mul_m4rm(ptr::null_mut(), a, b, 0);
+            let c_product = c::mzd_mul(ptr::null_mut(), ca, cb, 0);
+            assert_same(product, c_product);
+            let transposed = mzd_transpose(ptr::null_mut(), a);
+            let c_transposed = c::mzd_transpose(ptr::null_mut(), ca);
+            assert_same(transposed, c_transposed);
+            let sum = mzd_add(ptr::null_mut(), transposed, b);
+            let c_sum = c::mzd_add(ptr::null_mut(), c_transposed, cb);
+            assert_same(sum, c_sum);
+            for m in &[a, b, product, transposed, sum] {
+                mzd_free(*m);
+            }
+            for m in &[ca, cb, c_product, c_transposed, c_sum] {
+                c::mzd_free(*m);
+            }
+        }
+    }
+
+    #[test]
+    fn echelonize_inverse_solve() {
+        unsafe {
+            let (a, ca) = random(80, 90);
+            assert_eq!(mzd_echelonize(a, 1), c::mzd_echelonize(ca, 1));
+            assert_same(a, ca);
+
+            let (square, c_square) = random(70, 70);
+            let copy = mzd_copy(ptr::null_mut(), square);
+            if mzd_echelonize(copy, 0) == 70 {
+                let inverse = mzd_inv_m4ri(ptr::null_mut(), square, 0);
+                let c_inverse = c::mzd_inv_m4ri(ptr::null_mut(), c_square, 0);
+                assert_same(inverse, c_inverse);
+                mzd_free(inverse);
+                c::mzd_free(c_inverse);
+            }
+
+            // B = A X has a solution, which is unique if A has full column rank
+            let (system, c_system) = random(90, 80);
+            let (x, _) = random(80, 3);
+            let b = mzd_mul_naive(ptr::null_mut(), system, x);
+            let cb = to_c(b);
+            let system_copy = mzd_copy(ptr::null_mut(), system);
+            let full_rank = mzd_echelonize(system_copy, 0) == 80;
+            assert_eq!(mzd_solve_left(system, b, 0, 1), 0);
+            assert_eq!(c::mzd_solve_left(c_system, cb, 0, 1), 0);
+            if full_rank {
+                for r in 0..80 {
+                    let words = slice::from_raw_parts(c::mzd_row(cb, r as Rci), 1);
+                    assert_eq!(row(b, r), words);
+                    assert_eq!(row(b, r), row(x, r));
+                }
+            }
+            for m in &[a, square, copy, system, x, b, system_copy] {
+                mzd_free(*m);
+            }
+            for m in &[ca, c_square, c_system, cb] {
+                c::mzd_free(*m);
+            }
+        }
+    }
+}
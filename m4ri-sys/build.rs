@@ -1,72 +1,316 @@
-use std::{env, path::PathBuf};
-use std::{io, fs};
+use std::collections::HashMap;
 use std::path::Path;
-use std::process::Command;
-
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
-    fs::create_dir_all(&dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
+use std::{env, path::PathBuf};
+use std::{fs, io};
+
+/// Compile the C side of the layout tests
+///
+/// It exports the sizes and field offsets of the M4RI structs as seen by the C compiler.
+fn compile_layout_shim(include_dir: &Path) {
+    cc::Build::new()
+        .file("src/layout.c")
+        .include(include_dir)
+        .compile("m4ri_sys_layout");
+}
+
+/// Generate the bindings to the public M4RI headers into `$OUT_DIR/bindings.rs`
+#[cfg(feature = "bindgen")]
+fn generate_bindings(include_dir: &Path) {
+    let out_file = PathBuf::from(env::var("OUT_DIR").unwrap()).join("bindings.rs");
+    bindgen::Builder::default()
+        .header(include_dir.join("m4ri").join("m4ri.h").to_str().unwrap())
+        .clang_arg(format!("-I{}", include_dir.to_str().unwrap()))
+        .allowlist_function("_?(mzd|mzp|djb|m4ri)_.*")
+        .allowlist_type("(mzd|mzp|djb|code|mzd_block)_t")
+        .allowlist_var("m4ri_.*|mzd_flag_.*")
+        .layout_tests(true)
+        .generate()
+        .expect("Failed to generate bindings")
+        .write_to_file(out_file)
+        .expect("Failed to write bindings");
+}
+
+/// Oldest M4RI release the bindings are known to work with
+const MIN_M4RI_VERSION: &str = "0.0.20140914";
+
+/// Parse a version like `0.0.20140914` into its numeric components
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version.trim().split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Check the version in the `m4ri.pc` file that M4RI installs next to the library
+///
+/// Panics if the installed M4RI is older than `MIN_M4RI_VERSION`.
+fn check_installed_version(lib_dir: &Path) {
+    let pc_file = lib_dir.join("pkgconfig").join("m4ri.pc");
+    let version = fs::read_to_string(&pc_file).ok().and_then(|contents| {
+        contents
+            .lines()
+            .find_map(|line| line.strip_prefix("Version:"))
+            .map(|version| version.trim().to_string())
+    });
+    let version = match version {
+        Some(version) => version,
+        None => {
+            println!(
+                "cargo:warning=Could not read the m4ri version from {}, assuming it is at least {}",
+                pc_file.display(),
+                MIN_M4RI_VERSION
+            );
+            return;
         }
+    };
+    match (parse_version(&version), parse_version(MIN_M4RI_VERSION)) {
+        (Some(found), Some(minimum)) if found < minimum => panic!(
+            "The m4ri in {} has version {}, but at least {} is required",
+            lib_dir.display(),
+            version,
+            MIN_M4RI_VERSION
+        ),
+        (Some(_), _) => {}
+        (None, _) => println!(
+            "cargo:warning=Could not parse the m4ri version {:?} in {}",
+            version,
+            pc_file.display()
+        ),
     }
-    Ok(())
 }
 
-fn main() -> io::Result<()> {
-    println!("Making M4RI");
-    println!("PWD: {:?}", env::current_dir().unwrap());
-    let mut out_dir: PathBuf = env::var("OUT_DIR").unwrap().into();
-    out_dir.push("m4ri");
-    println!("OUT_DIR = {:?}", &out_dir);
-
-    copy_dir_all("vendor/m4ri", &out_dir)?;
-
-    if !out_dir.join("Makefile").exists() {
-        println!("cargo:info=Building m4ri lib");
-        println!("Configuring build!");
-
-        let status = Command::new("autoreconf")
-            .arg("--install")
-            .current_dir(&out_dir)
-            .status()
-            .expect("Failed to execute autoreconf");
-        if !status.success() {
-            panic!("Autoconf failed!");
+/// Find an installed M4RI library and emit the linker settings for it
+///
+/// `M4RI_DIR` and `M4RI_LIB_DIR` are used with or without the `system` feature,
+/// which only adds the lookup through pkg-config.
+///
+/// Returns the include directory that contains `m4ri/m4ri.h`.
+fn find_system_m4ri() -> Option<PathBuf> {
+    let link_kind = if env::var_os("M4RI_STATIC").is_some() {
+        "static"
+    } else {
+        "dylib"
+    };
+
+    let lib_dir = env::var_os("M4RI_LIB_DIR")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("M4RI_DIR").map(|dir| Path::new(&dir).join("lib")));
+    if let Some(lib_dir) = lib_dir {
+        let include_dir = env::var_os("M4RI_INCLUDE_DIR")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("M4RI_DIR").map(|dir| Path::new(&dir).join("include")))
+            .unwrap_or_else(|| lib_dir.join("..").join("include"));
+        check_installed_version(&lib_dir);
+        println!("cargo:rustc-link-search=native={}", lib_dir.display());
+        println!("cargo:rustc-link-lib={}=m4ri", link_kind);
+        return Some(include_dir);
+    }
+
+    #[cfg(feature = "system")]
+    {
+        match pkg_config::Config::new()
+            .atleast_version(MIN_M4RI_VERSION)
+            .statik(link_kind == "static")
+            .probe("m4ri")
+        {
+            Ok(library) => {
+                return Some(
+                    library
+                        .include_paths
+                        .into_iter()
+                        .find(|path| path.join("m4ri").join("m4ri.h").exists())
+                        .unwrap_or_else(|| PathBuf::from("/usr/include")),
+                )
+            }
+            Err(e) => println!("cargo:warning=Could not find m4ri using pkg-config: {}", e),
+        }
+    }
+
+    None
+}
+
+/// Default cache sizes in bytes, used to tune the algorithms
+///
+/// Can be overridden with `M4RI_CPU_L1_CACHE`, `M4RI_CPU_L2_CACHE` and `M4RI_CPU_L3_CACHE`.
+const DEFAULT_CACHE_SIZES: [(&str, &str); 3] = [
+    ("M4RI_CPU_L1_CACHE", "32768"),
+    ("M4RI_CPU_L2_CACHE", "262144"),
+    ("M4RI_CPU_L3_CACHE", "8388608"),
+];
+
+fn flag(enabled: bool) -> String {
+    if enabled { "1" } else { "0" }.to_string()
+}
+
+/// Generate `m4ri_config.h` from the autoconf template `m4ri_config.h.in`
+fn write_config(template: &Path, target: &Path, values: &HashMap<&str, String>) -> io::Result<()> {
+    let template = fs::read_to_string(template)?;
+    let mut config = String::with_capacity(template.len());
+    let mut rest = template.as_str();
+    while let Some(start) = rest.find('@') {
+        let (before, after) = rest.split_at(start);
+        config.push_str(before);
+        match after[1..].find('@') {
+            Some(end) if after[1..=end].chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+                let name = &after[1..=end];
+                match values.get(name) {
+                    Some(value) => config.push_str(value),
+                    None => {
+                        println!("cargo:warning=Unknown m4ri_config.h setting {}, using 0", name);
+                        config.push('0');
+                    }
+                }
+                rest = &after[end + 2..];
+            }
+            _ => {
+                config.push('@');
+                rest = &after[1..];
+            }
         }
+    }
+    config.push_str(rest);
+    fs::write(target, config)
+}
 
-        let status = Command::new("./configure")
-            .arg("--enable-static")
-            .arg("--enable-thread-safe")
-            .arg("--disable-png")
-            .env("CFLAGS", "-Ofast -fPIC")
-            .current_dir(&out_dir)
-            .status()
-            .expect("Failed to execute ./configure");
-        if !status.success() {
-            panic!("Configure failed");
+/// Build the vendored copy of M4RI with the cc crate
+///
+/// Returns the include directory that contains `m4ri/m4ri.h`.
+fn build_vendored() -> io::Result<PathBuf> {
+    let source_dir = Path::new("vendor").join("m4ri").join("m4ri");
+    if !source_dir.join("m4ri_config.h.in").exists() {
+        panic!("vendor/m4ri is empty, run `git submodule update --init`");
+    }
+    let out_dir: PathBuf = env::var("OUT_DIR").unwrap().into();
+    let include_dir = out_dir.join("include");
+    let header_dir = include_dir.join("m4ri");
+    fs::create_dir_all(&header_dir)?;
+
+    let mut sources = Vec::new();
+    for entry in fs::read_dir(&source_dir)? {
+        let path = entry?.path();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("c") => sources.push(path),
+            Some("h") => {
+                fs::copy(&path, header_dir.join(path.file_name().unwrap()))?;
+            }
+            _ => {}
         }
     }
+    sources.sort();
+    // misc.c defines the original m4ri_die, which is replaced by src/die.c in the other files
+    let misc = source_dir.join("misc.c");
+    sources.retain(|path| *path != misc);
+
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let target_features = env::var("CARGO_CFG_TARGET_FEATURE").unwrap_or_default();
+    let is_x86 = target_arch == "x86" || target_arch == "x86_64";
+    let sse2 = match env::var("M4RI_SIMD").as_ref().map(String::as_str) {
+        Ok("none") => false,
+        Ok("sse2") => true,
+        Ok(other) => panic!("Unsupported M4RI_SIMD value {:?}, use \"none\" or \"sse2\"", other),
+        Err(_) => is_x86 && target_features.split(',').any(|f| f == "sse2"),
+    };
+    let openmp = env::var_os("CARGO_FEATURE_OPENMP").is_some();
+    let png = env::var_os("CARGO_FEATURE_PNG").is_some();
+    let debug_dump = env::var_os("CARGO_FEATURE_DEBUG_DUMP").is_some();
 
-    println!("Executing Make");
+    let mut build = cc::Build::new();
+    build
+        .include(&include_dir)
+        .include(&header_dir)
+        .warnings(false)
+        // Lets the panic from the m4ri_die hook unwind through M4RI
+        .flag_if_supported("-fexceptions");
+    if sse2 {
+        build.flag_if_supported("-msse2");
+    }
+    if openmp {
+        build.flag(if build.get_compiler().is_like_msvc() {
+            "/openmp"
+        } else {
+            "-fopenmp"
+        });
+    }
+    let compiler = build.get_compiler();
 
-    let status = Command::new("make")
-        .arg("-j5")
-        .current_dir(&out_dir)
-        .status()
-        .expect("Failed to execute /usr/bin/make");
-    if !status.success() {
-        panic!("Make failed");
+    let mut values = HashMap::new();
+    values.insert("M4RI_HAVE_MM_MALLOC", flag(is_x86 && sse2));
+    values.insert(
+        "M4RI_HAVE_POSIX_MEMALIGN",
+        flag(env::var("CARGO_CFG_TARGET_FAMILY").is_ok_and(|f| f == "unix")),
+    );
+    values.insert("M4RI_HAVE_SSE2", flag(sse2));
+    values.insert("M4RI_HAVE_OPENMP", flag(openmp));
+    values.insert("M4RI_HAVE_LIBPNG", flag(png));
+    values.insert("M4RI_DEBUG_DUMP", flag(debug_dump));
+    values.insert("M4RI_DEBUG_MZD", flag(false));
+    // Thread-safe mode: the global matrix and memory caches are not synchronised
+    values.insert("M4RI_ENABLE_MZD_CACHE", flag(false));
+    values.insert("M4RI_ENABLE_MMC", flag(false));
+    for (name, default) in DEFAULT_CACHE_SIZES.iter() {
+        println!("cargo:rerun-if-env-changed={}", name);
+        values.insert(name, env::var(name).unwrap_or_else(|_| default.to_string()));
     }
+    values.insert("CC", compiler.path().display().to_string());
+    values.insert("CFLAGS", String::new());
+    values.insert("SIMD_CFLAGS", if sse2 { "-msse2" } else { "" }.to_string());
+    values.insert("OPENMP_CFLAGS", if openmp { "-fopenmp" } else { "" }.to_string());
+    write_config(
+        &source_dir.join("m4ri_config.h.in"),
+        &header_dir.join("m4ri_config.h"),
+        &values,
+    )?;
+
+    let mut misc_build = build.clone();
+    build
+        .files(&sources)
+        .file("src/die.c")
+        .define("m4ri_die", "m4ri_sys_die")
+        .compile("m4ri");
+    misc_build.file(&misc).compile("m4ri_misc");
+    println!("cargo:rustc-cfg=m4ri_die_hook");
+
+    if png {
+        println!("cargo:rustc-link-lib=png");
+    }
+    if openmp && !compiler.is_like_msvc() {
+        println!(
+            "cargo:rustc-link-lib={}",
+            if compiler.is_like_clang() { "omp" } else { "gomp" }
+        );
+    }
+
+    Ok(include_dir)
+}
+
+fn main() -> io::Result<()> {
+    for var in &[
+        "M4RI_DIR",
+        "M4RI_LIB_DIR",
+        "M4RI_INCLUDE_DIR",
+        "M4RI_STATIC",
+        "M4RI_NO_VENDOR",
+        "M4RI_SIMD",
+    ] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/layout.c");
+    println!("cargo:rerun-if-changed=src/die.c");
+    println!("cargo:rustc-check-cfg=cfg(m4ri_die_hook)");
+    println!("cargo:rerun-if-changed=vendor/m4ri");
+
+    let include_dir = match find_system_m4ri() {
+        Some(include_dir) => include_dir,
+        None if env::var_os("M4RI_NO_VENDOR").is_some() => panic!(
+            "M4RI_NO_VENDOR is set, but no installed m4ri was found. \
+             Enable the `system` feature or set M4RI_DIR or M4RI_LIB_DIR."
+        ),
+        None => build_vendored()?,
+    };
+    // Lets crates that depend on m4ri-sys find the headers as DEP_M4RI_INCLUDE
+    println!("cargo:include={}", include_dir.display());
 
-    // Output settings for Cargo
-    println!("cargo:rustc-link-search=native={}", out_dir.join(".libs").to_str().unwrap());
-    //println!("cargo:rustc-link-search=native=m4ri-sys/{}", out_dir.join(".libs").to_str().unwrap());
-    println!("cargo:rustc-link-lib=static=m4ri");
+    compile_layout_shim(&include_dir);
+    #[cfg(feature = "bindgen")]
+    generate_bindings(&include_dir);
 
     Ok(())
 }
@@ -1,9 +1,8 @@
+//! See `brilliantrussian.h`
 use crate::misc::Rci;
 use crate::mzd::Mzd;
-/// See `brilliantrussian.h`
-use libc;
 
-extern "C" {
+extern "C-unwind" {
 
     /// Constructs all possible 2^k row combinations using the
     /// gray code table
@@ -198,6 +197,43 @@ extern "C" {
     /// k: M4ri parameter, may be 0 for auto-choose
     pub fn mzd_top_echelonize_m4ri(m: *mut Mzd, k: libc::c_int);
 
+    /// Matrix elimination using the Method of the four russians (m4ri)
+    ///
+    /// This is the implementation of `mzd_echelonize_m4ri`.
+    ///
+    /// A: Matrix to be reduced
+    /// full: return the reduced row echelon form, not only upper triangular form
+    /// k: M4ri parameter, may be 0 for auto-choose
+    /// heuristic: use heuristic to switch to PLUQ on dense submatrices
+    /// threshold: density at which to switch
+    ///
+    /// Return the rank of A
+    pub fn _mzd_echelonize_m4ri(
+        a: *mut Mzd,
+        full: libc::c_int,
+        k: libc::c_int,
+        heuristic: libc::c_int,
+        threshold: libc::c_double,
+    ) -> Rci;
+
+    /// Matrix elimination using the Method of the four russians (m4ri)
+    /// in the submatrix starting at (r, c) and stopping at row max_r
+    ///
+    /// A: Matrix to be reduced
+    /// k: M4ri parameter, may be 0 for auto-choose
+    /// r: first row to consider
+    /// c: first column to consider
+    /// max_r: stop at this row
+    ///
+    /// Return the rank of the submatrix
+    pub fn _mzd_top_echelonize_m4ri(
+        a: *mut Mzd,
+        k: libc::c_int,
+        r: Rci,
+        c: Rci,
+        max_r: Rci,
+    ) -> Rci;
+
     /// Invert the matrix using Konrod's method
     ///
     /// dst: Matrix to hold the inverse (may be Null)
@@ -222,4 +258,19 @@ extern "C" {
     /// b: input matrix
     /// k: M4RI parameter, may be 0 for automatic choice
     pub fn mzd_addmul_m4rm(c: *mut Mzd, a: *const Mzd, b: *const Mzd, k: libc::c_int) -> *mut Mzd;
+
+    /// Matrix multiplication using Konrods Method without any checks
+    ///
+    /// c: preallocated product matrix
+    /// a: input matrix
+    /// b: input matrix
+    /// k: M4RI parameter, may be 0 for automatic choice
+    /// clear: whether to clear C before accumulating AB
+    pub fn _mzd_mul_m4rm(
+        c: *mut Mzd,
+        a: *const Mzd,
+        b: *const Mzd,
+        k: libc::c_int,
+        clear: libc::c_int,
+    ) -> *mut Mzd;
 }
@@ -0,0 +1,30 @@
+/*
+ * Replacement for m4ri_die in the vendored build
+ *
+ * The build script compiles M4RI with -Dm4ri_die=m4ri_sys_die, so fatal
+ * errors end up here instead of in the aborting original in misc.c.
+ * If a hook was installed, the formatted message is handed to it.
+ */
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+typedef void (*m4ri_sys_error_hook_t)(const char *message);
+
+static m4ri_sys_error_hook_t m4ri_sys_error_hook = NULL;
+
+void m4ri_sys_set_error_hook(m4ri_sys_error_hook_t hook) { m4ri_sys_error_hook = hook; }
+
+void m4ri_sys_die(const char *errormessage, ...) {
+  char message[1024];
+  va_list args;
+  va_start(args, errormessage);
+  vsnprintf(message, sizeof(message), errormessage, args);
+  va_end(args);
+  if (m4ri_sys_error_hook != NULL) {
+    /* The hook panics, so this does not return */
+    m4ri_sys_error_hook(message);
+  }
+  fputs(message, stderr);
+  abort();
+}
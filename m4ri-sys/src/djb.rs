@@ -15,7 +15,7 @@ pub enum Srctyp {
     SourceSource,
 }
 
-extern "C" {
+extern "C-unwind" {
     /// Allocate a new DJB linear map
     ///
     /// nrows: number of rows
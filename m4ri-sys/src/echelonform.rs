@@ -1,10 +1,9 @@
 //! Links to `echelonform.h`
-use libc;
 
 use crate::misc::Rci;
 use crate::mzd::Mzd;
 
-extern "C" {
+extern "C-unwind" {
 
     /// (Reduced) row echelon form
     ///
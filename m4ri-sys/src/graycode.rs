@@ -1,15 +1,16 @@
 //! Links to `graycode.h`
-use libc;
 
 #[repr(C)]
 pub struct Code {
     /// Array of Gray code entries
-    ord: *mut libc::c_int,
+    pub ord: *mut libc::c_int,
     /// Increment
-    inc: *mut libc::c_int,
+    pub inc: *mut libc::c_int,
 }
 
-extern "C" {
+extern "C-unwind" {
+    /// Global code book, indexed by the length of the Gray code
+    pub static mut m4ri_codebook: *mut *mut Code;
 
     /// Returns the ith Gray code entry for a gray code of length 2^l
     ///
@@ -0,0 +1,77 @@
+//! Links to `io.h`
+//!
+//! The PNG functions are only available if M4RI was built with libpng,
+//! which is enabled with the `png` feature.
+
+use crate::misc::Rci;
+use crate::mzd::Mzd;
+
+extern "C-unwind" {
+    /// Print the dimensions, density and hash of A to stdout
+    ///
+    /// If do_rank is set, the rank is computed (on a copy) and printed as well.
+    pub fn mzd_info(a: *const Mzd, do_rank: libc::c_int);
+
+    /// Read a matrix from a 1-bit PNG image
+    ///
+    /// fn: filename
+    /// verbose: print an error message on failure
+    ///
+    /// Returns null on failure
+    #[cfg(feature = "png")]
+    pub fn mzd_from_png(filename: *const libc::c_char, verbose: libc::c_int) -> *mut Mzd;
+
+    /// Write the matrix A to a 1-bit PNG image
+    ///
+    /// A: Matrix
+    /// fn: filename
+    /// compression_level: zlib compression level (0-9)
+    /// comment: optional comment to add to the image, may be null
+    /// verbose: print an error message on failure
+    ///
+    /// Returns 0 on success
+    #[cfg(feature = "png")]
+    pub fn mzd_to_png(
+        a: *const Mzd,
+        filename: *const libc::c_char,
+        compression_level: libc::c_int,
+        comment: *const libc::c_char,
+        verbose: libc::c_int,
+    ) -> libc::c_int;
+
+    /// Read a matrix stored in Jean-Charles Faugère's sparse format
+    ///
+    /// fn: filename
+    /// verbose: print an error message on failure
+    ///
+    /// Returns null on failure
+    pub fn mzd_from_jcf(filename: *const libc::c_char, verbose: libc::c_int) -> *mut Mzd;
+
+    /// Create an m x n matrix from a string of '0' and '1' characters
+    ///
+    /// The string is read in row-major order and must have length m * n.
+    pub fn mzd_from_str(m: Rci, n: Rci, str: *const libc::c_char) -> *mut Mzd;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mzd::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_mzd_from_str() {
+        let s = CString::new("101001").unwrap();
+        unsafe {
+            let m = mzd_from_str(2, 3, s.as_ptr());
+            assert_eq!((*m).nrows, 2);
+            assert_eq!((*m).ncols, 3);
+            let bits: Vec<_> = (0..2)
+                .flat_map(|i| (0..3).map(move |j| (i, j)))
+                .map(|(i, j)| mzd_read_bit(m, i, j))
+                .collect();
+            assert_eq!(bits, vec![1, 0, 1, 0, 0, 1]);
+            mzd_free(m);
+        }
+    }
+}
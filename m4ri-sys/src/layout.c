@@ -0,0 +1,34 @@
+/*
+ * Exports the layout of the M4RI structs as seen by the C compiler,
+ * such that the tests can compare them with the Rust definitions.
+ */
+#include <stddef.h>
+#include <m4ri/m4ri.h>
+
+const size_t m4ri_sys_mzd_layout[] = {
+    sizeof(mzd_t),
+    offsetof(mzd_t, nrows),
+    offsetof(mzd_t, ncols),
+    offsetof(mzd_t, width),
+    offsetof(mzd_t, rowstride),
+    offsetof(mzd_t, offset_vector),
+    offsetof(mzd_t, row_offset),
+    offsetof(mzd_t, flags),
+    offsetof(mzd_t, blockrows_log),
+    offsetof(mzd_t, high_bitmask),
+    offsetof(mzd_t, blocks),
+    offsetof(mzd_t, rows),
+};
+
+const size_t m4ri_sys_mzd_block_layout[] = {
+    sizeof(mzd_block_t),
+    offsetof(mzd_block_t, size),
+    offsetof(mzd_block_t, begin),
+    offsetof(mzd_block_t, end),
+};
+
+const size_t m4ri_sys_mzp_layout[] = {
+    sizeof(mzp_t),
+    offsetof(mzp_t, values),
+    offsetof(mzp_t, length),
+};
@@ -0,0 +1,100 @@
+//! Checks the hand-written struct definitions against the C compiler
+//!
+//! The offsets are exported by `layout.c`, which is compiled by the build script
+//! against the same headers as the library.
+
+use std::mem::{offset_of, size_of};
+
+use crate::mzd::{Mzd, MzdBlock};
+use crate::mzp::Mzp;
+
+extern "C" {
+    static m4ri_sys_mzd_layout: [libc::size_t; 12];
+    static m4ri_sys_mzd_block_layout: [libc::size_t; 4];
+    static m4ri_sys_mzp_layout: [libc::size_t; 3];
+}
+
+#[test]
+fn test_mzd_layout() {
+    let c = unsafe { m4ri_sys_mzd_layout };
+    let rust = [
+        size_of::<Mzd>(),
+        offset_of!(Mzd, nrows),
+        offset_of!(Mzd, ncols),
+        offset_of!(Mzd, width),
+        offset_of!(Mzd, rowstride),
+        offset_of!(Mzd, offset_vector),
+        offset_of!(Mzd, row_offset),
+        offset_of!(Mzd, flags),
+        offset_of!(Mzd, blockrows_log),
+        offset_of!(Mzd, high_bitmask),
+        offset_of!(Mzd, blocks),
+        offset_of!(Mzd, rows),
+    ];
+    assert_eq!(rust, c);
+}
+
+#[test]
+fn test_mzd_block_layout() {
+    let c = unsafe { m4ri_sys_mzd_block_layout };
+    let rust = [
+        size_of::<MzdBlock>(),
+        offset_of!(MzdBlock, size),
+        offset_of!(MzdBlock, begin),
+        offset_of!(MzdBlock, end),
+    ];
+    assert_eq!(rust, c);
+}
+
+#[test]
+fn test_mzp_layout() {
+    let c = unsafe { m4ri_sys_mzp_layout };
+    let rust = [
+        size_of::<Mzp>(),
+        offset_of!(Mzp, values),
+        offset_of!(Mzp, length),
+    ];
+    assert_eq!(rust, c);
+}
+
+/// The size and field offsets of a struct, for comparing it with its generated binding
+#[cfg(feature = "bindgen")]
+macro_rules! layout {
+    ($type:ty, $($field:ident),*) => {
+        [size_of::<$type>(), $(offset_of!($type, $field)),*]
+    };
+}
+
+#[cfg(feature = "bindgen")]
+#[test]
+fn test_matches_bindgen() {
+    use crate::bindings;
+
+    macro_rules! mzd_layout {
+        ($type:ty) => {
+            layout!(
+                $type,
+                nrows,
+                ncols,
+                width,
+                rowstride,
+                offset_vector,
+                row_offset,
+                flags,
+                blockrows_log,
+                high_bitmask,
+                blocks,
+                rows
+            )
+        };
+    }
+    assert_eq!(mzd_layout!(Mzd), mzd_layout!(bindings::mzd_t));
+    assert_eq!(
+        layout!(MzdBlock, size, begin, end),
+        layout!(bindings::mzd_block_t, size, begin, end)
+    );
+    assert_eq!(
+        layout!(Mzp, values, length),
+        layout!(bindings::mzp_t, values, length)
+    );
+}
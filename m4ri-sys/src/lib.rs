@@ -1,28 +1,60 @@
 //! Implements the FFI to M4RI
 //!
 //! See https://bitbucket.org/malb/m4ri
+//!
+//! # Safety
+//! All functions take raw pointers to M4RI structures. Unless documented otherwise,
+//! these need to be valid, initialised matrices or permutations of matching dimensions,
+//! and positions need to be within bounds, as in the C library.
 mod brilliantrussian;
 mod djb;
 mod echelonform;
 mod graycode;
+mod io;
 mod misc;
+mod mmc;
 mod mp;
 mod mzd;
 mod mzp;
 mod ple;
+mod ple_russian;
 mod solve;
 mod strassen;
+mod triangular;
+mod triangular_russian;
+
+#[cfg(test)]
+mod layout;
+
+/// Bindings generated from the vendored M4RI headers by bindgen
+///
+/// Only available with the `bindgen` feature. The hand-written bindings
+/// in the crate root are checked against these in the tests.
+#[cfg(feature = "bindgen")]
+#[allow(
+    non_upper_case_globals,
+    non_camel_case_types,
+    non_snake_case,
+    dead_code,
+    clippy::all
+)]
+pub mod bindings {
+    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+}
 
 pub use self::brilliantrussian::*;
 pub use self::djb::*;
 pub use self::echelonform::*;
 pub use self::graycode::*;
-pub use self::misc::Rci;
-pub use self::misc::Word;
-pub use self::misc::BIT;
+pub use self::io::*;
+pub use self::misc::*;
+pub use self::mmc::*;
 pub use self::mp::*;
 pub use self::mzd::*;
 pub use self::mzp::*;
 pub use self::ple::*;
+pub use self::ple_russian::*;
 pub use self::solve::*;
 pub use self::strassen::*;
+pub use self::triangular::*;
+pub use self::triangular_russian::*;
@@ -1,5 +1,4 @@
 //! Links to m4ri/misc.h
-use libc;
 
 /// M4RI Internal representation
 pub type Rci = libc::c_int;
@@ -24,3 +23,134 @@ pub static m4ri_one: Word = 1u64;
 /// A word with all bits set
 #[allow(non_upper_case_globals, dead_code)]
 pub static m4ri_ffff: Word = 0xffff_ffff_ffff_ffff;
+
+/// Callback used to obtain random words, see `mzd_randomize_custom`
+pub type M4riRandomCallback = Option<unsafe extern "C" fn(data: *mut libc::c_void) -> Word>;
+
+extern "C-unwind" {
+    /// Print an error message and abort
+    ///
+    /// The message is a printf-style format string.
+    /// This is the original function from `misc.c`, which always aborts, also
+    /// after `m4ri_install_error_hook`: the vendored library itself is built to
+    /// call a replacement that can panic instead.
+    pub fn m4ri_die(errormessage: *const libc::c_char, ...);
+
+    /// Write a string representation of the word data to destination
+    ///
+    /// destination: preallocated buffer of at least 64 + 64/4 + 1 characters
+    /// data: the word to print
+    /// colon: insert a colon after every 4-th bit
+    pub fn m4ri_word_to_str(destination: *mut libc::c_char, data: Word, colon: libc::c_int);
+
+    /// Return a uniformly distributed random word
+    pub fn m4ri_random_word() -> Word;
+
+    /// Initialize the global data structures of the library
+    ///
+    /// This is called automatically when the library is loaded,
+    /// but calling it twice does no harm.
+    pub fn m4ri_init();
+
+    /// Free the global data structures of the library
+    ///
+    /// This is called automatically when the library is unloaded.
+    pub fn m4ri_fini();
+}
+
+#[cfg(m4ri_die_hook)]
+extern "C-unwind" {
+    fn m4ri_sys_set_error_hook(hook: extern "C-unwind" fn(*const libc::c_char));
+}
+
+/// Panics with the message passed to `m4ri_die`
+#[cfg(m4ri_die_hook)]
+extern "C-unwind" fn panic_on_error(message: *const libc::c_char) {
+    let message = unsafe { std::ffi::CStr::from_ptr(message) }.to_string_lossy();
+    panic!("M4RI error: {}", message.trim_end());
+}
+
+#[cfg(m4ri_die_hook)]
+static INSTALL: std::sync::Once = std::sync::Once::new();
+
+/// Make fatal M4RI errors panic instead of aborting the process
+///
+/// M4RI reports invalid arguments and failed allocations through `m4ri_die`.
+/// After calling this, the vendored build panics with the error message instead,
+/// so the error can be caught with `std::panic::catch_unwind`.
+/// The panic unwinds through M4RI, leaking whatever it allocated at that point.
+/// Panics inside OpenMP parallel regions still abort.
+/// The panic hook still runs, so the message is printed unless it is replaced.
+///
+/// The hook is global to the process and can't be removed again,
+/// so only the application should install it, never a library.
+///
+/// Returns `false` if M4RI was not vendored, as the `m4ri_die` of an
+/// installed library can't be replaced.
+pub fn m4ri_install_error_hook() -> bool {
+    #[cfg(m4ri_die_hook)]
+    INSTALL.call_once(|| unsafe { m4ri_sys_set_error_hook(panic_on_error) });
+    cfg!(m4ri_die_hook)
+}
+
+/// Check whether `m4ri_install_error_hook` was called and succeeded
+pub fn m4ri_error_hook_installed() -> bool {
+    #[cfg(m4ri_die_hook)]
+    return INSTALL.is_completed();
+    #[cfg(not(m4ri_die_hook))]
+    return false;
+}
+
+/// Check whether the payload of a panic caught from M4RI reports a failed allocation
+///
+/// This is the case if M4RI's own allocation functions ran out of memory
+/// while the error hook was installed.
+pub fn m4ri_is_allocation_failure(payload: &(dyn std::any::Any + Send)) -> bool {
+    let message = match payload.downcast_ref::<String>() {
+        Some(message) => message,
+        None => return false,
+    };
+    message.starts_with("M4RI error: m4ri_mm_") && message.ends_with("returned NULL")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mzd::{mzd_free, mzd_init};
+    use crate::strassen::mzd_mul;
+    use std::panic;
+    use std::ptr;
+
+    #[test]
+    fn error_hook() {
+        if !m4ri_install_error_hook() {
+            return;
+        }
+        unsafe {
+            let a = mzd_init(2, 3);
+            let b = mzd_init(2, 3);
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                mzd_mul(ptr::null_mut(), a, b, 0);
+            }));
+            let message = result.unwrap_err().downcast::<String>().unwrap();
+            assert!(message.starts_with("M4RI error: mzd_mul"), "{}", message);
+            assert!(!m4ri_is_allocation_failure(&message));
+            assert!(m4ri_error_hook_installed());
+            mzd_free(a);
+            mzd_free(b);
+        }
+    }
+
+    #[test]
+    fn allocation_failure_messages() {
+        let failure = String::from("M4RI error: m4ri_mm_calloc: calloc returned NULL");
+        assert!(m4ri_is_allocation_failure(&failure));
+        let failure = String::from("M4RI error: m4ri_mm_malloc: malloc returned NULL");
+        assert!(m4ri_is_allocation_failure(&failure));
+        let other = String::from("M4RI error: mzd_mul: C (0 x 0) has wrong dimensions");
+        assert!(!m4ri_is_allocation_failure(&other));
+        assert!(!m4ri_is_allocation_failure(
+            &"m4ri_mm_calloc: calloc returned NULL"
+        ));
+    }
+}
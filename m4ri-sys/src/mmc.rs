@@ -0,0 +1,13 @@
+//! Links to `mmc.h`, the memory manager cache of M4RI
+
+
+extern "C-unwind" {
+    /// Allocate size bytes, using the cache if possible
+    pub fn m4ri_mmc_malloc(size: libc::size_t) -> *mut libc::c_void;
+
+    /// Free the memory at condemned of size bytes, keeping it in the cache if possible
+    pub fn m4ri_mmc_free(condemned: *mut libc::c_void, size: libc::size_t);
+
+    /// Free all memory held by the cache
+    pub fn m4ri_mmc_cleanup();
+}
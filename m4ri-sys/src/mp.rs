@@ -1,8 +1,9 @@
 //! Corresponds to `mp.h`
+//!
+//! The functions are only compiled into M4RI if it was built with OpenMP.
 use crate::mzd::Mzd;
-use libc;
 
-extern "C" {
+extern "C-unwind" {
     /// Matrix multiplication via the cubic multiplication algorithm on multiple cores
     ///
     /// C: Preallocated product matrix, may be NULL for automatic creation
@@ -25,3 +26,16 @@ extern "C" {
         cutoff: libc::c_int,
     ) -> *mut Mzd;
 }
+
+#[cfg(feature = "openmp")]
+extern "C-unwind" {
+    /// Set the number of threads OpenMP uses for subsequent parallel regions
+    ///
+    /// Part of the OpenMP runtime, only available with the `openmp` feature.
+    pub fn omp_set_num_threads(num_threads: libc::c_int);
+
+    /// Return the maximum number of threads OpenMP uses for a parallel region
+    ///
+    /// Part of the OpenMP runtime, only available with the `openmp` feature.
+    pub fn omp_get_max_threads() -> libc::c_int;
+}
@@ -1,12 +1,13 @@
 //! Links to mzd.h
 //!
-//! FIXME implement missing functions
+//! Functions that are declared `static inline` in the header are reimplemented in Rust.
 //!
-use libc;
 use std::mem::size_of;
 
+use crate::misc::m4ri_ffff;
 use crate::misc::m4ri_one;
 use crate::misc::m4ri_radix;
+use crate::misc::M4riRandomCallback;
 use crate::misc::Rci;
 use crate::misc::Wi;
 use crate::misc::Word;
@@ -14,10 +15,13 @@ use crate::misc::BIT;
 
 /// Represents the blocks used by M4RI internally
 #[repr(C)]
-struct MzdBlock {
-    size: libc::size_t,
-    begin: *mut Word,
-    end: *mut Word,
+pub struct MzdBlock {
+    /// Size of the block in bytes
+    pub size: libc::size_t,
+    /// Pointer to the first word of the block
+    pub begin: *mut Word,
+    /// Pointer past the last word of the block
+    pub end: *mut Word,
 }
 
 /// Represents the Mzd data type used by M4RI
@@ -36,16 +40,16 @@ pub struct Mzd {
     /// rowstride = (width < mzd_paddingwidth || (width & 1) == 0) ? width : width + 1;
     /// ``
     /// where width is the width of the underlying non-windowed matrix
-    rowstride: Wi,
+    pub rowstride: Wi,
 
     /// Offset in words from start of block to first word
     ///
     /// ``rows[0] = blocks[0].begin + offset_vector``
-    offset_vector: Wi,
+    pub offset_vector: Wi,
 
     /// Number of rows to the first row counting from the start of the
     /// first block
-    row_offset: Wi,
+    pub row_offset: Wi,
 
     /// Booleans to speed up things
     ///
@@ -56,12 +60,12 @@ pub struct Mzd {
     /// 3. Is windowed, but has zero excess
     /// 4. Is windowed, but owns the Blocks allocations
     /// 5. Spans more than 1 Block
-    flags: u8,
+    pub flags: u8,
 
     /// blockrows_log = log2(blockrows)
     /// where blockrows is the number of rows in one block,
     /// which is a power of 2.
-    blockrows_log: u8,
+    pub blockrows_log: u8,
 
     // Ensures sizeof(mzd_t) == 64
     padding: [u8; 62
@@ -71,13 +75,16 @@ pub struct Mzd {
         - 2 * size_of::<*const libc::c_void>()],
 
     /// Mask for valid bits in the word with the highest index (width - 1)
-    high_bitmask: Word,
+    pub high_bitmask: Word,
     /// Pointers to the actual blocks of memory containing the values packed into words
-    blocks: *const MzdBlock,
+    pub blocks: *const MzdBlock,
     /// Address of first word in each row, so the first word of `row [i]` is in `m->rows[i]`
     pub rows: *const *mut Word,
 }
 
+// The padding keeps the struct at one cache line on 32-bit targets as well
+const _: () = assert!(size_of::<Mzd>() == 64);
+
 /// Flag when `ncols%64 == 0`
 pub static MZD_FLAG_NONZERO_EXCESS: u8 = 0x2;
 
@@ -93,7 +100,7 @@ pub static MZD_FLAG_WINDOWED_OWNSBLOCKS: u8 = 0x10;
 /// Flag for multiple blocks
 pub static MZD_FLAG_MULTIPLE_BLOCKS: u8 = 0x20;
 
-extern "C" {
+extern "C-unwind" {
     /// Create a new rows x columns matrix
     pub fn mzd_init(rows: Rci, columns: Rci) -> *mut Mzd;
 
@@ -126,9 +133,6 @@ extern "C" {
         highc: Rci,
     ) -> *mut Mzd;
 
-    /// Swap the two rows rowa and rowb
-    pub fn mzd_row_swap(matrix: *mut Mzd, rowa: Rci, rowb: Rci);
-
     /// \brief copy row j from A to row i from B.
     ///
     /// The offsets of A and B must match and the number of columns of A
@@ -183,9 +187,37 @@ extern "C" {
     /// Fill the matrix m with uniformly distributed bits.
     pub fn mzd_randomize(m: *mut Mzd);
 
+    /// Fill the matrix m with bits obtained from a custom random source
+    ///
+    /// param M: matrix
+    /// param rc: callback that returns a uniformly random word
+    /// param data: passed to rc on every call
+    pub fn mzd_randomize_custom(m: *mut Mzd, rc: M4riRandomCallback, data: *mut libc::c_void);
+
+    /// Gaussian elimination on the matrix, starting at column startcol
+    ///
+    /// param M: Matrix to be reduced
+    /// param startcol: first column to consider for reduction
+    /// param full: return the reduced row echelon form, not only upper triangular form
+    ///
+    /// Return the rank of M
+    pub fn mzd_gauss_delayed(m: *mut Mzd, startcol: Rci, full: libc::c_int) -> Rci;
+
+    /// Gaussian elimination on the matrix
+    ///
+    /// This is a wrapper around `mzd_gauss_delayed(M, 0, full)`
+    ///
+    /// Return the rank of M
+    pub fn mzd_echelonize_naive(m: *mut Mzd, full: libc::c_int) -> Rci;
+
     /// Return true if A == B
     pub fn mzd_equal(a: *const Mzd, b: *const Mzd) -> libc::c_int;
 
+    /// Return -1, 0 or 1 if A < B, A == B or A > B respectively
+    ///
+    /// The comparison is lexicographic on the rows, which are compared as integers.
+    pub fn mzd_cmp(a: *const Mzd, b: *const Mzd) -> libc::c_int;
+
     /// Copy a matrix to dest
     ///
     /// Dest may be null for automatic creation
@@ -198,7 +230,7 @@ extern "C" {
     pub fn mzd_set_ui(a: *mut Mzd, n: libc::c_uint);
 
     /// Stack A on top of B into C
-    pub fn mzd_stack(c: *mut Mzd, a: *mut Mzd, b: *const Mzd) -> *mut Mzd;
+    pub fn mzd_stack(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd;
 
     /// Copy a submatrix
     /// first argument may be preallocated space or null
@@ -222,6 +254,11 @@ extern "C" {
     /// otherwise a new matrix is created
     pub fn mzd_add(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd;
 
+    /// Same as `mzd_add`, but without any checks on the input.
+    ///
+    /// C must be preallocated.
+    pub fn _mzd_add(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd;
+
     /// Set C = A - B
     /// If C is passed in, the result is written there
     /// otherwise a new matrix is created
@@ -230,7 +267,9 @@ extern "C" {
     pub fn mzd_sub(c: *mut Mzd, a: *const Mzd, b: *const Mzd) -> *mut Mzd;
 
     /// Zero test for matrix
-    pub fn mzd_is_zero(a: *const Mzd);
+    ///
+    /// Return a non-zero value if all entries of A are zero
+    pub fn mzd_is_zero(a: *const Mzd) -> libc::c_int;
 
     /// Clear the given row, but only begins at the column coloffset.
     ///
@@ -239,9 +278,341 @@ extern "C" {
     /// param coloffset Column offset
     pub fn mzd_row_clear_offset(m: *mut Mzd, row: Rci, coloffset: Rci);
 
+    /// Add the row sourcerow to the row destrow
+    ///
+    /// ``M[destrow] = M[destrow] + M[sourcerow]``
+    pub fn mzd_row_add(m: *mut Mzd, sourcerow: Rci, destrow: Rci);
+
+    /// Find the next nonzero entry in M starting at start_row and start_col
+    ///
+    /// This function walks down rows in the inner loop and columns in the
+    /// outer loop. If a nonzero entry is found this function returns 1 and
+    /// zero otherwise.
+    ///
+    /// If and only if a nonzero entry is found r and c are updated.
+    ///
+    /// param M Matrix
+    /// param start_row Index of row where to start search
+    /// param start_col Index of column where to start search
+    /// param r Row index updated if pivot is found
+    /// param c Column index updated if pivot is found
+    pub fn mzd_find_pivot(
+        m: *const Mzd,
+        start_row: Rci,
+        start_col: Rci,
+        r: *mut Rci,
+        c: *mut Rci,
+    ) -> libc::c_int;
+
+    /// Return the number of nonzero entries divided by nrows * ncols
+    ///
+    /// If res = 0 then 100 samples per row are made, if res > 0 the
+    /// function takes res sized steps within each row (res = 1 uses every word).
+    ///
+    /// param A Matrix
+    /// param res Resolution of sampling (in words)
+    pub fn mzd_density(a: *const Mzd, res: Wi) -> libc::c_double;
+
+    /// Return the number of nonzero entries divided by nrows * ncols
+    /// considering only the submatrix starting at (r,c).
+    ///
+    /// If res = 0 then 100 samples per row are made, if res > 0 the
+    /// function takes res sized steps within each row (res = 1 uses every word).
+    ///
+    /// param A Matrix
+    /// param res Resolution of sampling (in words)
+    /// param r Row to start counting
+    /// param c Column to start counting
+    pub fn _mzd_density(a: *const Mzd, res: Wi, r: Rci, c: Rci) -> libc::c_double;
+
+    /// Return the first row with all zero entries.
+    ///
+    /// If no such row can be found returns nrows.
+    pub fn mzd_first_zero_row(a: *const Mzd) -> Rci;
+
+    /// Return upper triangular submatrix of A
+    ///
+    /// param U Matrix to write to, may be null
+    /// param A Matrix to extract from
+    pub fn mzd_extract_u(u: *mut Mzd, a: *const Mzd) -> *mut Mzd;
+
+    /// Return lower triangular submatrix of A
+    ///
+    /// param L Matrix to write to, may be null
+    /// param A Matrix to extract from
+    pub fn mzd_extract_l(l: *mut Mzd, a: *const Mzd) -> *mut Mzd;
+
+    /// Print a matrix to stdout.
+    ///
+    /// The output will contain colons between every 4-th column.
+    pub fn mzd_print(m: *const Mzd);
+
+    /// Print row i of M to stdout.
+    ///
+    /// The output will contain colons between every 4-th column.
+    pub fn mzd_print_row(m: *const Mzd, i: Rci);
+}
+
+/// Bitmask for the `n` lowest bits of a word
+///
+/// Corresponds to `__M4RI_LEFT_BITMASK`, `n` must be in `1..=64`.
+#[inline]
+pub fn m4ri_left_bitmask(n: libc::c_int) -> Word {
+    m4ri_ffff >> ((m4ri_radix - n) % m4ri_radix)
+}
+
+/// Bitmask for the `n` highest bits of a word
+///
+/// Corresponds to `__M4RI_RIGHT_BITMASK`, `n` must be in `1..=64`.
+#[inline]
+pub fn m4ri_right_bitmask(n: libc::c_int) -> Word {
+    m4ri_ffff << (m4ri_radix - n)
+}
+
+/// Swap the two rows rowa and rowb starting at startblock
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `m` must be a valid matrix and `rowa` and `rowb` must be rows of it.
+#[inline]
+pub unsafe fn _mzd_row_swap(m: *mut Mzd, rowa: Rci, rowb: Rci, startblock: Wi) {
+    let width = (*m).width;
+    if rowa == rowb || startblock >= width {
+        return;
+    }
+    let a = *(*m).rows.add(rowa as usize);
+    let b = *(*m).rows.add(rowb as usize);
+    for i in startblock..(width - 1) {
+        std::ptr::swap(a.add(i as usize), b.add(i as usize));
+    }
+    let last = (width - 1) as usize;
+    let tmp = (*a.add(last) ^ *b.add(last)) & (*m).high_bitmask;
+    *a.add(last) ^= tmp;
+    *b.add(last) ^= tmp;
+}
+
+/// Swap the two rows rowa and rowb
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `m` must be a valid matrix and `rowa` and `rowb` must be rows of it.
+#[inline]
+pub unsafe fn mzd_row_swap(m: *mut Mzd, rowa: Rci, rowb: Rci) {
+    _mzd_row_swap(m, rowa, rowb, 0)
+}
+
+/// Swap the two columns cola and colb but only between start_row and stop_row
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `m` must be a valid matrix, `cola` and `colb` must be columns of it and
+/// `start_row..stop_row` must be a range of its rows.
+#[inline]
+pub unsafe fn mzd_col_swap_in_rows(
+    m: *mut Mzd,
+    cola: Rci,
+    colb: Rci,
+    start_row: Rci,
+    stop_row: Rci,
+) {
+    if cola == colb {
+        return;
+    }
+    for row in start_row..stop_row {
+        let a = mzd_read_bit(m, row, cola);
+        let b = mzd_read_bit(m, row, colb);
+        mzd_write_bit(m, row, cola, b);
+        mzd_write_bit(m, row, colb, a);
+    }
+}
+
+/// Get n bits starting at position (x,y) from the matrix M.
+///
+/// The first bit is stored at the least significant position of the result.
+/// n must be at most m4ri_radix, and y + n must not exceed the number of columns.
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `m` must be a valid matrix, `x` must be a row of it, `n` must be in `1..=64`
+/// and `y + n` must not exceed the number of columns.
+#[inline]
+pub unsafe fn mzd_read_bits(m: *const Mzd, x: Rci, y: Rci, n: libc::c_int) -> Word {
+    debug_assert!(n > 0 && n <= m4ri_radix);
+    let spot = y % m4ri_radix;
+    let block = (y / m4ri_radix) as usize;
+    let row: *const Word = *(*m).rows.add(x as usize);
+    let mut temp = *row.add(block) >> spot;
+    if spot + n > m4ri_radix {
+        temp |= *row.add(block + 1) << (m4ri_radix - spot);
+    }
+    temp & m4ri_left_bitmask(n)
+}
+
+/// Get n bits starting at position (x,y) from the matrix M as an integer
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// The same as for `mzd_read_bits`.
+#[inline]
+pub unsafe fn mzd_read_bits_int(m: *const Mzd, x: Rci, y: Rci, n: libc::c_int) -> libc::c_int {
+    mzd_read_bits(m, x, y, n) as libc::c_int
+}
+
+/// XOR n bits from values to M starting at position (x,y).
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `m` must be a valid matrix that is not accessed through any other pointer
+/// during the call, `x` must be a row of it, `n` must be in `1..=64` and
+/// `y + n` must not exceed the number of columns. `values` must not have bits set
+/// above the lowest `n`, or the padding after the last column is changed.
+#[inline]
+pub unsafe fn mzd_xor_bits(m: *const Mzd, x: Rci, y: Rci, n: libc::c_int, values: Word) {
+    let spot = y % m4ri_radix;
+    let block = (y / m4ri_radix) as usize;
+    let row: *mut Word = *(*m).rows.add(x as usize);
+    *row.add(block) ^= values << spot;
+    let space = m4ri_radix - spot;
+    if n > space {
+        *row.add(block + 1) ^= values >> space;
+    }
+}
+
+/// AND n bits from values to M starting at position (x,y).
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `m` must be a valid matrix that is not accessed through any other pointer
+/// during the call, `x` must be a row of it, `n` must be in `1..=64` and
+/// `y + n` must not exceed the number of columns.
+#[inline]
+pub unsafe fn mzd_and_bits(m: *const Mzd, x: Rci, y: Rci, n: libc::c_int, values: Word) {
+    let spot = y % m4ri_radix;
+    let block = (y / m4ri_radix) as usize;
+    let row: *mut Word = *(*m).rows.add(x as usize);
+    *row.add(block) &= values << spot;
+    let space = m4ri_radix - spot;
+    if n > space {
+        *row.add(block + 1) &= values >> space;
+    }
+}
+
+/// Clear n bits in M starting at position (x,y).
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `m` must be a valid matrix that is not accessed through any other pointer
+/// during the call, `x` must be a row of it, `n` must be in `1..=64` and
+/// `y + n` must not exceed the number of columns.
+#[inline]
+pub unsafe fn mzd_clear_bits(m: *const Mzd, x: Rci, y: Rci, n: libc::c_int) {
+    let values = m4ri_left_bitmask(n);
+    let spot = y % m4ri_radix;
+    let block = (y / m4ri_radix) as usize;
+    let row: *mut Word = *(*m).rows.add(x as usize);
+    *row.add(block) &= !(values << spot);
+    let space = m4ri_radix - spot;
+    if n > space {
+        *row.add(block + 1) &= !(values >> space);
+    }
+}
+
+/// Add the row srcrow to the row dstrow, starting at column coloffset
+///
+/// ``M[dstrow][coloffset:] += M[srcrow][coloffset:]``
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `m` must be a valid matrix, `dstrow` and `srcrow` must be rows of it
+/// and `coloffset` must be a column of it.
+#[inline]
+pub unsafe fn mzd_row_add_offset(m: *mut Mzd, dstrow: Rci, srcrow: Rci, coloffset: Rci) {
+    let startblock = coloffset / m4ri_radix;
+    let width = (*m).width;
+    let src: *const Word = *(*m).rows.add(srcrow as usize);
+    let dst: *mut Word = *(*m).rows.add(dstrow as usize);
+    for i in startblock..width {
+        let mut mask = m4ri_ffff;
+        if i == startblock {
+            mask &= m4ri_right_bitmask(m4ri_radix - coloffset % m4ri_radix);
+        }
+        if i == width - 1 {
+            mask &= (*m).high_bitmask;
+        }
+        *dst.add(i as usize) ^= *src.add(i as usize) & mask;
+    }
+}
+
+/// ``C[c_row][c_startblock:] = A[a_row][a_startblock:] + B[b_row][b_startblock:]``
+///
+/// The number of words processed is determined by the width of C.
+/// C may be equal to A.
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `a`, `b` and `c` must be valid matrices with the given rows. Starting at the
+/// given blocks, the rows of `a` and `b` must have at least as many words as the
+/// row of `c`. `c` may be `a` or `b`, but must not overlap them otherwise.
+#[allow(clippy::too_many_arguments)]
+#[inline]
+pub unsafe fn mzd_combine(
+    c: *mut Mzd,
+    c_row: Rci,
+    c_startblock: Wi,
+    a: *const Mzd,
+    a_row: Rci,
+    a_startblock: Wi,
+    b: *const Mzd,
+    b_row: Rci,
+    b_startblock: Wi,
+) {
+    let wide = (*c).width - c_startblock;
+    if wide <= 0 {
+        return;
+    }
+    let c_words: *mut Word = (*(*c).rows.add(c_row as usize)).add(c_startblock as usize);
+    let a_words: *const Word = (*(*a).rows.add(a_row as usize)).add(a_startblock as usize);
+    let b_words: *const Word = (*(*b).rows.add(b_row as usize)).add(b_startblock as usize);
+    for i in 0..(wide - 1) as usize {
+        *c_words.add(i) = *a_words.add(i) ^ *b_words.add(i);
+    }
+    let last = (wide - 1) as usize;
+    let value = *a_words.add(last) ^ *b_words.add(last);
+    *c_words.add(last) ^= (value ^ *c_words.add(last)) & (*c).high_bitmask;
+}
+
+/// Compute a hash value of the matrix
+///
+/// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `a` must be a valid matrix.
+#[inline]
+pub unsafe fn mzd_hash(a: *const Mzd) -> Word {
+    let mut hash: Word = 0;
+    for r in 0..(*a).nrows {
+        let row: *const Word = *(*a).rows.add(r as usize);
+        let row_hash = (0..(*a).width as usize).fold(0, |acc, i| acc ^ *row.add(i));
+        hash ^= row_hash.rotate_left((r % m4ri_radix) as u32);
+    }
+    hash
 }
 
 /// Write the bit to position M[row, col]
+///
+/// # Safety
+/// `matrix` must be a valid matrix, `row` and `col` must be in bounds, and
+/// `value` must be 0 or 1. There is no bounds checking.
 #[inline]
 pub unsafe fn mzd_write_bit(matrix: *mut Mzd, row: Rci, col: Rci, value: BIT) {
     let therow: *const *mut Word = (*matrix).rows.offset(row as isize);
@@ -255,10 +626,10 @@ pub unsafe fn mzd_write_bit(matrix: *mut Mzd, row: Rci, col: Rci, value: BIT) {
 
 /// Read the bit at position M[row, col]
 ///
-/// # Unsafe behaviour
-/// No bounds checking
-///
 /// Reimplemented in Rust as the C library declares it as inline
+///
+/// # Safety
+/// `matrix` must be a valid matrix and `row` and `col` must be in bounds.
 #[inline]
 pub unsafe fn mzd_read_bit(matrix: *const Mzd, row: Rci, col: Rci) -> BIT {
     let therow: *const *mut Word = (*matrix).rows.offset(row as isize);
@@ -273,6 +644,9 @@ pub unsafe fn mzd_read_bit(matrix: *const Mzd, row: Rci, col: Rci) -> BIT {
 /// param: M: matrix
 ///
 /// Return a pointer to the first word of the first row
+///
+/// # Safety
+/// `matrix` must be a valid matrix. The result is only valid while it is.
 #[inline]
 pub unsafe fn mzd_first_row(matrix: *const Mzd) -> *mut Word {
     let result: *mut Word = (*(*matrix).blocks)
@@ -285,10 +659,27 @@ pub unsafe fn mzd_first_row(matrix: *const Mzd) -> *mut Word {
     result
 }
 
+/// Get a const pointer to the first word of a row
+///
+/// Param M Matrix
+/// Param row the row index
+///
+/// # Safety
+/// `matrix` must be a valid matrix and `row` must be in bounds.
+/// The result is only valid while the matrix is.
+#[inline]
+pub unsafe fn mzd_row_const(matrix: *const Mzd, row: Rci) -> *const Word {
+    mzd_row(matrix, row)
+}
+
 /// Get pointer to first word of row
 ///
 /// Param M Matrix
 /// Param row the row index
+///
+/// # Safety
+/// `matrix` must be a valid matrix and `row` must be in bounds. The result is only
+/// valid while the matrix is, and must not be written to if `matrix` may not be modified.
 #[inline]
 pub unsafe fn mzd_row(matrix: *const Mzd, row: Rci) -> *mut Word {
     debug_assert!(row >= 0);
@@ -320,6 +711,11 @@ pub unsafe fn mzd_row(matrix: *const Mzd, row: Rci) -> *mut Word {
 /// Also, this function copies a bunch of references
 /// As a result, you could get multiple mut references into the same memory.
 /// This is very unsafe if not used properly.
+///
+/// # Safety
+/// `matrix` must be a valid matrix that outlives the window, and the bounds must
+/// lie within it. The window must not be written to, and must be freed with
+/// `mzd_free_window_const`.
 pub unsafe fn mzd_init_window_const(
     matrix: *const Mzd,
     lowr: Rci,
@@ -327,18 +723,24 @@ pub unsafe fn mzd_init_window_const(
     highr: Rci,
     highc: Rci,
 ) -> *const Mzd {
-    mzd_init_window(std::mem::transmute(matrix), lowr, lowc, highr, highc)
+    mzd_init_window(matrix as *mut Mzd, lowr, lowc, highr, highc)
 }
 
 /// Test if a matrix is windowed
 ///
 /// return a non-zero value if the matrix is windowed, otherwise return zero
+///
+/// # Safety
+/// `m` must be a valid matrix.
 #[inline]
 pub unsafe fn mzd_is_windowed(m: *const Mzd) -> u8 {
     (*m).flags & MZD_FLAG_WINDOWED_ZEROOFFSET
 }
 
 /// Test if this mzd_t should free blocks
+///
+/// # Safety
+/// `m` must be a valid matrix.
 #[inline]
 pub unsafe fn mzd_owns_blocks(m: *const Mzd) -> bool {
     !(*m).blocks.is_null()
@@ -357,6 +759,10 @@ impl Drop for Mzd {
 /// Free a matrix window created with mzd_init_window
 ///
 /// This is actually just `mzd_free` so call `ptr::drop_in_place` instead
+///
+/// # Safety
+/// `matrix` must be a window created with `mzd_init_window` that has not
+/// been freed yet, and must not be used afterwards.
 #[inline]
 pub unsafe fn mzd_free_window(matrix: *mut Mzd) {
     std::ptr::drop_in_place(matrix)
@@ -365,9 +771,13 @@ pub unsafe fn mzd_free_window(matrix: *mut Mzd) {
 /// Free a "const" window created with `mzd_init_window_const`.
 ///
 /// This function *MUST* be called for const windows.
+///
+/// # Safety
+/// `matrix` must be a window created with `mzd_init_window_const` that has not
+/// been freed yet, and must not be used afterwards.
 #[inline]
 pub unsafe fn mzd_free_window_const(matrix: *const Mzd) {
-    let matrix: *mut Mzd = std::mem::transmute(matrix);
+    let matrix = matrix as *mut Mzd;
     std::ptr::drop_in_place(matrix)
 }
 
@@ -421,6 +831,95 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_mzd_is_zero() {
+        unsafe {
+            let matrix = mzd_init(10, 70);
+            assert_ne!(mzd_is_zero(matrix), 0);
+            assert_eq!(mzd_first_zero_row(matrix), 0);
+            mzd_write_bit(matrix, 3, 69, 1);
+            assert_eq!(mzd_is_zero(matrix), 0);
+            let (mut r, mut c) = (0, 0);
+            assert_eq!(mzd_find_pivot(matrix, 0, 0, &mut r, &mut c), 1);
+            assert_eq!((r, c), (3, 69));
+            ptr::drop_in_place(matrix);
+        }
+    }
+
+    #[test]
+    fn test_mzd_row_ops() {
+        unsafe {
+            let matrix = mzd_init(4, 130);
+            mzd_randomize(matrix);
+            let orig = mzd_copy(ptr::null_mut(), matrix);
+            mzd_row_swap(matrix, 0, 3);
+            for j in 0..130 {
+                assert_eq!(mzd_read_bit(matrix, 0, j), mzd_read_bit(orig, 3, j));
+                assert_eq!(mzd_read_bit(matrix, 3, j), mzd_read_bit(orig, 0, j));
+            }
+            mzd_row_swap(matrix, 0, 3);
+            assert_eq!(mzd_equal(matrix, orig), 1);
+
+            mzd_row_add(matrix, 1, 2);
+            mzd_row_add_offset(matrix, 0, 1, 70);
+            mzd_combine(matrix, 3, 0, matrix, 3, 0, orig, 3, 0);
+            for j in 0..130 {
+                let expected = mzd_read_bit(orig, 1, j) ^ mzd_read_bit(orig, 2, j);
+                assert_eq!(mzd_read_bit(matrix, 2, j), expected);
+                let expected = if j < 70 {
+                    mzd_read_bit(orig, 0, j)
+                } else {
+                    mzd_read_bit(orig, 0, j) ^ mzd_read_bit(orig, 1, j)
+                };
+                assert_eq!(mzd_read_bit(matrix, 0, j), expected);
+                assert_eq!(mzd_read_bit(matrix, 3, j), 0);
+            }
+            assert_eq!(mzd_first_zero_row(matrix), 3);
+            assert_eq!(mzd_cmp(matrix, matrix), 0);
+            ptr::drop_in_place(matrix);
+            ptr::drop_in_place(orig);
+        }
+    }
+
+    #[test]
+    fn test_mzd_read_write_bits() {
+        unsafe {
+            let matrix = mzd_init(2, 128);
+            mzd_xor_bits(matrix, 1, 60, 8, 0b1011_0111);
+            assert_eq!(mzd_read_bits(matrix, 1, 60, 8), 0b1011_0111);
+            assert_eq!(mzd_read_bits_int(matrix, 1, 62, 4), 0b1101);
+            assert_eq!(mzd_read_bit(matrix, 1, 60), 1);
+            assert_eq!(mzd_read_bit(matrix, 1, 63), 0);
+            assert_eq!(mzd_read_bit(matrix, 1, 67), 1);
+            mzd_clear_bits(matrix, 1, 62, 4);
+            assert_eq!(mzd_read_bits(matrix, 1, 60, 8), 0b1011_0011);
+            assert_eq!(mzd_read_bits(matrix, 0, 0, 64), 0);
+            ptr::drop_in_place(matrix);
+        }
+    }
+
+    #[test]
+    fn test_mzd_extract_u_l() {
+        unsafe {
+            let matrix = mzd_init(10, 10);
+            mzd_randomize(matrix);
+            let u = mzd_extract_u(ptr::null_mut(), matrix);
+            let l = mzd_extract_l(ptr::null_mut(), matrix);
+            for i in 0..10 {
+                for j in 0..10 {
+                    let bit = mzd_read_bit(matrix, i, j);
+                    assert_eq!(mzd_read_bit(u, i, j), if j >= i { bit } else { 0 });
+                    assert_eq!(mzd_read_bit(l, i, j), if j <= i { bit } else { 0 });
+                }
+            }
+            mzd_set_ui(matrix, 1);
+            assert!((mzd_density(matrix, 1) - 0.1).abs() < 1e-9);
+            ptr::drop_in_place(matrix);
+            ptr::drop_in_place(u);
+            ptr::drop_in_place(l);
+        }
+    }
+
     #[test]
     fn test_mzd_read_bit() {
         for _ in 0..10 {
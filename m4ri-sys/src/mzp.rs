@@ -1,17 +1,21 @@
-/// Corresponds to the mzp.h file.
-///
-/// Some functions are missing
-use libc;
+//! Corresponds to the mzp.h file.
 
 use crate::misc::Rci;
 use crate::mzd::Mzd;
 
+/// Permutation matrices
+///
+/// Represented as a sequence of transpositions: entry `i` of `values`
+/// means that row (or column) `i` is swapped with row `values[i]`.
 #[repr(C)]
 pub struct Mzp {
-    private: [u8; 0],
+    /// The swap operations in LAPACK format
+    pub values: *mut Rci,
+    /// The length of the swap array
+    pub length: Rci,
 }
 
-extern "C" {
+extern "C-unwind" {
     /// Construct an identity permutation
     ///
     /// length: the length of the permutation
@@ -23,16 +27,18 @@ extern "C" {
     /// Create a window into the permutation
     ///
     /// Use mzp_free_window to free the window
-    pub fn mzp_init_window(p: *mut Mzp, begin: Rci, end: Rci);
+    pub fn mzp_init_window(p: *mut Mzp, begin: Rci, end: Rci) -> *mut Mzp;
 
-    /// Free a permutation window created with Mzp_init_window
-    pub fn Mzp_free_window(condemned: *mut Mzp);
+    /// Free a permutation window created with mzp_init_window
+    pub fn mzp_free_window(condemned: *mut Mzp);
 
     /// Copy permutation Q to P
     /// Target may be null
     pub fn mzp_copy(p: *mut Mzp, q: *const Mzp) -> *mut Mzp;
 
     /// Set the permutation to the identity permutation
+    ///
+    /// Only value 1 is supported
     pub fn mzp_set_ui(p: *mut Mzp, value: libc::c_uint);
 
     /// Apply the permutation P to A from the left
@@ -47,8 +53,80 @@ extern "C" {
     /// Apply the permutation P to A from the right, but transpose P
     pub fn mzd_apply_p_right_trans(a: *mut Mzd, p: *const Mzp);
 
+    /// Apply the permutation P to A from the right starting at start_row
+    ///
+    /// Only columns from start_col onwards are permuted.
+    pub fn mzd_apply_p_right_even_capped(
+        a: *mut Mzd,
+        p: *const Mzp,
+        start_row: Rci,
+        start_col: Rci,
+    );
+
+    /// Apply the permutation P^T to A from the right starting at start_row
+    ///
+    /// Only columns from start_col onwards are permuted.
+    pub fn mzd_apply_p_right_trans_even_capped(
+        a: *mut Mzd,
+        p: *const Mzp,
+        start_row: Rci,
+        start_col: Rci,
+    );
+
+    /// Apply the permutation P^T to the upper triangular matrix A from the right
+    ///
+    /// Only the upper triangular part of A is permuted.
+    pub fn mzd_apply_p_right_trans_tri(a: *mut Mzd, p: *const Mzp);
+
     /// Print the mzp
     pub fn mzp_print(p: *const Mzp);
 
-// FIXME add missing components
+    /// Rotate zero columns to the end.
+    ///
+    /// Given a matrix M with zero columns from zs up to excluding ze,
+    /// rotate the columns from ze up to excluding de to the position zs
+    /// and zero out the columns that end up behind them.
+    ///
+    /// param M Matrix
+    /// param zs Start index of the zero columns
+    /// param ze End index of the zero columns (exclusive)
+    /// param de End index of the columns to rotate (exclusive)
+    /// param zero_out Actually zero out the columns
+    pub fn mzd_col_block_rotate(
+        m: *mut Mzd,
+        zs: Rci,
+        ze: Rci,
+        de: Rci,
+        zero_out: libc::c_int,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mzd::*;
+
+    #[test]
+    fn test_mzp_window() {
+        unsafe {
+            let p = mzp_init(10);
+            assert_eq!((*p).length, 10);
+            let values = std::slice::from_raw_parts((*p).values, 10);
+            assert!(values.iter().enumerate().all(|(i, &v)| i as Rci == v));
+            let window = mzp_init_window(p, 2, 5);
+            assert_eq!((*window).length, 3);
+            assert_eq!(*(*window).values, 2);
+            mzp_free_window(window);
+
+            // swap rows 0 and 9
+            *(*p).values = 9;
+            let m = mzd_init(10, 10);
+            mzd_set_ui(m, 1);
+            mzd_apply_p_left(m, p);
+            assert_eq!(mzd_read_bit(m, 0, 9), 1);
+            assert_eq!(mzd_read_bit(m, 9, 0), 1);
+            mzd_free(m);
+            mzp_free(p);
+        }
+    }
 }
@@ -3,9 +3,8 @@
 use crate::misc::Rci;
 use crate::mzd::Mzd;
 use crate::mzp::Mzp;
-use libc;
 
-extern "C" {
+extern "C-unwind" {
 
     /// PLUQ matrix decomposition.
     ///
@@ -64,4 +63,24 @@ extern "C" {
     /// Returns Rank of A.
     pub fn mzd_ple(a: *mut Mzd, p: *mut Mzp, q: *mut Mzp, cutoff: libc::c_int) -> Rci;
 
+    /// PLUQ matrix decomposition without any checks on the input
+    ///
+    /// See `mzd_pluq()`
+    pub fn _mzd_pluq(a: *mut Mzd, p: *mut Mzp, q: *mut Mzp, cutoff: libc::c_int) -> Rci;
+
+    /// PLE matrix decomposition without any checks on the input
+    ///
+    /// See `mzd_ple()`
+    pub fn _mzd_ple(a: *mut Mzd, p: *mut Mzp, q: *mut Mzp, cutoff: libc::c_int) -> Rci;
+
+    /// PLUQ matrix decomposition (naive base case)
+    ///
+    /// See `mzd_pluq()`
+    pub fn _mzd_pluq_naive(a: *mut Mzd, p: *mut Mzp, q: *mut Mzp) -> Rci;
+
+    /// PLE matrix decomposition (naive base case)
+    ///
+    /// See `mzd_ple()`
+    pub fn _mzd_ple_naive(a: *mut Mzd, p: *mut Mzp, q: *mut Mzp) -> Rci;
+
 }
@@ -0,0 +1,34 @@
+//! Links to `ple_russian.h`
+
+use crate::misc::Rci;
+use crate::mzd::Mzd;
+use crate::mzp::Mzp;
+
+extern "C-unwind" {
+    /// PLE matrix decomposition of A using Gray codes.
+    ///
+    /// Returns (P,L,E,Q) satisfying PLE = A where P is a permutation matrix
+    /// of dimension m x m, L is m x r unit lower triangular and S is an r
+    /// x n matrix which is upper triangular except that its columns are
+    /// permuted, that is E = UQ for U r x n upper triangular and Q is a n
+    /// x n permutation matrix. The matrix L and E are stored in place over
+    /// A.
+    ///
+    /// A Matrix
+    /// P Preallocated row permutation
+    /// Q Preallocated column permutation
+    /// k Size of Gray code tables
+    ///
+    /// Returns the rank of A
+    pub fn _mzd_ple_russian(a: *mut Mzd, p: *mut Mzp, q: *mut Mzp, k: libc::c_int) -> Rci;
+
+    /// PLUQ matrix decomposition of A using Gray codes.
+    ///
+    /// A Matrix
+    /// P Preallocated row permutation
+    /// Q Preallocated column permutation
+    /// k Size of Gray code tables
+    ///
+    /// Returns the rank of A
+    pub fn _mzd_pluq_russian(a: *mut Mzd, p: *mut Mzp, q: *mut Mzp, k: libc::c_int) -> Rci;
+}
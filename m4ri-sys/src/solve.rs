@@ -6,9 +6,8 @@ use crate::misc::Rci;
 use crate::mzd::Mzd;
 use crate::mzp::Mzp;
 
-use libc;
 
-extern "C" {
+extern "C-unwind" {
     /// Solves A X = B with A and B matrices.
     ///
     /// The solution X is stored inplace on B.
@@ -59,4 +58,39 @@ extern "C" {
         cutoff: libc::c_int,
         inconsistency_check: libc::c_int,
     ) -> libc::c_int;
+
+    /// Solves (P L U Q) X = B without any checks on the input
+    ///
+    /// See `mzd_pluq_solve_left`
+    pub fn _mzd_pluq_solve_left(
+        a: *const Mzd,
+        rank: Rci,
+        p: *const Mzp,
+        q: *const Mzp,
+        b: *mut Mzd,
+        cutoff: libc::c_int,
+        inconsistency_check: libc::c_int,
+    ) -> libc::c_int;
+
+    /// Solves A X = B with A and B matrices without any checks on the input
+    ///
+    /// See `mzd_solve_left`
+    pub fn _mzd_solve_left(
+        a: *mut Mzd,
+        b: *mut Mzd,
+        cutoff: libc::c_int,
+        inconsistency_check: libc::c_int,
+    ) -> libc::c_int;
+
+    /// Solve X for A X = 0.
+    ///
+    /// If r is the rank of the nr x nc matrix A, return the nc x (nc-r)
+    /// matrix X such that A*X == 0 and that the columns of X are linearly
+    /// independent.
+    ///
+    /// param A Input matrix (overwritten).
+    /// param cutoff Minimal dimension for Strassen recursion (default: 0).
+    ///
+    /// return X, or NULL if the kernel is trivial
+    pub fn mzd_kernel_left_pluq(a: *mut Mzd, cutoff: libc::c_int) -> *mut Mzd;
 }
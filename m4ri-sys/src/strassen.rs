@@ -1,9 +1,8 @@
 //! Links to strassen.h
 
 use crate::mzd::Mzd;
-use libc;
 
-extern "C" {
+extern "C-unwind" {
 
     /// \brief Matrix multiplication via the Strassen-Winograd matrix
     /// multiplication algorithm, i.e. compute C = AB.
@@ -30,4 +29,47 @@ extern "C" {
     /// \param cutoff Minimal dimension for Strassen recursion.
     pub fn mzd_addmul(c: *mut Mzd, a: *const Mzd, b: *const Mzd, cutoff: libc::c_int) -> *mut Mzd;
 
+    /// \brief Matrix multiplication via the Strassen-Winograd matrix
+    /// multiplication algorithm without any checks on the input.
+    ///
+    /// \param C Preallocated product matrix
+    /// \param A Input matrix A
+    /// \param B Input matrix B
+    /// \param cutoff Minimal dimension for Strassen recursion.
+    pub fn _mzd_mul_even(
+        c: *mut Mzd,
+        a: *const Mzd,
+        b: *const Mzd,
+        cutoff: libc::c_int,
+    ) -> *mut Mzd;
+
+    /// \brief Matrix multiplication and in-place addition via the
+    /// Strassen-Winograd matrix multiplication algorithm without any
+    /// checks on the input.
+    ///
+    /// \param C product matrix
+    /// \param A Input matrix A
+    /// \param B Input matrix B
+    /// \param cutoff Minimal dimension for Strassen recursion.
+    pub fn _mzd_addmul_even(
+        c: *mut Mzd,
+        a: *const Mzd,
+        b: *const Mzd,
+        cutoff: libc::c_int,
+    ) -> *mut Mzd;
+
+    /// \brief Matrix multiplication and in-place addition via the
+    /// Strassen-Winograd matrix multiplication algorithm without any
+    /// checks on the input, for matrices that are not aligned.
+    ///
+    /// \param C product matrix
+    /// \param A Input matrix A
+    /// \param B Input matrix B
+    /// \param cutoff Minimal dimension for Strassen recursion.
+    pub fn _mzd_addmul(c: *mut Mzd, a: *const Mzd, b: *const Mzd, cutoff: libc::c_int)
+        -> *mut Mzd;
+
+    /// \brief Return the cutoff for Strassen recursion based on the cache size
+    pub fn _mzd_get_cutoff(a: *const Mzd, b: *const Mzd) -> libc::c_int;
+
 }
@@ -0,0 +1,111 @@
+//! Links to `triangular.h`
+//!
+//! Triangular system solving (TRSM) and triangular matrix inversion.
+//! The variants prefixed with an underscore skip the checks on the input.
+
+use crate::mzd::Mzd;
+
+extern "C-unwind" {
+    /// Solves X U = B with X and B matrices and U upper triangular.
+    ///
+    /// X is stored inplace on B.
+    ///
+    /// param U Input upper triangular matrix.
+    /// param B Input matrix, being overwritten by the solution matrix X
+    /// param cutoff Minimal dimension for Strassen recursion.
+    pub fn mzd_trsm_upper_right(u: *const Mzd, b: *mut Mzd, cutoff: libc::c_int);
+
+    /// Solves X U = B, see `mzd_trsm_upper_right`
+    pub fn _mzd_trsm_upper_right(u: *const Mzd, b: *mut Mzd, cutoff: libc::c_int);
+
+    /// Solves X L = B with X and B matrices and L lower triangular.
+    ///
+    /// X is stored inplace on B.
+    ///
+    /// param L Input lower triangular matrix.
+    /// param B Input matrix, being overwritten by the solution matrix X
+    /// param cutoff Minimal dimension for Strassen recursion.
+    pub fn mzd_trsm_lower_right(l: *const Mzd, b: *mut Mzd, cutoff: libc::c_int);
+
+    /// Solves X L = B, see `mzd_trsm_lower_right`
+    pub fn _mzd_trsm_lower_right(l: *const Mzd, b: *mut Mzd, cutoff: libc::c_int);
+
+    /// Solves L X = B with X and B matrices and L lower triangular.
+    ///
+    /// X is stored inplace on B.
+    ///
+    /// param L Input lower triangular matrix.
+    /// param B Input matrix, being overwritten by the solution matrix X
+    /// param cutoff Minimal dimension for Strassen recursion.
+    pub fn mzd_trsm_lower_left(l: *const Mzd, b: *mut Mzd, cutoff: libc::c_int);
+
+    /// Solves L X = B, see `mzd_trsm_lower_left`
+    pub fn _mzd_trsm_lower_left(l: *const Mzd, b: *mut Mzd, cutoff: libc::c_int);
+
+    /// Solves U X = B with X and B matrices and U upper triangular.
+    ///
+    /// X is stored inplace on B.
+    ///
+    /// param U Input upper triangular matrix.
+    /// param B Input matrix, being overwritten by the solution matrix X
+    /// param cutoff Minimal dimension for Strassen recursion.
+    pub fn mzd_trsm_upper_left(u: *const Mzd, b: *mut Mzd, cutoff: libc::c_int);
+
+    /// Solves U X = B, see `mzd_trsm_upper_left`
+    pub fn _mzd_trsm_upper_left(u: *const Mzd, b: *mut Mzd, cutoff: libc::c_int);
+
+    /// Invert the upper triangular matrix A by reduction to matrix multiplication.
+    ///
+    /// A is overwritten with its inverse, which is also returned.
+    pub fn mzd_trtri_upper(a: *mut Mzd) -> *mut Mzd;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mzd::*;
+    use std::ptr;
+
+    unsafe fn random_upper_triangular(n: libc::c_int) -> *mut Mzd {
+        let u = mzd_init(n, n);
+        mzd_randomize(u);
+        for i in 0..n {
+            for j in 0..=i {
+                mzd_write_bit(u, i, j, (i == j) as libc::c_int);
+            }
+        }
+        u
+    }
+
+    #[test]
+    fn test_trsm_upper_left() {
+        unsafe {
+            let u = random_upper_triangular(100);
+            let x = mzd_init(100, 30);
+            mzd_randomize(x);
+            let b = mzd_mul_naive(ptr::null_mut(), u, x);
+            mzd_trsm_upper_left(u, b, 0);
+            assert_eq!(mzd_equal(b, x), 1);
+            mzd_free(u);
+            mzd_free(x);
+            mzd_free(b);
+        }
+    }
+
+    #[test]
+    fn test_trtri_upper() {
+        unsafe {
+            let u = random_upper_triangular(70);
+            let inverse = mzd_copy(ptr::null_mut(), u);
+            mzd_trtri_upper(inverse);
+            let product = mzd_mul_naive(ptr::null_mut(), u, inverse);
+            let identity = mzd_init(70, 70);
+            mzd_set_ui(identity, 1);
+            assert_eq!(mzd_equal(product, identity), 1);
+            mzd_free(u);
+            mzd_free(inverse);
+            mzd_free(product);
+            mzd_free(identity);
+        }
+    }
+}
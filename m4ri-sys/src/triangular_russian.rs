@@ -0,0 +1,35 @@
+//! Links to `triangular_russian.h`
+//!
+//! Triangular system solving and inversion using the method of the four russians.
+
+use crate::mzd::Mzd;
+
+extern "C-unwind" {
+    /// Solves U X = B with X and B matrices and U upper triangular using
+    /// the method of the four russians.
+    ///
+    /// X is stored inplace on B.
+    ///
+    /// param U Input upper triangular matrix.
+    /// param B Input matrix, being overwritten by the solution matrix X
+    /// param k M4RI parameter, may be 0 for auto-choose
+    pub fn _mzd_trsm_upper_left_russian(u: *const Mzd, b: *mut Mzd, k: libc::c_int);
+
+    /// Solves L X = B with X and B matrices and L lower triangular using
+    /// the method of the four russians.
+    ///
+    /// X is stored inplace on B.
+    ///
+    /// param L Input lower triangular matrix.
+    /// param B Input matrix, being overwritten by the solution matrix X
+    /// param k M4RI parameter, may be 0 for auto-choose
+    pub fn _mzd_trsm_lower_left_russian(l: *const Mzd, b: *mut Mzd, k: libc::c_int);
+
+    /// Invert the upper triangular matrix A using the method of the four russians.
+    ///
+    /// A is overwritten with its inverse, which is also returned.
+    ///
+    /// param A Input upper triangular matrix
+    /// param k M4RI parameter, may be 0 for auto-choose
+    pub fn mzd_trtri_upper_russian(a: *mut Mzd, k: libc::c_int) -> *mut Mzd;
+}